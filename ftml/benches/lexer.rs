@@ -0,0 +1,79 @@
+/*
+ * benches/lexer.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Compares the hand-written lexer against the pest reference lexer.
+
+#[macro_use]
+extern crate criterion;
+extern crate ftml;
+
+#[macro_use]
+extern crate str_macro;
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use ftml::tokenizer::{tokenize, tokenize_reference};
+use std::fs;
+use std::path::Path;
+
+/// Loads every corpus file, plus all of them joined into one long page.
+fn inputs() -> Vec<(String, String)> {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut inputs = Vec::new();
+
+    for entry in fs::read_dir(directory).expect("Unable to read corpus directory") {
+        let path = entry.expect("Unable to read corpus entry").path();
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let text = fs::read_to_string(&path).expect("Unable to read corpus file");
+        inputs.push((name, text));
+    }
+
+    inputs.sort();
+
+    let combined = inputs
+        .iter()
+        .map(|(_, text)| text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+        .repeat(50);
+
+    inputs.push((str!("combined"), combined));
+    inputs
+}
+
+fn lexer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lexer");
+
+    for (name, text) in inputs() {
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("hand-written", &name),
+            &text,
+            |b, text| b.iter(|| tokenize(text)),
+        );
+        group.bench_with_input(BenchmarkId::new("pest", &name), &text, |b, text| {
+            b.iter(|| tokenize_reference(text))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, lexer);
+criterion_main!(benches);
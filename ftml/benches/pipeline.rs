@@ -0,0 +1,169 @@
+/*
+ * benches/pipeline.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Benchmarks each stage of the pipeline over the shared fixtures.
+//!
+//! Every stage is measured separately, with the inputs to that stage
+//! prepared ahead of time, so a regression can be traced to one step.
+
+#[macro_use]
+extern crate criterion;
+extern crate ftml;
+
+#[path = "../tests/fixtures/mod.rs"]
+mod fixtures;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput};
+use ftml::data::{NullPageCallbacks, PageCallbacks};
+use ftml::render::html::HtmlRender;
+use ftml::render::text::TextRender;
+use ftml::render::Render;
+use std::sync::Arc;
+
+/// Fixture text after preprocessing and include substitution.
+fn expanded_fixtures() -> Vec<(&'static str, String)> {
+    let settings = fixtures::settings();
+
+    fixtures::all()
+        .into_iter()
+        .map(|fixture| {
+            let mut text = fixture.text;
+            ftml::preprocess(&mut text);
+
+            (fixture.name, fixtures::expand_includes(&text, &settings))
+        })
+        .collect()
+}
+
+fn callbacks() -> Arc<dyn PageCallbacks> {
+    Arc::new(NullPageCallbacks {})
+}
+
+fn preprocess(c: &mut Criterion) {
+    let mut group = c.benchmark_group("preprocess");
+
+    for fixture in fixtures::all() {
+        group.throughput(Throughput::Bytes(fixture.text.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(fixture.name),
+            &fixture.text,
+            |b, text| {
+                b.iter_batched(
+                    || text.clone(),
+                    |mut text| ftml::preprocess(&mut text),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn include(c: &mut Criterion) {
+    let mut group = c.benchmark_group("include");
+    let settings = fixtures::settings();
+    let mut text = fixtures::include_hub();
+    ftml::preprocess(&mut text);
+
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("include-hub", |b| {
+        b.iter(|| fixtures::expand_includes(&text, &settings))
+    });
+
+    group.finish();
+}
+
+fn tokenize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tokenize");
+
+    for (name, text) in expanded_fixtures() {
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &text, |b, text| {
+            b.iter(|| ftml::tokenize(text))
+        });
+    }
+
+    group.finish();
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+
+    // Some worst-case fixtures take a significant fraction of a second
+    group.sample_size(10);
+    let page_info = fixtures::page_info();
+    let settings = fixtures::settings();
+
+    for (name, text) in expanded_fixtures() {
+        let tokens = ftml::tokenize(&text);
+
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            &tokens,
+            |b, tokens| {
+                b.iter(|| ftml::parse(tokens, &page_info, callbacks(), &settings))
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn render(c: &mut Criterion) {
+    let page_info = fixtures::page_info();
+    let settings = fixtures::settings();
+    let inputs = expanded_fixtures();
+
+    let trees = inputs
+        .iter()
+        .map(|(name, text)| {
+            let tokens = ftml::tokenize(text);
+            let (tree, _warnings) =
+                ftml::parse(&tokens, &page_info, callbacks(), &settings).into();
+
+            (*name, tree.to_owned())
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("render-html");
+
+    for (name, tree) in &trees {
+        group.bench_with_input(BenchmarkId::from_parameter(name), tree, |b, tree| {
+            b.iter(|| HtmlRender.render(tree, &page_info, callbacks(), &settings))
+        });
+    }
+
+    group.finish();
+
+    let mut group = c.benchmark_group("render-text");
+
+    for (name, tree) in &trees {
+        group.bench_with_input(BenchmarkId::from_parameter(name), tree, |b, tree| {
+            b.iter(|| TextRender.render(tree, &page_info, callbacks(), &settings))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, preprocess, include, tokenize, parse, render);
+criterion_main!(benches);
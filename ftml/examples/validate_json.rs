@@ -69,7 +69,7 @@ fn main() {
             }
         };
 
-        let outcome = Outcome::Success(syntax_tree);
+        let outcome = Outcome::Success(Box::new(syntax_tree));
         results.push((path, outcome));
     }
 
@@ -105,7 +105,7 @@ fn write_result(
 
 #[derive(Debug)]
 enum Outcome {
-    Success(ftml::tree::SyntaxTree<'static>),
+    Success(Box<ftml::tree::SyntaxTree<'static>>),
     Failure(String),
     Error(String),
 }
@@ -0,0 +1,202 @@
+/*
+ * batch.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rendering of many pages at once on a pool of worker threads.
+//!
+//! This runs the full pipeline (preprocessing, includes, tokenization,
+//! parsing, and rendering) for each page. All workers share a single
+//! [`IncludeCache`], so a page included by many others is only fetched once.
+
+use crate::data::{PageCallbacks, PageInfo, PageRef};
+use crate::includes::{
+    include, remove_noincludes, CachedIncluder, FetchedPage, IncludeCache, IncludeRef,
+    Includer,
+};
+use crate::parsing::parse;
+use crate::preproc::preprocess;
+use crate::render::Render;
+use crate::settings::WikitextSettings;
+use crate::tokenizer::tokenize;
+use std::borrow::Cow;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+/// How many levels of nested includes are substituted before stopping.
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// A single page to be rendered as part of a batch.
+#[derive(Debug, Clone)]
+pub struct BatchPage<'a> {
+    pub page_info: PageInfo<'a>,
+    pub text: String,
+}
+
+/// Renders every page in `pages` on up to `workers` threads.
+///
+/// Each worker gets its own includer from `make_includer`, wrapped in a
+/// [`CachedIncluder`] using `include_cache`. The cache may be reused across
+/// batches, and should be cleared if the included pages change.
+///
+/// If fetching includes fails for a page, it is rendered without
+/// substituting them. The outputs are in the same order as `pages`.
+pub fn render_batch<R, I, F>(
+    renderer: &R,
+    pages: &[BatchPage],
+    page_callbacks: Arc<dyn PageCallbacks>,
+    settings: &WikitextSettings,
+    include_cache: &IncludeCache,
+    make_includer: F,
+    workers: NonZeroUsize,
+) -> Vec<R::Output>
+where
+    R: Render + Sync,
+    R::Output: Send,
+    I: for<'t> Includer<'t>,
+    F: Fn() -> I + Sync,
+{
+    let workers = workers.get().min(pages.len()).max(1);
+    let next_page = AtomicUsize::new(0);
+
+    info!(
+        "Rendering batch of {} pages with {} workers",
+        pages.len(),
+        workers,
+    );
+
+    let mut results = thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut outputs = Vec::new();
+
+                    loop {
+                        let index = next_page.fetch_add(1, Ordering::Relaxed);
+                        let page = match pages.get(index) {
+                            Some(page) => page,
+                            None => break,
+                        };
+
+                        let includer =
+                            CachedIncluder::new(include_cache, make_includer());
+                        let output = render_page(
+                            renderer,
+                            page,
+                            Arc::clone(&page_callbacks),
+                            settings,
+                            includer,
+                        );
+
+                        outputs.push((index, output));
+                    }
+
+                    outputs
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Batch render worker panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, output)| output).collect()
+}
+
+fn render_page<R, I>(
+    renderer: &R,
+    page: &BatchPage,
+    page_callbacks: Arc<dyn PageCallbacks>,
+    settings: &WikitextSettings,
+    mut includer: I,
+) -> R::Output
+where
+    R: Render,
+    I: for<'t> Includer<'t>,
+{
+    // Substitute page inclusions, including nested ones
+    let mut text = page.text.clone();
+
+    for _ in 0..MAX_INCLUDE_DEPTH {
+        preprocess(&mut text);
+
+        let stripped = remove_noincludes(&text);
+        let result = include(&stripped, settings, BatchIncluder(&mut includer), || None);
+
+        match result {
+            Ok((output, included_pages)) => {
+                let done = included_pages.is_empty();
+                text = output;
+
+                if done {
+                    break;
+                }
+            }
+            Err(_) => {
+                warn!(
+                    "Unable to fetch included pages for '{}', rendering without them",
+                    page.page_info.page,
+                );
+                break;
+            }
+        }
+    }
+
+    let tokens = tokenize(&text);
+    let (tree, _warnings) = parse(
+        &tokens,
+        &page.page_info,
+        Arc::clone(&page_callbacks),
+        settings,
+    )
+    .into();
+
+    renderer.render(&tree, &page.page_info, page_callbacks, settings)
+}
+
+/// Adapter which gives an includer an error type that can also
+/// represent an invalid return, so [`include`] can be called generically.
+struct BatchIncluder<'a, I>(&'a mut I);
+
+impl<'a, 't, I> Includer<'t> for BatchIncluder<'a, I>
+where
+    I: Includer<'t>,
+{
+    type Error = Option<I::Error>;
+
+    #[inline]
+    fn include_pages(
+        &mut self,
+        includes: &[IncludeRef<'t>],
+    ) -> Result<Vec<FetchedPage<'t>>, Self::Error> {
+        self.0.include_pages(includes).map_err(Some)
+    }
+
+    #[inline]
+    fn no_such_include(
+        &mut self,
+        page_ref: &PageRef<'t>,
+    ) -> Result<Cow<'t, str>, Self::Error> {
+        self.0.no_such_include(page_ref).map_err(Some)
+    }
+}
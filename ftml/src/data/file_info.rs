@@ -0,0 +1,65 @@
+/*
+ * data/file_info.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::FileRef;
+use ref_map::*;
+use std::borrow::Cow;
+
+/// Information on a file referenced by `[[file]]`, as provided by the host.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct FileInfo<'a> {
+    /// The file, exactly as it was requested.
+    pub file: FileRef<'a>,
+
+    /// The size of this file, in bytes.
+    pub size: Option<u64>,
+
+    /// The MIME type of this file, such as `application/pdf`.
+    pub mime_type: Option<Cow<'a, str>>,
+
+    /// Whether this file exists.
+    ///
+    /// Links to nonexistent files are marked as missing.
+    pub exists: bool,
+}
+
+impl FileInfo<'_> {
+    /// Information for a file which does not exist.
+    pub fn missing(file: &FileRef) -> FileInfo<'static> {
+        FileInfo {
+            file: file.to_owned(),
+            size: None,
+            mime_type: None,
+            exists: false,
+        }
+    }
+
+    pub fn to_owned(&self) -> FileInfo<'static> {
+        FileInfo {
+            file: self.file.to_owned(),
+            size: self.size,
+            mime_type: self
+                .mime_type
+                .ref_map(|value| Cow::Owned(value.as_ref().to_owned())),
+            exists: self.exists,
+        }
+    }
+}
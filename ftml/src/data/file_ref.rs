@@ -0,0 +1,72 @@
+/*
+ * data/file_ref.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+
+/// Represents a reference to a file attached to a page on this site.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct FileRef<'t> {
+    /// The full name of the page the file is attached to.
+    pub page: Cow<'t, str>,
+
+    /// The name of the file.
+    pub file: Cow<'t, str>,
+}
+
+impl<'t> FileRef<'t> {
+    #[inline]
+    pub fn new<S1, S2>(page: S1, file: S2) -> Self
+    where
+        S1: Into<Cow<'t, str>>,
+        S2: Into<Cow<'t, str>>,
+    {
+        FileRef {
+            page: page.into(),
+            file: file.into(),
+        }
+    }
+
+    /// Parses a local file URL, of the form `/local--files/page/file`.
+    pub fn parse_url(url: &'t str) -> Option<Self> {
+        let path = url.strip_prefix("/local--files/")?;
+        let (page, file) = path.split_once('/')?;
+
+        if page.is_empty() || file.is_empty() || file.contains('/') {
+            return None;
+        }
+
+        Some(FileRef::new(page, file))
+    }
+
+    pub fn to_owned(&self) -> FileRef<'static> {
+        FileRef {
+            page: Cow::Owned(self.page.as_ref().to_owned()),
+            file: Cow::Owned(self.file.as_ref().to_owned()),
+        }
+    }
+}
+
+impl Display for FileRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", &self.page, &self.file)
+    }
+}
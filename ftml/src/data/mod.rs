@@ -21,12 +21,18 @@
 //! Module for POD (plain old data) structs.
 
 mod backlinks;
+mod file_info;
+mod file_ref;
 mod page_callbacks;
 mod page_info;
 mod page_ref;
+mod user_info;
 
 pub use self::backlinks::Backlinks;
+pub use self::file_info::FileInfo;
+pub use self::file_ref::FileRef;
 pub use self::page_callbacks::{ExpressionResult, NullPageCallbacks, PageCallbacks};
 pub use self::page_info::PageInfo;
 pub use self::page_info::PartialPageInfo;
 pub use self::page_ref::{PageRef, PageRefParseError};
+pub use self::user_info::UserInfo;
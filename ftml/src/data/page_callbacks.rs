@@ -4,7 +4,10 @@ use std::fmt::{Debug, Formatter, Result};
 use wikidot_normalize::normalize;
 
 use super::page_info::PartialPageInfo;
-use super::PageRef;
+use super::user_info::UserInfo;
+use super::{FileInfo, FileRef, PageRef};
+use crate::render::ModuleRenderMode;
+use crate::tree::GallerySize;
 
 #[derive(Debug)]
 pub enum ExpressionResult<'t> {
@@ -27,21 +30,64 @@ impl<'t> ToString for ExpressionResult<'t> {
     }
 }
 
-pub trait PageCallbacks: Debug {
+/// Hooks into the host application, used during parsing and rendering.
+///
+/// Implementations must be thread-safe, since the same callbacks
+/// may be shared between renders running on different threads.
+pub trait PageCallbacks: Debug + Send + Sync {
     fn module_has_body(&self, module_name: Cow<str>) -> bool;
     fn render_module<'a>(
         &self,
         module_name: Cow<str>,
         params: HashMap<Cow<str>, Cow<str>>,
         body: Cow<str>,
+        mode: ModuleRenderMode,
     ) -> Cow<'static, str>;
-    fn render_user<'a>(&self, user: Cow<str>, avatar: bool) -> Cow<'static, str>;
-    fn get_i18n_message<'a>(&self, message_id: Cow<str>) -> Cow<'static, str>;
+
+    /// Overrides a message shown in rendered output, for the given page language.
+    ///
+    /// Returning `None` uses the built-in message, see [`crate::i18n`].
+    fn get_i18n_message<'a>(
+        &self,
+        _language: Cow<str>,
+        _message_id: Cow<str>,
+    ) -> Option<Cow<'static, str>> {
+        None
+    }
+
     fn get_html_injected_code<'a>(&self, html_id: Cow<str>) -> Cow<'static, str>;
     fn get_page_info<'a>(
         &self,
         page_refs: &Vec<PageRef<'a>>,
     ) -> Vec<PartialPageInfo<'static>>;
+    fn get_user_info<'a>(&self, names: &[Cow<'a, str>]) -> Vec<UserInfo<'static>>;
+
+    /// Lists the names of the files attached to the given page.
+    ///
+    /// This is used by `[[gallery]]` blocks without a body.
+    fn list_page_files<'a>(&self, _page_ref: &PageRef<'a>) -> Vec<Cow<'static, str>> {
+        Vec::new()
+    }
+
+    /// Gets the size, type, and existence of the given attached files.
+    ///
+    /// This is used to annotate `[[file]]` links. Files not returned
+    /// are linked without any additional information.
+    fn get_file_info<'a>(&self, _files: &[FileRef<'a>]) -> Vec<FileInfo<'static>> {
+        Vec::new()
+    }
+
+    /// Gets the URL of a thumbnail of the given size for an image.
+    ///
+    /// Returning `None` uses the full image instead.
+    fn get_thumbnail_url<'a>(
+        &self,
+        _image_url: Cow<str>,
+        _size: GallerySize,
+    ) -> Option<Cow<'static, str>> {
+        None
+    }
+
     fn evaluate_expression<'a>(&self, expression: Cow<str>) -> ExpressionResult<'static>;
     fn normalize_page_name<'a>(&self, full_name: Cow<str>) -> Cow<'static, str>;
 }
@@ -58,34 +104,12 @@ impl PageCallbacks for NullPageCallbacks {
         module_name: Cow<str>,
         _params: HashMap<Cow<str>, Cow<str>>,
         _body: Cow<str>,
+        mode: ModuleRenderMode,
     ) -> Cow<'static, str> {
-        return Cow::from(format!("NullModule[{module_name}]"));
-    }
-
-    fn render_user<'a>(&self, user: Cow<str>, _avatar: bool) -> Cow<'static, str> {
-        return Cow::from(format!("NullUser[{user}]"));
-    }
-
-    fn get_i18n_message<'a>(&self, message_id: Cow<str>) -> Cow<'static, str> {
-        let message_id = message_id.into_owned();
-
-        let s = match message_id.as_str() {
-            "button-copy-clipboard" => "Copy to Clipboard",
-            "collapsible-open" => "+ open block",
-            "collapsible-hide" => "- hide block",
-            "table-of-contents" => "Table of Contents",
-            "toc-open" => "Unfold",
-            "toc-close" => "Fold",
-            "footnote" => "Footnote",
-            "footnote-block-title" => "Footnotes",
-            "image-context-bad" => "No images in this context",
-            _ => {
-                error!("Unknown message requested (key {message_id})");
-                "?"
-            }
-        };
-
-        Cow::from(s)
+        match mode {
+            ModuleRenderMode::Html => Cow::from(format!("NullModule[{module_name}]")),
+            ModuleRenderMode::Text => Cow::from(format!("[module {module_name}]")),
+        }
     }
 
     fn get_html_injected_code<'a>(&self, _html_id: Cow<str>) -> Cow<'static, str> {
@@ -106,6 +130,10 @@ impl PageCallbacks for NullPageCallbacks {
             .collect();
     }
 
+    fn get_user_info<'a>(&self, names: &[Cow<'a, str>]) -> Vec<UserInfo<'static>> {
+        names.iter().map(|name| UserInfo::missing(name)).collect()
+    }
+
     fn evaluate_expression<'a>(
         &self,
         _expression: Cow<str>,
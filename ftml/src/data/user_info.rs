@@ -0,0 +1,80 @@
+/*
+ * data/user_info.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use ref_map::*;
+use std::borrow::Cow;
+
+/// Information on a user referenced by `[[user]]`, as provided by the host.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct UserInfo<'a> {
+    /// The username, exactly as it was requested.
+    pub name: Cow<'a, str>,
+
+    /// The host's ID for this user, if any.
+    pub id: Option<i64>,
+
+    /// The name to show for this user.
+    ///
+    /// If `None`, then the username is used instead.
+    pub display_name: Option<Cow<'a, str>>,
+
+    /// A link to this user's profile page.
+    pub profile_url: Option<Cow<'a, str>>,
+
+    /// A link to this user's avatar image, used in `[[*user]]`.
+    pub avatar_url: Option<Cow<'a, str>>,
+
+    /// Whether this user exists.
+    ///
+    /// Deleted and nonexistent users are shown as a deleted account.
+    pub exists: bool,
+}
+
+impl UserInfo<'_> {
+    /// Information for a user which does not exist.
+    pub fn missing(name: &str) -> UserInfo<'static> {
+        UserInfo {
+            name: Cow::Owned(str!(name)),
+            id: None,
+            display_name: None,
+            profile_url: None,
+            avatar_url: None,
+            exists: false,
+        }
+    }
+
+    pub fn to_owned(&self) -> UserInfo<'static> {
+        macro_rules! owned {
+            ($value:expr) => {
+                Cow::Owned($value.as_ref().to_owned())
+            };
+        }
+
+        UserInfo {
+            name: owned!(self.name),
+            id: self.id,
+            display_name: self.display_name.ref_map(|value| owned!(value)),
+            profile_url: self.profile_url.ref_map(|value| owned!(value)),
+            avatar_url: self.avatar_url.ref_map(|value| owned!(value)),
+            exists: self.exists,
+        }
+    }
+}
@@ -0,0 +1,41 @@
+/*
+ * i18n/en.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::Catalog;
+
+pub const MESSAGES: Catalog = &[
+    ("button-copy-clipboard", "Copy to Clipboard"),
+    ("button-submit", "Submit"),
+    ("button-reset", "Reset"),
+    ("collapsible-open", "+ open block"),
+    ("collapsible-hide", "- hide block"),
+    ("table-of-contents", "Table of Contents"),
+    ("toc-open", "Unfold"),
+    ("toc-close", "Fold"),
+    ("footnote", "Footnote"),
+    ("footnote-block-title", "Footnotes"),
+    ("note-info", "Note"),
+    ("note-warning", "Warning"),
+    ("note-danger", "Danger"),
+    ("note-tip", "Tip"),
+    ("bibliography-block-title", "Bibliography"),
+    ("image-context-bad", "No images in this context"),
+    ("user-deleted", "(account deleted)"),
+];
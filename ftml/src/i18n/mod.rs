@@ -0,0 +1,87 @@
+/*
+ * i18n/mod.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Built-in catalogs for messages shown in rendered output.
+//!
+//! Catalogs are chosen by the page's language (see `PageInfo::language`),
+//! using only its primary subtag, so `ru-RU` uses the Russian catalog.
+//! The `default` language, and any without a catalog, use English.
+//!
+//! Hosts may override individual messages via `PageCallbacks::get_i18n_message`.
+
+mod en;
+mod ru;
+
+/// A list of message IDs and their text.
+pub type Catalog = &'static [(&'static str, &'static str)];
+
+/// The languages which have a built-in catalog.
+pub const LANGUAGES: [&str; 2] = ["en", "ru"];
+
+/// The languages which write numbers with a decimal comma, such as `1 234,5`.
+///
+/// Only the primary subtag is listed. All others use a decimal point.
+const DECIMAL_COMMA_LANGUAGES: [&str; 18] = [
+    "bg", "cs", "da", "de", "el", "es", "fi", "fr", "id", "it", "nl", "no", "pl", "pt",
+    "ro", "ru", "sv", "uk",
+];
+
+/// Gets the character separating the fractional part of numbers in this language.
+pub fn decimal_separator(language: &str) -> char {
+    let primary = language.split(['-', '_']).next().unwrap_or("");
+
+    if DECIMAL_COMMA_LANGUAGES
+        .iter()
+        .any(|comma_language| primary.eq_ignore_ascii_case(comma_language))
+    {
+        ','
+    } else {
+        '.'
+    }
+}
+
+/// Gets the catalog for the given language, if there is one.
+pub fn catalog(language: &str) -> Option<Catalog> {
+    let primary = language.split(['-', '_']).next().unwrap_or("");
+
+    if primary.eq_ignore_ascii_case("en") {
+        Some(self::en::MESSAGES)
+    } else if primary.eq_ignore_ascii_case("ru") {
+        Some(self::ru::MESSAGES)
+    } else {
+        None
+    }
+}
+
+/// Gets the given message in this language, falling back to English.
+///
+/// Returns `None` if the message ID is not known.
+pub fn get_message(language: &str, message_id: &str) -> Option<&'static str> {
+    let lookup = |catalog: Catalog| {
+        catalog
+            .iter()
+            .find(|(id, _)| *id == message_id)
+            .map(|(_, message)| *message)
+    };
+
+    catalog(language)
+        .and_then(lookup)
+        .or_else(|| lookup(self::en::MESSAGES))
+}
@@ -0,0 +1,138 @@
+/*
+ * includes/includer/cached.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A cache of fetched page sources, shareable between threads.
+///
+/// Entries are keyed on the page reference only, since variable
+/// substitution happens after the page has been fetched.
+/// A page which does not exist is cached as `None`.
+#[derive(Debug, Default)]
+pub struct IncludeCache {
+    pages: Mutex<HashMap<PageRef<'static>, Option<String>>>,
+}
+
+impl IncludeCache {
+    #[inline]
+    pub fn new() -> Self {
+        IncludeCache::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.pages.lock().unwrap().clear();
+    }
+}
+
+/// An includer which consults an [`IncludeCache`] before
+/// falling back to the wrapped includer.
+///
+/// Only pages missing from the cache are requested from the
+/// inner includer, and its results are stored for later lookups.
+#[derive(Debug)]
+pub struct CachedIncluder<'c, I> {
+    cache: &'c IncludeCache,
+    inner: I,
+}
+
+impl<'c, I> CachedIncluder<'c, I> {
+    #[inline]
+    pub fn new(cache: &'c IncludeCache, inner: I) -> Self {
+        CachedIncluder { cache, inner }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<'c, 't, I> Includer<'t> for CachedIncluder<'c, I>
+where
+    I: Includer<'t>,
+{
+    type Error = I::Error;
+
+    fn include_pages(
+        &mut self,
+        includes: &[IncludeRef<'t>],
+    ) -> Result<Vec<FetchedPage<'t>>, I::Error> {
+        // Determine which pages still need to be fetched.
+        //
+        // The lock is not held while calling into the inner includer,
+        // so two threads may occasionally fetch the same page.
+        // This is harmless, the later result simply replaces the earlier.
+        let missing = {
+            let pages = self.cache.pages.lock().unwrap();
+
+            includes
+                .iter()
+                .filter(|include| !pages.contains_key(include.page_ref()))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        if !missing.is_empty() {
+            debug!("Fetching {} uncached included pages", missing.len());
+
+            let fetched = self.inner.include_pages(&missing)?;
+            let mut pages = self.cache.pages.lock().unwrap();
+
+            for FetchedPage { page_ref, content } in fetched {
+                let content = content.map(|content| content.into_owned());
+                pages.insert(page_ref.to_owned(), content);
+            }
+        }
+
+        // Produce results in the same order as requested
+        let pages = self.cache.pages.lock().unwrap();
+
+        Ok(includes
+            .iter()
+            .map(|include| {
+                let page_ref = include.page_ref();
+                let content = pages.get(page_ref).cloned().flatten().map(Cow::Owned);
+
+                FetchedPage {
+                    page_ref: page_ref.clone(),
+                    content,
+                }
+            })
+            .collect())
+    }
+
+    #[inline]
+    fn no_such_include(
+        &mut self,
+        page_ref: &PageRef<'t>,
+    ) -> Result<Cow<'t, str>, I::Error> {
+        self.inner.no_such_include(page_ref)
+    }
+}
@@ -18,6 +18,7 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+mod cached;
 mod debug;
 mod null;
 
@@ -30,6 +31,7 @@ mod prelude {
 use crate::includes::{IncludeRef, PageRef};
 use std::borrow::Cow;
 
+pub use self::cached::{CachedIncluder, IncludeCache};
 pub use self::debug::DebugIncluder;
 pub use self::null::NullIncluder;
 
@@ -29,7 +29,9 @@ mod includer;
 mod parse;
 
 pub use self::include_ref::IncludeRef;
-pub use self::includer::{DebugIncluder, FetchedPage, Includer, NullIncluder};
+pub use self::includer::{
+    CachedIncluder, DebugIncluder, FetchedPage, IncludeCache, Includer, NullIncluder,
+};
 
 use self::parse::parse_include_block;
 use crate::data::PageRef;
@@ -0,0 +1,96 @@
+/*
+ * latex/error.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+/// An error in LaTeX source, pointing to the token which caused it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatexError {
+    pub kind: LatexErrorKind,
+
+    /// The byte range of the failing token in the original source.
+    ///
+    /// For tokens produced by a macro, this is the macro invocation.
+    pub span: Range<usize>,
+}
+
+impl LatexError {
+    #[inline]
+    pub fn new(kind: LatexErrorKind, span: Range<usize>) -> Self {
+        LatexError { kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LatexErrorKind {
+    UnknownCommand(String),
+    UnknownEnvironment(String),
+    MismatchedEnvironment { expected: String, actual: String },
+    UnexpectedToken(String),
+    UnclosedGroup,
+    UnclosedEnvironment(String),
+    MissingArgument(String),
+    DoubleScript,
+    InvalidDefinition,
+    InvalidParameter,
+    MacroDepthExceeded,
+    NestingDepthExceeded,
+}
+
+impl Display for LatexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            LatexErrorKind::UnknownCommand(ref name) => {
+                write!(f, "Unknown command \\{name}")
+            }
+            LatexErrorKind::UnknownEnvironment(ref name) => {
+                write!(f, "Unknown environment '{name}'")
+            }
+            LatexErrorKind::MismatchedEnvironment {
+                ref expected,
+                ref actual,
+            } => {
+                write!(f, "Expected \\end{{{expected}}}, found \\end{{{actual}}}")
+            }
+            LatexErrorKind::UnexpectedToken(ref token) => {
+                write!(f, "Unexpected '{token}'")
+            }
+            LatexErrorKind::UnclosedGroup => write!(f, "Group is never closed"),
+            LatexErrorKind::UnclosedEnvironment(ref name) => {
+                write!(f, "Environment '{name}' is never closed")
+            }
+            LatexErrorKind::MissingArgument(ref command) => {
+                write!(f, "Missing argument for {command}")
+            }
+            LatexErrorKind::DoubleScript => write!(f, "Double superscript or subscript"),
+            LatexErrorKind::InvalidDefinition => write!(f, "Invalid macro definition"),
+            LatexErrorKind::InvalidParameter => {
+                write!(f, "Macro parameter is out of range")
+            }
+            LatexErrorKind::MacroDepthExceeded => {
+                write!(f, "Macros expand too much, is a macro recursive?")
+            }
+            LatexErrorKind::NestingDepthExceeded => {
+                write!(f, "Formula is nested too deeply")
+            }
+        }
+    }
+}
@@ -0,0 +1,288 @@
+/*
+ * latex/macros.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Support for page-level LaTeX macros.
+//!
+//! Macros are defined with `\newcommand`, `\renewcommand` or `\def`,
+//! and apply to everything which comes after them on the page.
+
+use super::error::{LatexError, LatexErrorKind};
+use super::token::{tokenize, Token, TokenKind};
+use std::collections::{HashMap, VecDeque};
+
+/// How many macros a single formula may expand, which catches recursive definitions.
+const MAX_EXPANSIONS: usize = 1000;
+
+/// How many times larger than its source a formula may grow through expansion.
+///
+/// Arguments can be repeated in a macro body, so a handful of expansions
+/// can still produce an exponential number of tokens.
+const MAX_EXPANSION_FACTOR: usize = 16;
+
+/// The fewest tokens a formula may expand to, so short formulas can still use macros.
+const MIN_EXPANSION_TOKENS: usize = 4096;
+
+#[derive(Debug, Clone)]
+struct Macro {
+    parameters: usize,
+    body: Vec<Token>,
+}
+
+/// The set of macros defined so far while rendering a page.
+#[derive(Debug, Clone, Default)]
+pub struct LatexMacros {
+    macros: HashMap<String, Macro>,
+}
+
+impl LatexMacros {
+    #[inline]
+    pub fn new() -> Self {
+        LatexMacros::default()
+    }
+
+    /// Tokenizes the given source and expands any macros in it.
+    ///
+    /// Definitions within the source are added to this set, and are
+    /// removed from the output. They only apply to what comes after them.
+    pub fn expand(&mut self, source: &str) -> Result<Vec<Token>, LatexError> {
+        let mut input = VecDeque::from(tokenize(source));
+        let mut output = Vec::new();
+        let mut expansions = 0;
+        let max_tokens = (input.len() * MAX_EXPANSION_FACTOR).max(MIN_EXPANSION_TOKENS);
+
+        while let Some(token) = input.pop_front() {
+            match token.command() {
+                Some("newcommand" | "renewcommand" | "def") => {
+                    let (name, definition) = read_definition(&token, &mut input)?;
+                    self.macros.insert(name, definition);
+                }
+                Some(name) if self.macros.contains_key(name) => {
+                    expansions += 1;
+                    if expansions > MAX_EXPANSIONS {
+                        return Err(LatexError::new(
+                            LatexErrorKind::MacroDepthExceeded,
+                            token.span,
+                        ));
+                    }
+
+                    let definition = &self.macros[name];
+                    let mut arguments = Vec::with_capacity(definition.parameters);
+
+                    for _ in 0..definition.parameters {
+                        match read_argument(&mut input) {
+                            Some(argument) => arguments.push(argument),
+                            None => {
+                                return Err(LatexError::new(
+                                    LatexErrorKind::MissingArgument(token.text),
+                                    token.span,
+                                ))
+                            }
+                        }
+                    }
+
+                    // Check the size before substituting, so nothing huge is allocated
+                    let size = definition
+                        .body
+                        .iter()
+                        .map(|body_token| match body_token.parameter() {
+                            Some(index) => arguments[index - 1].len(),
+                            None => 1,
+                        })
+                        .sum::<usize>();
+
+                    if output.len() + input.len() + size > max_tokens {
+                        return Err(LatexError::new(
+                            LatexErrorKind::MacroDepthExceeded,
+                            token.span,
+                        ));
+                    }
+
+                    // Substitute the arguments, then scan the result again
+                    let mut replacement = Vec::with_capacity(size);
+
+                    for body_token in &definition.body {
+                        match body_token.parameter() {
+                            Some(index) => {
+                                replacement.extend_from_slice(&arguments[index - 1]);
+                            }
+                            None => replacement.push(Token {
+                                span: token.span.clone(),
+                                ..body_token.clone()
+                            }),
+                        }
+                    }
+
+                    for token in replacement.into_iter().rev() {
+                        input.push_front(token);
+                    }
+                }
+                _ => output.push(token),
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// Determines if this source has nothing but macro definitions.
+///
+/// Such a block is a preamble for the rest of the page, and is not displayed.
+pub fn is_definitions_only(source: &str) -> bool {
+    match LatexMacros::new().expand(source) {
+        Ok(tokens) => tokens.iter().all(|token| token.kind == TokenKind::Space),
+        Err(_) => false,
+    }
+}
+
+/// Converts tokens back into LaTeX source.
+#[cfg(feature = "mathml")]
+pub fn to_source(tokens: &[Token]) -> String {
+    let mut source = String::new();
+    let mut previous: Option<&Token> = None;
+
+    for token in tokens {
+        // Keep a command from running into letters after it
+        if let Some(previous) = previous {
+            let needs_space = previous.kind == TokenKind::Command
+                && previous.text.ends_with(|c: char| c.is_ascii_alphabetic())
+                && token.kind == TokenKind::Letter;
+
+            if needs_space {
+                source.push(' ');
+            }
+        }
+
+        source.push_str(&token.text);
+        previous = Some(token);
+    }
+
+    source
+}
+
+fn skip_spaces(input: &mut VecDeque<Token>) {
+    while let Some(Token {
+        kind: TokenKind::Space,
+        ..
+    }) = input.front()
+    {
+        input.pop_front();
+    }
+}
+
+/// Reads one argument, either a group (without its braces) or a single token.
+fn read_argument(input: &mut VecDeque<Token>) -> Option<Vec<Token>> {
+    skip_spaces(input);
+
+    let token = input.pop_front()?;
+    match token.kind {
+        TokenKind::OpenGroup => {
+            let mut tokens = Vec::new();
+            let mut depth = 0;
+
+            loop {
+                let token = input.pop_front()?;
+                match token.kind {
+                    TokenKind::OpenGroup => depth += 1,
+                    TokenKind::CloseGroup if depth == 0 => return Some(tokens),
+                    TokenKind::CloseGroup => depth -= 1,
+                    _ => (),
+                }
+
+                tokens.push(token);
+            }
+        }
+        TokenKind::CloseGroup => None,
+        _ => Some(vec![token]),
+    }
+}
+
+fn read_definition(
+    command: &Token,
+    input: &mut VecDeque<Token>,
+) -> Result<(String, Macro), LatexError> {
+    let invalid =
+        || LatexError::new(LatexErrorKind::InvalidDefinition, command.span.clone());
+
+    // Name of the macro, which may be in braces for \newcommand
+    let name = match read_argument(input).as_deref() {
+        Some([token]) if token.kind == TokenKind::Command => str!(&token.text[1..]),
+        _ => return Err(invalid()),
+    };
+
+    let parameters = if command.is_command("def") {
+        // \def\name#1#2{...}
+        let mut parameters = 0;
+
+        while let Some(token) = input.front() {
+            if token.parameter() != Some(parameters + 1) {
+                break;
+            }
+
+            parameters += 1;
+            input.pop_front();
+        }
+
+        parameters
+    } else {
+        // \newcommand{\name}[2]{...}
+        skip_spaces(input);
+
+        match input.front() {
+            Some(token) if token.text == "[" => {
+                input.pop_front();
+
+                let mut count = String::new();
+                loop {
+                    match input.pop_front() {
+                        Some(token) if token.text == "]" => break,
+                        Some(token) if token.kind == TokenKind::Digit => {
+                            count.push_str(&token.text);
+                        }
+                        _ => return Err(invalid()),
+                    }
+                }
+
+                count.parse().map_err(|_| invalid())?
+            }
+            _ => 0,
+        }
+    };
+
+    skip_spaces(input);
+    if input.front().map(|token| token.kind) != Some(TokenKind::OpenGroup) {
+        return Err(invalid());
+    }
+
+    let body = read_argument(input).ok_or_else(invalid)?;
+
+    // All parameters used in the body must exist
+    for token in &body {
+        if let Some(index) = token.parameter() {
+            if index == 0 || index > parameters {
+                return Err(LatexError::new(
+                    LatexErrorKind::InvalidParameter,
+                    token.span.clone(),
+                ));
+            }
+        }
+    }
+
+    Ok((name, Macro { parameters, body }))
+}
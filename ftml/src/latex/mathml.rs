@@ -0,0 +1,236 @@
+/*
+ * latex/mathml.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Renders parsed LaTeX as MathML.
+//!
+//! This is used when the `mathml` feature is disabled, or when
+//! `latex2mathml` is unable to handle the given source.
+
+use super::parse::{Environment, MathNode, MAX_NODE_DEPTH};
+use super::symbols::{function_has_limits, SymbolKind};
+use crate::render::html::escape;
+
+/// Renders a complete `<math>` element into the buffer.
+///
+/// The alternate text is a linearization of the formula, for accessibility.
+pub fn render_mathml(buffer: &mut String, node: &MathNode, block: bool, alt_text: &str) {
+    buffer.push_str("<math xmlns=\"http://www.w3.org/1998/Math/MathML\" display=\"");
+    buffer.push_str(if block { "block" } else { "inline" });
+    buffer.push_str("\" alttext=\"");
+    escape(buffer, alt_text);
+    buffer.push_str("\">");
+    render_node(buffer, node, None, 0);
+    buffer.push_str("</math>");
+}
+
+fn render_node(
+    buffer: &mut String,
+    node: &MathNode,
+    variant: Option<&str>,
+    depth: usize,
+) {
+    // The parser limits nesting, but don't rely on that to protect the stack
+    if depth > MAX_NODE_DEPTH {
+        buffer.push_str("<merror><mtext>…</mtext></merror>");
+        return;
+    }
+
+    match node {
+        MathNode::Identifier { text, .. } => {
+            // Capital Greek letters are upright
+            let upright = text.chars().all(|c| ('Α'..='Ω').contains(&c));
+            let variant = variant.or(if upright { Some("normal") } else { None });
+
+            render_token(buffer, "mi", text, variant);
+        }
+        MathNode::Number(text) => render_token(buffer, "mn", text, variant),
+        MathNode::Operator { text, .. } => render_token(buffer, "mo", text, None),
+        MathNode::Function(name) => render_token(buffer, "mi", name, None),
+        MathNode::Text(text) => render_token(buffer, "mtext", text, None),
+        MathNode::Space(width) => {
+            buffer.push_str("<mspace width=\"");
+            buffer.push_str(width);
+            buffer.push_str("\"/>");
+        }
+        MathNode::Row(nodes) => {
+            buffer.push_str("<mrow>");
+            for node in nodes {
+                render_node(buffer, node, variant, depth + 1);
+            }
+            buffer.push_str("</mrow>");
+        }
+        MathNode::Fraction {
+            numerator,
+            denominator,
+            binomial,
+        } => {
+            if *binomial {
+                buffer.push_str("<mrow><mo>(</mo><mfrac linethickness=\"0\">");
+            } else {
+                buffer.push_str("<mfrac>");
+            }
+
+            render_node(buffer, numerator, variant, depth + 1);
+            render_node(buffer, denominator, variant, depth + 1);
+
+            if *binomial {
+                buffer.push_str("</mfrac><mo>)</mo></mrow>");
+            } else {
+                buffer.push_str("</mfrac>");
+            }
+        }
+        MathNode::Root { radicand, index } => match index {
+            Some(index) => {
+                buffer.push_str("<mroot>");
+                render_node(buffer, radicand, variant, depth + 1);
+                render_node(buffer, index, variant, depth + 1);
+                buffer.push_str("</mroot>");
+            }
+            None => {
+                buffer.push_str("<msqrt>");
+                render_node(buffer, radicand, variant, depth + 1);
+                buffer.push_str("</msqrt>");
+            }
+        },
+        MathNode::Scripts {
+            base,
+            subscript,
+            superscript,
+        } => {
+            let limits = match **base {
+                MathNode::Operator { kind, .. } => kind == SymbolKind::LargeOperator,
+                MathNode::Function(ref name) => function_has_limits(name),
+                _ => false,
+            };
+
+            let tag = match (limits, subscript.is_some(), superscript.is_some()) {
+                (true, true, true) => "munderover",
+                (true, true, false) => "munder",
+                (true, false, _) => "mover",
+                (false, true, true) => "msubsup",
+                (false, true, false) => "msub",
+                (false, false, _) => "msup",
+            };
+
+            str_write!(buffer, "<{tag}>");
+            render_node(buffer, base, variant, depth + 1);
+
+            if let Some(subscript) = subscript {
+                render_node(buffer, subscript, variant, depth + 1);
+            }
+
+            if let Some(superscript) = superscript {
+                render_node(buffer, superscript, variant, depth + 1);
+            }
+
+            str_write!(buffer, "</{tag}>");
+        }
+        MathNode::Accent {
+            base,
+            accent,
+            under,
+            ..
+        } => {
+            let (tag, attribute) = if *under {
+                ("munder", "accentunder")
+            } else {
+                ("mover", "accent")
+            };
+
+            str_write!(buffer, "<{tag} {attribute}=\"true\">");
+            render_node(buffer, base, variant, depth + 1);
+            render_token(buffer, "mo", accent, None);
+            str_write!(buffer, "</{tag}>");
+        }
+        MathNode::Style { variant, body } => {
+            render_node(buffer, body, Some(variant), depth + 1)
+        }
+        MathNode::Fenced { open, close, body } => {
+            buffer.push_str("<mrow>");
+            render_fence(buffer, open);
+            render_node(buffer, body, variant, depth + 1);
+            render_fence(buffer, close);
+            buffer.push_str("</mrow>");
+        }
+        MathNode::Table { environment, rows } => {
+            let delimiters = environment.delimiters();
+
+            if let Some((open, _)) = delimiters {
+                buffer.push_str("<mrow>");
+                render_fence(buffer, open);
+            }
+
+            match environment {
+                Environment::Cases => buffer.push_str("<mtable columnalign=\"left\">"),
+                Environment::Align | Environment::Gather => {
+                    buffer.push_str("<mtable displaystyle=\"true\">")
+                }
+                _ => buffer.push_str("<mtable>"),
+            }
+
+            for row in rows {
+                buffer.push_str("<mtr>");
+
+                for (column, cell) in row.iter().enumerate() {
+                    // Aligned equations alternate between right and left
+                    match environment {
+                        Environment::Align if column % 2 == 0 => {
+                            buffer.push_str("<mtd columnalign=\"right\">")
+                        }
+                        Environment::Align => {
+                            buffer.push_str("<mtd columnalign=\"left\">")
+                        }
+                        _ => buffer.push_str("<mtd>"),
+                    }
+
+                    render_node(buffer, cell, variant, depth + 1);
+                    buffer.push_str("</mtd>");
+                }
+
+                buffer.push_str("</mtr>");
+            }
+
+            buffer.push_str("</mtable>");
+
+            if let Some((_, close)) = delimiters {
+                render_fence(buffer, close);
+                buffer.push_str("</mrow>");
+            }
+        }
+    }
+}
+
+fn render_token(buffer: &mut String, tag: &str, text: &str, variant: Option<&str>) {
+    match variant {
+        Some(variant) => str_write!(buffer, "<{tag} mathvariant=\"{variant}\">"),
+        None => str_write!(buffer, "<{tag}>"),
+    }
+
+    escape(buffer, text);
+    str_write!(buffer, "</{tag}>");
+}
+
+fn render_fence(buffer: &mut String, fence: &str) {
+    if !fence.is_empty() {
+        buffer.push_str("<mo fence=\"true\" stretchy=\"true\">");
+        escape(buffer, fence);
+        buffer.push_str("</mo>");
+    }
+}
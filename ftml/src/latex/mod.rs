@@ -0,0 +1,47 @@
+/*
+ * latex/mod.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Support for LaTeX math, shared between the renderers.
+//!
+//! This handles page-level macros, and parses a practical subset of
+//! LaTeX math, which can be rendered as MathML without `latex2mathml`
+//! or read aloud in text output.
+
+mod error;
+mod macros;
+mod parse;
+mod speech;
+mod symbols;
+mod token;
+
+#[cfg(feature = "html")]
+mod mathml;
+
+pub use self::error::LatexError;
+pub use self::macros::{is_definitions_only, LatexMacros};
+pub use self::parse::parse;
+pub use self::speech::linearize;
+pub use self::token::Token;
+
+#[cfg(feature = "html")]
+pub use self::mathml::render_mathml;
+
+#[cfg(feature = "mathml")]
+pub use self::macros::to_source;
@@ -0,0 +1,650 @@
+/*
+ * latex/parse.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Parses LaTeX tokens into a tree of math elements.
+
+use super::error::{LatexError, LatexErrorKind};
+use super::symbols::*;
+use super::token::{Token, TokenKind};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MathNode {
+    Identifier {
+        text: String,
+        speech: Option<&'static str>,
+    },
+    Number(String),
+    Operator {
+        text: String,
+        speech: Option<&'static str>,
+        kind: SymbolKind,
+    },
+    Function(String),
+    Text(String),
+    Space(&'static str),
+    Row(Vec<MathNode>),
+    Fraction {
+        numerator: Box<MathNode>,
+        denominator: Box<MathNode>,
+        binomial: bool,
+    },
+    Root {
+        radicand: Box<MathNode>,
+        index: Option<Box<MathNode>>,
+    },
+    Scripts {
+        base: Box<MathNode>,
+        subscript: Option<Box<MathNode>>,
+        superscript: Option<Box<MathNode>>,
+    },
+    Accent {
+        base: Box<MathNode>,
+        accent: &'static str,
+        speech: &'static str,
+        under: bool,
+    },
+    Style {
+        variant: &'static str,
+        body: Box<MathNode>,
+    },
+    Fenced {
+        open: String,
+        close: String,
+        body: Box<MathNode>,
+    },
+    Table {
+        environment: Environment,
+        rows: Vec<Vec<MathNode>>,
+    },
+}
+
+impl MathNode {
+    /// Whether this node is a single item, which needs no grouping when read aloud.
+    pub fn is_simple(&self) -> bool {
+        match self {
+            MathNode::Identifier { .. }
+            | MathNode::Number(_)
+            | MathNode::Operator { .. }
+            | MathNode::Function(_)
+            | MathNode::Text(_) => true,
+            MathNode::Style { body, .. } => body.is_simple(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Environment {
+    Matrix,
+    ParenMatrix,
+    BracketMatrix,
+    BraceMatrix,
+    BarMatrix,
+    DoubleBarMatrix,
+    Cases,
+    Align,
+    Gather,
+}
+
+impl Environment {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let environment = match name {
+            "matrix" | "smallmatrix" => Environment::Matrix,
+            "pmatrix" => Environment::ParenMatrix,
+            "bmatrix" => Environment::BracketMatrix,
+            "Bmatrix" => Environment::BraceMatrix,
+            "vmatrix" => Environment::BarMatrix,
+            "Vmatrix" => Environment::DoubleBarMatrix,
+            "cases" => Environment::Cases,
+            "align" | "align*" | "aligned" | "split" => Environment::Align,
+            "gather" | "gather*" | "gathered" => Environment::Gather,
+            _ => return None,
+        };
+
+        Some(environment)
+    }
+
+    /// The delimiters placed around this environment, if any.
+    pub fn delimiters(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Environment::ParenMatrix => Some(("(", ")")),
+            Environment::BracketMatrix => Some(("[", "]")),
+            Environment::BraceMatrix => Some(("{", "}")),
+            Environment::BarMatrix => Some(("|", "|")),
+            Environment::DoubleBarMatrix => Some(("‖", "‖")),
+            Environment::Cases => Some(("{", "")),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn is_matrix(self) -> bool {
+        !matches!(
+            self,
+            Environment::Cases | Environment::Align | Environment::Gather,
+        )
+    }
+}
+
+/// How deeply groups and command arguments may nest.
+///
+/// This is checked, rather than relying on the stack, so that deeply nested
+/// formulas produce an error instead of overflowing it.
+pub const MAX_DEPTH: usize = 100;
+
+/// How deeply nodes in a parsed tree may nest.
+///
+/// Each level of parsing produces at most a few levels of nodes,
+/// so this is a safety limit for code walking the tree.
+pub const MAX_NODE_DEPTH: usize = MAX_DEPTH * 4;
+
+/// Parses the given (macro-expanded) tokens.
+///
+/// Multiple lines at the top level, separated by `\\`, are
+/// treated as if they were in a `gather` or `align` environment.
+pub fn parse(tokens: &[Token]) -> Result<MathNode, LatexError> {
+    parse_nested(tokens, 0)
+}
+
+fn parse_nested(tokens: &[Token], depth: usize) -> Result<MathNode, LatexError> {
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        depth,
+    };
+
+    let mut rows = parser.parse_rows()?;
+    if let Some(token) = parser.next() {
+        return Err(unexpected(token));
+    }
+
+    if rows.len() == 1 && rows[0].len() == 1 {
+        return Ok(rows.remove(0).remove(0));
+    }
+
+    let environment = if rows.iter().all(|row| row.len() == 1) {
+        Environment::Gather
+    } else {
+        Environment::Align
+    };
+
+    Ok(MathNode::Table { environment, rows })
+}
+
+#[derive(Debug)]
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Gets the next token which isn't a space, without consuming it.
+    fn peek(&mut self) -> Option<&'a Token> {
+        while let Some(token) = self.tokens.get(self.position) {
+            if token.kind != TokenKind::Space {
+                return Some(token);
+            }
+
+            self.position += 1;
+        }
+
+        None
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.peek()?;
+        self.position += 1;
+        Some(token)
+    }
+
+    fn peek_is(&mut self, text: &str) -> bool {
+        matches!(self.peek(), Some(token) if token.text == text)
+    }
+
+    /// Parses cells separated by `&`, in rows separated by `\\`.
+    fn parse_rows(&mut self) -> Result<Vec<Vec<MathNode>>, LatexError> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+
+        loop {
+            row.push(self.parse_expression()?);
+
+            match self.peek() {
+                Some(token) if token.kind == TokenKind::Alignment => {
+                    self.position += 1;
+                }
+                Some(token) if token.is_command("\\") || token.is_command("cr") => {
+                    self.position += 1;
+                    rows.push(std::mem::take(&mut row));
+                }
+                _ => break,
+            }
+        }
+
+        // Ignore a trailing line break
+        if row != [MathNode::Row(vec![])] || rows.is_empty() {
+            rows.push(row);
+        }
+
+        Ok(rows)
+    }
+
+    /// Parses a series of nodes, until the end of a group, cell or row.
+    fn parse_expression(&mut self) -> Result<MathNode, LatexError> {
+        let mut nodes = Vec::new();
+
+        while let Some(token) = self.peek() {
+            let stop = match token.kind {
+                TokenKind::CloseGroup | TokenKind::Alignment => true,
+                TokenKind::Command => {
+                    matches!(token.command(), Some("\\" | "cr" | "end" | "right"),)
+                }
+                _ => false,
+            };
+
+            if stop {
+                break;
+            }
+
+            nodes.push(self.parse_scripted()?);
+        }
+
+        Ok(row_or_single(nodes))
+    }
+
+    /// Parses a node, with any superscript and subscript after it.
+    fn parse_scripted(&mut self) -> Result<MathNode, LatexError> {
+        let base = match self.peek().map(|token| token.kind) {
+            Some(TokenKind::Superscript | TokenKind::Subscript) => MathNode::Row(vec![]),
+            _ => self.parse_atom(true)?,
+        };
+
+        let mut subscript = None;
+        let mut superscript = None;
+
+        while let Some(token) = self.peek() {
+            let slot = match token.kind {
+                TokenKind::Superscript => &mut superscript,
+                TokenKind::Subscript => &mut subscript,
+                TokenKind::Command
+                    if token.is_command("limits") || token.is_command("nolimits") =>
+                {
+                    self.position += 1;
+                    continue;
+                }
+                _ => break,
+            };
+
+            self.position += 1;
+            if slot.is_some() {
+                return Err(LatexError::new(
+                    LatexErrorKind::DoubleScript,
+                    token.span.clone(),
+                ));
+            }
+
+            *slot = Some(Box::new(self.parse_argument(token)?));
+        }
+
+        if subscript.is_none() && superscript.is_none() {
+            return Ok(base);
+        }
+
+        Ok(MathNode::Scripts {
+            base: Box::new(base),
+            subscript,
+            superscript,
+        })
+    }
+
+    /// Parses the argument to a command, either a group or a single item.
+    fn parse_argument(&mut self, command: &Token) -> Result<MathNode, LatexError> {
+        match self.peek() {
+            Some(token)
+                if !matches!(
+                    token.kind,
+                    TokenKind::CloseGroup
+                        | TokenKind::Alignment
+                        | TokenKind::Superscript
+                        | TokenKind::Subscript,
+                ) =>
+            {
+                self.parse_atom(false)
+            }
+            _ => Err(missing_argument(command)),
+        }
+    }
+
+    fn parse_atom(&mut self, merge_digits: bool) -> Result<MathNode, LatexError> {
+        let token = self.next().expect("No token to parse");
+
+        // Groups and commands nest through here
+        if self.depth >= MAX_DEPTH {
+            return Err(LatexError::new(
+                LatexErrorKind::NestingDepthExceeded,
+                token.span.clone(),
+            ));
+        }
+
+        self.depth += 1;
+        let result = self.parse_token(token, merge_digits);
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_token(
+        &mut self,
+        token: &'a Token,
+        merge_digits: bool,
+    ) -> Result<MathNode, LatexError> {
+        match token.kind {
+            TokenKind::OpenGroup => {
+                let body = self.parse_expression()?;
+
+                match self.next() {
+                    Some(close) if close.kind == TokenKind::CloseGroup => Ok(body),
+                    Some(other) => Err(unexpected(other)),
+                    None => Err(LatexError::new(
+                        LatexErrorKind::UnclosedGroup,
+                        token.span.clone(),
+                    )),
+                }
+            }
+            TokenKind::Letter => Ok(MathNode::Identifier {
+                text: token.text.clone(),
+                speech: None,
+            }),
+            TokenKind::Digit => {
+                let mut number = token.text.clone();
+
+                // Combine runs of digits, with any decimal point
+                if merge_digits {
+                    while let Some(next) = self.tokens.get(self.position) {
+                        let decimal = next.text == "."
+                            && matches!(
+                                self.tokens.get(self.position + 1),
+                                Some(after) if after.kind == TokenKind::Digit,
+                            );
+
+                        if next.kind != TokenKind::Digit && !decimal {
+                            break;
+                        }
+
+                        number.push_str(&next.text);
+                        self.position += 1;
+                    }
+                }
+
+                Ok(MathNode::Number(number))
+            }
+            TokenKind::Symbol => {
+                let text = match token.text.as_str() {
+                    "'" => str!("′"),
+                    "-" => str!("−"),
+                    "*" => str!("∗"),
+                    text => str!(text),
+                };
+
+                Ok(MathNode::Operator {
+                    text,
+                    speech: character_speech(&token.text),
+                    kind: SymbolKind::Operator,
+                })
+            }
+            TokenKind::Command => self.parse_command(token),
+            _ => Err(unexpected(token)),
+        }
+    }
+
+    fn parse_command(&mut self, token: &'a Token) -> Result<MathNode, LatexError> {
+        let name = token.command().unwrap_or("");
+
+        if let Some((kind, text, speech)) = lookup_symbol(name) {
+            let text = str!(text);
+            let speech = Some(speech);
+
+            return Ok(match kind {
+                SymbolKind::Identifier => MathNode::Identifier { text, speech },
+                _ => MathNode::Operator { text, speech, kind },
+            });
+        }
+
+        if is_function(name) {
+            return Ok(MathNode::Function(str!(name)));
+        }
+
+        if let Some(width) = lookup_space(name) {
+            return Ok(MathNode::Space(width));
+        }
+
+        if let Some((accent, speech)) = lookup_accent(name) {
+            let base = self.parse_argument(token)?;
+
+            return Ok(MathNode::Accent {
+                base: Box::new(base),
+                accent,
+                speech,
+                under: false,
+            });
+        }
+
+        if let Some(variant) = lookup_variant(name) {
+            let body = self.parse_argument(token)?;
+
+            return Ok(MathNode::Style {
+                variant,
+                body: Box::new(body),
+            });
+        }
+
+        match name {
+            "frac" | "dfrac" | "tfrac" | "binom" => {
+                let numerator = self.parse_argument(token)?;
+                let denominator = self.parse_argument(token)?;
+
+                Ok(MathNode::Fraction {
+                    numerator: Box::new(numerator),
+                    denominator: Box::new(denominator),
+                    binomial: name == "binom",
+                })
+            }
+            "sqrt" => {
+                let index = if self.peek_is("[") {
+                    self.position += 1;
+                    Some(Box::new(self.parse_optional_argument(token)?))
+                } else {
+                    None
+                };
+
+                let radicand = self.parse_argument(token)?;
+
+                Ok(MathNode::Root {
+                    radicand: Box::new(radicand),
+                    index,
+                })
+            }
+            "underline" => {
+                let base = self.parse_argument(token)?;
+
+                Ok(MathNode::Accent {
+                    base: Box::new(base),
+                    accent: "_",
+                    speech: "underline",
+                    under: true,
+                })
+            }
+            "text" | "textrm" | "textit" | "textbf" | "mbox" => {
+                Ok(MathNode::Text(self.read_raw_argument(token)?))
+            }
+            "operatorname" => Ok(MathNode::Function(self.read_raw_argument(token)?)),
+            "left" => {
+                let open = self.read_delimiter(token)?;
+                let body = self.parse_expression()?;
+
+                match self.next() {
+                    Some(right) if right.is_command("right") => {
+                        let close = self.read_delimiter(right)?;
+
+                        Ok(MathNode::Fenced {
+                            open,
+                            close,
+                            body: Box::new(body),
+                        })
+                    }
+                    _ => Err(LatexError::new(
+                        LatexErrorKind::MissingArgument(str!("\\right")),
+                        token.span.clone(),
+                    )),
+                }
+            }
+            "begin" => self.parse_environment(token),
+            "displaystyle" | "textstyle" | "scriptstyle" => Ok(MathNode::Row(vec![])),
+            "" | "right" | "end" | "\\" | "cr" => Err(unexpected(token)),
+            _ => Err(LatexError::new(
+                LatexErrorKind::UnknownCommand(str!(name)),
+                token.span.clone(),
+            )),
+        }
+    }
+
+    /// Parses an argument in square brackets, after the opening `[`.
+    fn parse_optional_argument(
+        &mut self,
+        command: &Token,
+    ) -> Result<MathNode, LatexError> {
+        let start = self.position;
+        let mut depth = 0;
+
+        loop {
+            let token = self.next().ok_or_else(|| missing_argument(command))?;
+
+            match token.kind {
+                TokenKind::OpenGroup => depth += 1,
+                TokenKind::CloseGroup => depth -= 1,
+                _ if depth == 0 && token.text == "]" => break,
+                _ => (),
+            }
+        }
+
+        parse_nested(&self.tokens[start..self.position - 1], self.depth)
+    }
+
+    /// Reads an argument as plain text, such as for `\text`.
+    fn read_raw_argument(&mut self, command: &Token) -> Result<String, LatexError> {
+        let open = match self.next() {
+            Some(token) if token.kind == TokenKind::OpenGroup => token,
+            Some(token) if token.kind != TokenKind::CloseGroup => {
+                return Ok(token.text.clone())
+            }
+            _ => return Err(missing_argument(command)),
+        };
+
+        let mut text = String::new();
+        let mut depth = 0;
+
+        loop {
+            let token = self.tokens.get(self.position).ok_or_else(|| {
+                LatexError::new(LatexErrorKind::UnclosedGroup, open.span.clone())
+            })?;
+
+            self.position += 1;
+
+            match token.kind {
+                TokenKind::OpenGroup => depth += 1,
+                TokenKind::CloseGroup if depth == 0 => return Ok(text),
+                TokenKind::CloseGroup => depth -= 1,
+                _ => (),
+            }
+
+            text.push_str(&token.text);
+        }
+    }
+
+    /// Reads the delimiter after `\left` or `\right`.
+    fn read_delimiter(&mut self, command: &Token) -> Result<String, LatexError> {
+        let token = self.next().ok_or_else(|| missing_argument(command))?;
+
+        match token.kind {
+            TokenKind::Symbol if token.text == "." => Ok(String::new()),
+            TokenKind::Symbol => Ok(token.text.clone()),
+            TokenKind::Command => match lookup_symbol(token.command().unwrap_or("")) {
+                Some((SymbolKind::Operator, text, _)) => Ok(str!(text)),
+                _ => Err(unexpected(token)),
+            },
+            _ => Err(missing_argument(command)),
+        }
+    }
+
+    fn parse_environment(&mut self, begin: &Token) -> Result<MathNode, LatexError> {
+        let name = self.read_raw_argument(begin)?;
+        let environment = Environment::from_name(&name).ok_or_else(|| {
+            LatexError::new(
+                LatexErrorKind::UnknownEnvironment(name.clone()),
+                begin.span.clone(),
+            )
+        })?;
+
+        let rows = self.parse_rows()?;
+
+        match self.next() {
+            Some(end) if end.is_command("end") => {
+                let end_name = self.read_raw_argument(end)?;
+                if end_name != name {
+                    return Err(LatexError::new(
+                        LatexErrorKind::MismatchedEnvironment {
+                            expected: name,
+                            actual: end_name,
+                        },
+                        end.span.clone(),
+                    ));
+                }
+
+                Ok(MathNode::Table { environment, rows })
+            }
+            _ => Err(LatexError::new(
+                LatexErrorKind::UnclosedEnvironment(name),
+                begin.span.clone(),
+            )),
+        }
+    }
+}
+
+fn row_or_single(mut nodes: Vec<MathNode>) -> MathNode {
+    if nodes.len() == 1 {
+        nodes.remove(0)
+    } else {
+        MathNode::Row(nodes)
+    }
+}
+
+fn unexpected(token: &Token) -> LatexError {
+    LatexError::new(
+        LatexErrorKind::UnexpectedToken(token.text.clone()),
+        token.span.clone(),
+    )
+}
+
+fn missing_argument(command: &Token) -> LatexError {
+    LatexError::new(
+        LatexErrorKind::MissingArgument(command.text.clone()),
+        command.span.clone(),
+    )
+}
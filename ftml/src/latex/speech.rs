@@ -0,0 +1,227 @@
+/*
+ * latex/speech.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Reads parsed LaTeX aloud, producing an accessible linearization.
+//!
+//! For instance, `x^2 + 1` becomes "x squared plus 1".
+
+use super::parse::{Environment, MathNode, MAX_NODE_DEPTH};
+use super::symbols::{function_has_limits, SymbolKind};
+
+/// Produces English text reading out the given formula.
+pub fn linearize(node: &MathNode) -> String {
+    let mut speech = Speech::default();
+    speech.node(node);
+    speech.output
+}
+
+#[derive(Debug, Default)]
+struct Speech {
+    output: String,
+    depth: usize,
+}
+
+impl Speech {
+    fn word(&mut self, word: &str) {
+        let word = word.trim();
+        if word.is_empty() {
+            return;
+        }
+
+        // Closing punctuation attaches to the previous word,
+        // and nothing is spaced after an opening bracket.
+        let attach =
+            matches!(word, ")" | "]" | "," | ";") || self.output.ends_with(['(', '[']);
+
+        if !self.output.is_empty() && !attach {
+            self.output.push(' ');
+        }
+
+        self.output.push_str(word);
+    }
+
+    fn node(&mut self, node: &MathNode) {
+        // The parser limits nesting, but don't rely on that to protect the stack
+        if self.depth > MAX_NODE_DEPTH {
+            self.word("…");
+            return;
+        }
+
+        self.depth += 1;
+        self.read_node(node);
+        self.depth -= 1;
+    }
+
+    fn read_node(&mut self, node: &MathNode) {
+        match node {
+            MathNode::Identifier { text, speech } => self.word(speech.unwrap_or(text)),
+            MathNode::Number(text) => self.word(text),
+            MathNode::Operator { text, speech, .. } => self.word(speech.unwrap_or(text)),
+            MathNode::Function(name) => self.word(name),
+            MathNode::Text(text) => self.word(text),
+            MathNode::Space(_) => (),
+            MathNode::Row(nodes) => {
+                for node in nodes {
+                    self.node(node);
+                }
+            }
+            MathNode::Fraction {
+                numerator,
+                denominator,
+                binomial: true,
+            } => {
+                self.node(numerator);
+                self.word("choose");
+                self.node(denominator);
+            }
+            MathNode::Fraction {
+                numerator,
+                denominator,
+                binomial: false,
+            } => {
+                if numerator.is_simple() && denominator.is_simple() {
+                    self.node(numerator);
+                    self.word("over");
+                    self.node(denominator);
+                } else {
+                    self.word("the fraction");
+                    self.node(numerator);
+                    self.word("over");
+                    self.node(denominator);
+                    self.word("end fraction");
+                }
+            }
+            MathNode::Root { radicand, index } => {
+                match index.as_deref() {
+                    None => self.word("the square root of"),
+                    Some(MathNode::Number(number)) if number == "3" => {
+                        self.word("the cube root of")
+                    }
+                    Some(index) => {
+                        self.word("the root of degree");
+                        self.node(index);
+                        self.word("of");
+                    }
+                }
+
+                self.node(radicand);
+
+                if !radicand.is_simple() {
+                    self.word("end root");
+                }
+            }
+            MathNode::Scripts {
+                base,
+                subscript,
+                superscript,
+            } => self.scripts(base, subscript.as_deref(), superscript.as_deref()),
+            MathNode::Accent {
+                base,
+                speech: "vector",
+                ..
+            } => {
+                self.word("vector");
+                self.node(base);
+            }
+            MathNode::Accent { base, speech, .. } => {
+                self.node(base);
+                self.word(speech);
+            }
+            MathNode::Style { body, .. } => self.node(body),
+            MathNode::Fenced { open, close, body } => {
+                self.word(open);
+                self.node(body);
+                self.word(close);
+            }
+            MathNode::Table { environment, rows } => {
+                match environment {
+                    Environment::Cases => self.word("cases:"),
+                    _ if environment.is_matrix() => self.word("matrix:"),
+                    _ => (),
+                }
+
+                for (index, row) in rows.iter().enumerate() {
+                    if index > 0 {
+                        self.word(";");
+                    }
+
+                    for (column, cell) in row.iter().enumerate() {
+                        // Cells in aligned equations are parts of the same equation
+                        if column > 0 && *environment != Environment::Align {
+                            self.word(",");
+                        }
+
+                        self.node(cell);
+                    }
+                }
+            }
+        }
+    }
+
+    fn scripts(
+        &mut self,
+        base: &MathNode,
+        subscript: Option<&MathNode>,
+        superscript: Option<&MathNode>,
+    ) {
+        let (large, limit) = match base {
+            MathNode::Operator { kind, .. } => (
+                matches!(kind, SymbolKind::LargeOperator | SymbolKind::Integral),
+                false,
+            ),
+            MathNode::Function(name) => (false, function_has_limits(name)),
+            _ => (false, false),
+        };
+
+        self.node(base);
+
+        // Sums and integrals read their bounds as a range
+        if large {
+            if let Some(subscript) = subscript {
+                self.word("from");
+                self.node(subscript);
+            }
+
+            if let Some(superscript) = superscript {
+                self.word("to");
+                self.node(superscript);
+            }
+
+            self.word("of");
+            return;
+        }
+
+        if let Some(subscript) = subscript {
+            self.word(if limit { "as" } else { "sub" });
+            self.node(subscript);
+        }
+
+        match superscript {
+            None => (),
+            Some(MathNode::Number(number)) if number == "2" => self.word("squared"),
+            Some(MathNode::Number(number)) if number == "3" => self.word("cubed"),
+            Some(MathNode::Operator { text, .. }) if text == "′" => self.word("prime"),
+            Some(superscript) => {
+                self.word("to the power of");
+                self.node(superscript);
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+/*
+ * latex/symbols.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Tables of the LaTeX commands which are understood.
+
+/// What kind of MathML element a symbol command produces.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SymbolKind {
+    Identifier,
+    Operator,
+
+    /// An operator like `\sum`, whose scripts are placed above and below.
+    LargeOperator,
+
+    /// An operator like `\int`, whose scripts are placed beside it.
+    Integral,
+}
+
+/// Looks up a command which stands for a single symbol.
+///
+/// Returns the kind of symbol, its Unicode text, and how it is read aloud.
+pub fn lookup_symbol(command: &str) -> Option<(SymbolKind, &'static str, &'static str)> {
+    use self::SymbolKind::*;
+
+    let symbol = match command {
+        // Greek letters
+        "alpha" => (Identifier, "α", "alpha"),
+        "beta" => (Identifier, "β", "beta"),
+        "gamma" => (Identifier, "γ", "gamma"),
+        "delta" => (Identifier, "δ", "delta"),
+        "epsilon" => (Identifier, "ϵ", "epsilon"),
+        "varepsilon" => (Identifier, "ε", "epsilon"),
+        "zeta" => (Identifier, "ζ", "zeta"),
+        "eta" => (Identifier, "η", "eta"),
+        "theta" => (Identifier, "θ", "theta"),
+        "vartheta" => (Identifier, "ϑ", "theta"),
+        "iota" => (Identifier, "ι", "iota"),
+        "kappa" => (Identifier, "κ", "kappa"),
+        "lambda" => (Identifier, "λ", "lambda"),
+        "mu" => (Identifier, "μ", "mu"),
+        "nu" => (Identifier, "ν", "nu"),
+        "xi" => (Identifier, "ξ", "xi"),
+        "pi" => (Identifier, "π", "pi"),
+        "varpi" => (Identifier, "ϖ", "pi"),
+        "rho" => (Identifier, "ρ", "rho"),
+        "varrho" => (Identifier, "ϱ", "rho"),
+        "sigma" => (Identifier, "σ", "sigma"),
+        "varsigma" => (Identifier, "ς", "sigma"),
+        "tau" => (Identifier, "τ", "tau"),
+        "upsilon" => (Identifier, "υ", "upsilon"),
+        "phi" => (Identifier, "ϕ", "phi"),
+        "varphi" => (Identifier, "φ", "phi"),
+        "chi" => (Identifier, "χ", "chi"),
+        "psi" => (Identifier, "ψ", "psi"),
+        "omega" => (Identifier, "ω", "omega"),
+        "Gamma" => (Identifier, "Γ", "capital gamma"),
+        "Delta" => (Identifier, "Δ", "capital delta"),
+        "Theta" => (Identifier, "Θ", "capital theta"),
+        "Lambda" => (Identifier, "Λ", "capital lambda"),
+        "Xi" => (Identifier, "Ξ", "capital xi"),
+        "Pi" => (Identifier, "Π", "capital pi"),
+        "Sigma" => (Identifier, "Σ", "capital sigma"),
+        "Upsilon" => (Identifier, "Υ", "capital upsilon"),
+        "Phi" => (Identifier, "Φ", "capital phi"),
+        "Psi" => (Identifier, "Ψ", "capital psi"),
+        "Omega" => (Identifier, "Ω", "capital omega"),
+
+        // Other identifiers
+        "infty" => (Identifier, "∞", "infinity"),
+        "partial" => (Identifier, "∂", "partial"),
+        "nabla" => (Identifier, "∇", "nabla"),
+        "hbar" => (Identifier, "ℏ", "h bar"),
+        "ell" => (Identifier, "ℓ", "ell"),
+        "aleph" => (Identifier, "ℵ", "aleph"),
+        "Re" => (Identifier, "ℜ", "real part"),
+        "Im" => (Identifier, "ℑ", "imaginary part"),
+        "emptyset" | "varnothing" => (Identifier, "∅", "the empty set"),
+        "angle" => (Identifier, "∠", "angle"),
+
+        // Binary operators
+        "pm" => (Operator, "±", "plus or minus"),
+        "mp" => (Operator, "∓", "minus or plus"),
+        "times" => (Operator, "×", "times"),
+        "cdot" => (Operator, "⋅", "times"),
+        "div" => (Operator, "÷", "divided by"),
+        "ast" => (Operator, "∗", "star"),
+        "star" => (Operator, "⋆", "star"),
+        "circ" => (Operator, "∘", "composed with"),
+        "cup" => (Operator, "∪", "union"),
+        "cap" => (Operator, "∩", "intersection"),
+        "setminus" => (Operator, "∖", "minus"),
+        "wedge" | "land" => (Operator, "∧", "and"),
+        "vee" | "lor" => (Operator, "∨", "or"),
+        "neg" | "lnot" => (Operator, "¬", "not"),
+        "oplus" => (Operator, "⊕", "direct sum"),
+        "otimes" => (Operator, "⊗", "tensor product"),
+
+        // Relations
+        "leq" | "le" => (Operator, "≤", "is less than or equal to"),
+        "geq" | "ge" => (Operator, "≥", "is greater than or equal to"),
+        "neq" | "ne" => (Operator, "≠", "is not equal to"),
+        "approx" => (Operator, "≈", "is approximately equal to"),
+        "equiv" => (Operator, "≡", "is equivalent to"),
+        "cong" => (Operator, "≅", "is congruent to"),
+        "sim" => (Operator, "∼", "is similar to"),
+        "propto" => (Operator, "∝", "is proportional to"),
+        "ll" => (Operator, "≪", "is much less than"),
+        "gg" => (Operator, "≫", "is much greater than"),
+        "in" => (Operator, "∈", "is in"),
+        "notin" => (Operator, "∉", "is not in"),
+        "ni" => (Operator, "∋", "contains"),
+        "subset" => (Operator, "⊂", "is a subset of"),
+        "subseteq" => (Operator, "⊆", "is a subset of or equal to"),
+        "supset" => (Operator, "⊃", "is a superset of"),
+        "supseteq" => (Operator, "⊇", "is a superset of or equal to"),
+        "perp" => (Operator, "⊥", "is perpendicular to"),
+        "parallel" => (Operator, "∥", "is parallel to"),
+        "mid" => (Operator, "∣", "divides"),
+
+        // Arrows
+        "to" | "rightarrow" => (Operator, "→", "goes to"),
+        "leftarrow" | "gets" => (Operator, "←", "comes from"),
+        "leftrightarrow" => (Operator, "↔", "corresponds to"),
+        "Rightarrow" | "implies" => (Operator, "⇒", "implies"),
+        "Leftarrow" => (Operator, "⇐", "is implied by"),
+        "Leftrightarrow" | "iff" => (Operator, "⇔", "if and only if"),
+        "mapsto" => (Operator, "↦", "maps to"),
+
+        // Quantifiers
+        "forall" => (Operator, "∀", "for all"),
+        "exists" => (Operator, "∃", "there exists"),
+
+        // Delimiters
+        "{" | "lbrace" => (Operator, "{", "open brace"),
+        "}" | "rbrace" => (Operator, "}", "close brace"),
+        "langle" => (Operator, "⟨", "open angle bracket"),
+        "rangle" => (Operator, "⟩", "close angle bracket"),
+        "lfloor" => (Operator, "⌊", "floor of"),
+        "rfloor" => (Operator, "⌋", "end floor"),
+        "lceil" => (Operator, "⌈", "ceiling of"),
+        "rceil" => (Operator, "⌉", "end ceiling"),
+        "|" | "Vert" => (Operator, "‖", "norm"),
+        "vert" => (Operator, "|", "vertical bar"),
+
+        // Punctuation
+        "ldots" | "dots" => (Operator, "…", "dot dot dot"),
+        "cdots" => (Operator, "⋯", "dot dot dot"),
+        "vdots" => (Operator, "⋮", "dot dot dot"),
+        "ddots" => (Operator, "⋱", "dot dot dot"),
+        "prime" => (Operator, "′", "prime"),
+        "$" => (Operator, "$", "dollars"),
+        "%" => (Operator, "%", "percent"),
+        "&" => (Operator, "&", "and"),
+        "#" => (Operator, "#", "number"),
+        "_" => (Operator, "_", "underscore"),
+
+        // Large operators
+        "sum" => (LargeOperator, "∑", "the sum"),
+        "prod" => (LargeOperator, "∏", "the product"),
+        "coprod" => (LargeOperator, "∐", "the coproduct"),
+        "bigcup" => (LargeOperator, "⋃", "the union"),
+        "bigcap" => (LargeOperator, "⋂", "the intersection"),
+        "int" => (Integral, "∫", "the integral"),
+        "iint" => (Integral, "∬", "the double integral"),
+        "iiint" => (Integral, "∭", "the triple integral"),
+        "oint" => (Integral, "∮", "the contour integral"),
+
+        _ => return None,
+    };
+
+    Some(symbol)
+}
+
+/// How a plain character is read aloud, if not as itself.
+pub fn character_speech(ch: &str) -> Option<&'static str> {
+    let speech = match ch {
+        "+" => "plus",
+        "-" => "minus",
+        "=" => "equals",
+        "<" => "is less than",
+        ">" => "is greater than",
+        "*" => "times",
+        "/" => "divided by",
+        "!" => "factorial",
+        "'" => "prime",
+        "|" => "vertical bar",
+        _ => return None,
+    };
+
+    Some(speech)
+}
+
+/// Whether this command is a function name, such as `\sin`.
+pub fn is_function(command: &str) -> bool {
+    matches!(
+        command,
+        "sin"
+            | "cos"
+            | "tan"
+            | "cot"
+            | "sec"
+            | "csc"
+            | "arcsin"
+            | "arccos"
+            | "arctan"
+            | "sinh"
+            | "cosh"
+            | "tanh"
+            | "coth"
+            | "log"
+            | "ln"
+            | "lg"
+            | "exp"
+            | "lim"
+            | "liminf"
+            | "limsup"
+            | "max"
+            | "min"
+            | "sup"
+            | "inf"
+            | "det"
+            | "gcd"
+            | "deg"
+            | "dim"
+            | "ker"
+            | "arg"
+            | "Pr",
+    )
+}
+
+/// Whether scripts on this function are placed above and below it.
+pub fn function_has_limits(command: &str) -> bool {
+    matches!(
+        command,
+        "lim"
+            | "liminf"
+            | "limsup"
+            | "max"
+            | "min"
+            | "sup"
+            | "inf"
+            | "det"
+            | "gcd"
+            | "Pr",
+    )
+}
+
+/// Looks up the width of a spacing command, in em.
+pub fn lookup_space(command: &str) -> Option<&'static str> {
+    let width = match command {
+        "," | "thinspace" => "0.1667em",
+        ":" | ">" | "medspace" => "0.2222em",
+        ";" | "thickspace" => "0.2778em",
+        "!" | "negthinspace" => "-0.1667em",
+        " " | "space" => "0.25em",
+        "quad" => "1em",
+        "qquad" => "2em",
+        _ => return None,
+    };
+
+    Some(width)
+}
+
+/// Looks up an accent command, returning its character and how it is read aloud.
+pub fn lookup_accent(command: &str) -> Option<(&'static str, &'static str)> {
+    let accent = match command {
+        "hat" | "widehat" => ("^", "hat"),
+        "bar" | "overline" => ("‾", "bar"),
+        "vec" => ("→", "vector"),
+        "dot" => ("˙", "dot"),
+        "ddot" => ("¨", "double dot"),
+        "tilde" | "widetilde" => ("~", "tilde"),
+        _ => return None,
+    };
+
+    Some(accent)
+}
+
+/// Looks up a font command, returning its MathML `mathvariant`.
+pub fn lookup_variant(command: &str) -> Option<&'static str> {
+    let variant = match command {
+        "mathrm" => "normal",
+        "mathbf" | "boldsymbol" => "bold",
+        "mathit" => "italic",
+        "mathbb" => "double-struck",
+        "mathcal" => "script",
+        "mathfrak" => "fraktur",
+        "mathsf" => "sans-serif",
+        "mathtt" => "monospace",
+        _ => return None,
+    };
+
+    Some(variant)
+}
@@ -0,0 +1,143 @@
+/*
+ * latex/token.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::ops::Range;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A control sequence, such as `\frac` or `\{`.
+    Command,
+    Letter,
+    Digit,
+    Symbol,
+    OpenGroup,
+    CloseGroup,
+    Superscript,
+    Subscript,
+    Alignment,
+    /// A macro parameter, such as `#1`.
+    Parameter,
+    Space,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+
+    /// The source text of this token.
+    pub text: String,
+
+    /// The byte range of this token in the original source.
+    pub span: Range<usize>,
+}
+
+impl Token {
+    /// Gets the name of this command, without the backslash.
+    pub fn command(&self) -> Option<&str> {
+        match self.kind {
+            TokenKind::Command => Some(&self.text[1..]),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn is_command(&self, name: &str) -> bool {
+        self.command() == Some(name)
+    }
+
+    /// Gets the number of this macro parameter.
+    pub fn parameter(&self) -> Option<usize> {
+        match self.kind {
+            TokenKind::Parameter => self.text[1..].parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Splits LaTeX source into tokens. Comments are dropped.
+pub fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        let mut end = start + ch.len_utf8();
+        let kind = match ch {
+            '\\' => {
+                // Either a run of letters, or a single other character
+                match chars.peek().copied() {
+                    Some((_, next)) if next.is_ascii_alphabetic() => {
+                        while let Some(&(index, next)) = chars.peek() {
+                            if !next.is_ascii_alphabetic() {
+                                break;
+                            }
+
+                            end = index + next.len_utf8();
+                            chars.next();
+                        }
+                    }
+                    Some((index, next)) => {
+                        end = index + next.len_utf8();
+                        chars.next();
+                    }
+                    None => (),
+                }
+
+                TokenKind::Command
+            }
+            '%' => {
+                // Comment until the end of the line
+                for (_, next) in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+
+                continue;
+            }
+            '#' => {
+                if let Some(&(index, next)) = chars.peek() {
+                    if next.is_ascii_digit() {
+                        end = index + 1;
+                        chars.next();
+                    }
+                }
+
+                TokenKind::Parameter
+            }
+            '{' => TokenKind::OpenGroup,
+            '}' => TokenKind::CloseGroup,
+            '^' => TokenKind::Superscript,
+            '_' => TokenKind::Subscript,
+            '&' => TokenKind::Alignment,
+            _ if ch.is_whitespace() => TokenKind::Space,
+            _ if ch.is_ascii_digit() => TokenKind::Digit,
+            _ if ch.is_alphabetic() => TokenKind::Letter,
+            _ => TokenKind::Symbol,
+        };
+
+        tokens.push(Token {
+            kind,
+            text: str!(&source[start..end]),
+            span: start..end,
+        });
+    }
+
+    tokens
+}
@@ -49,6 +49,10 @@
 //! [`TextRender`] and [`HtmlRender`], which render to
 //! plain text and full HTML respectively.
 //!
+//! The pipeline is thread-safe, so pages may be rendered in
+//! parallel. The [`batch`] module renders many pages at once
+//! on a pool of worker threads, sharing fetched includes.
+//!
 //! # Features
 //! This crate has one feature of note:
 //!
@@ -72,6 +76,7 @@
 //! [`Render`]: ./render/trait.Render.html
 //! [`TextRender`]: ./render/html/struct.HtmlRender.html
 //! [`HtmlRender`]: ./render/text/struct.TextRender.html
+//! [`batch`]: ./batch/index.html
 //! [`serde`]: https://docs.rs/serde
 //! [`wasm-pack`]: https://rustwasm.github.io/docs/wasm-pack/
 
@@ -111,10 +116,12 @@ extern crate str_macro;
 mod macros;
 
 mod id_prefix;
+mod latex;
 mod next_index;
 mod non_empty_vec;
 mod preproc;
 mod python_interface;
+mod slug;
 mod text;
 mod url;
 mod utf16;
@@ -122,7 +129,11 @@ mod utf16;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+
 pub mod data;
+pub mod i18n;
 pub mod includes;
 pub mod info;
 pub mod parsing;
@@ -90,6 +90,12 @@ impl ParseWarning {
         self.kind
     }
 
+    /// Gets a warning of a different kind at the same location.
+    #[inline]
+    pub(crate) fn with_kind(self, kind: ParseWarningKind) -> Self {
+        ParseWarning { kind, ..self }
+    }
+
     #[must_use]
     pub fn to_utf16_indices(&self, map: &Utf16IndexMap) -> Self {
         // Copy fields
@@ -163,6 +169,9 @@ pub enum ParseWarningKind {
     /// This table cell appears outside of a table row.
     TableCellOutsideTable,
 
+    /// This table cell's contents don't match the type declared for its column.
+    TableCellTypeMismatch,
+
     /// This tabview has no elements in it.
     TabViewEmpty,
 
@@ -220,9 +229,24 @@ pub enum ParseWarningKind {
     /// The given variable was not found, and thus not substituted.
     NoSuchVariable,
 
+    /// The referenced equation does not exist.
+    NoSuchEquation,
+
+    /// The cited bibliography entry does not exist.
+    NoSuchBibliographyEntry,
+
+    /// This bibliography entry is never cited.
+    BibliographyEntryUnused,
+
+    /// A bibliography entry with this label was already given, so this one is ignored.
+    BibliographyEntryDuplicate,
+
     /// The URL passed here was invalid.
     InvalidUrl,
 
+    /// The URL passed here is not from an allowed embed provider.
+    EmbedNotAllowed,
+
     /// Interwiki PageRef not allowed
     CrossSiteRef,
 
@@ -29,6 +29,7 @@ mod consume;
 mod depth;
 mod element_condition;
 mod exception;
+mod numbering;
 mod outcome;
 mod paragraph;
 mod parser;
@@ -50,22 +51,25 @@ mod prelude {
 
 use self::depth::{process_depths, DepthItem, DepthList};
 use self::element_condition::{ElementCondition, ElementConditionType};
+use self::numbering::continue_numbered_lists;
 use self::paragraph::{gather_paragraphs, NO_CLOSE_CONDITION};
 use self::parser::Parser;
 use self::rule::impls::RULE_PAGE;
 use self::string::parse_string;
 use self::strip::{strip_newlines, strip_whitespace};
-use crate::data::{PageCallbacks, PageInfo, PageRef};
+use crate::data::{FileRef, PageCallbacks, PageInfo, PageRef};
+use crate::id_prefix::isolate_ids;
 use crate::next_index::{NextIndex, TableOfContentsIndex};
 use crate::settings::WikitextSettings;
+use crate::slug::slugify;
 use crate::tokenizer::Tokenization;
 use crate::tree::{
     AttributeMap, Container, ContainerType, Element, LinkLabel, LinkLocation, LinkType,
-    SyntaxTree,
+    SyntaxTree, SyntaxTreeParts,
 };
 use std::borrow::Cow;
-use std::collections::HashMap;
-use std::rc::Rc;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 pub use self::boolean::{parse_boolean, NonBooleanValue};
 pub use self::exception::{ParseException, ParseWarning, ParseWarningKind};
@@ -81,7 +85,7 @@ pub type WikiScriptScope<'t> = HashMap<Cow<'t, str>, (Cow<'t, str>, u32)>;
 pub fn parse<'r, 't>(
     tokenization: &'r Tokenization<'t>,
     page_info: &'r PageInfo<'t>,
-    page_callbacks: Rc<dyn PageCallbacks>,
+    page_callbacks: Arc<dyn PageCallbacks>,
     settings: &'r WikitextSettings,
 ) -> ParseOutcome<SyntaxTree<'t>>
 where
@@ -97,10 +101,21 @@ where
         has_footnote_block,
         has_toc_block,
         internal_links,
+        users,
+        files,
+        equations,
+        equation_references,
+        bibliography,
+        bibliography_citations,
     } = parse_internal(page_info, page_callbacks, settings, tokenization);
 
     // For producing table of contents indexes
     let mut incrementer = Incrementer(0);
+    let heading_anchors = if settings.heading_anchors.use_slugs() {
+        build_heading_anchors(&table_of_contents_depths, settings)
+    } else {
+        vec![]
+    };
 
     info!("Finished paragraph gathering, matching on consumption");
     match result {
@@ -109,7 +124,25 @@ where
             exceptions,
             ..
         }) => {
-            let warnings = extract_exceptions(exceptions);
+            let mut warnings = extract_exceptions(exceptions);
+
+            // Equation references can come before the equation,
+            // so they are only checked once everything is parsed.
+            for (name, warning) in equation_references {
+                let exists = equations
+                    .iter()
+                    .any(|(equation_name, _)| equation_name.as_ref() == Some(&name));
+
+                if !exists {
+                    warnings.push(warning);
+                }
+            }
+
+            let bibliography =
+                build_bibliography(bibliography, bibliography_citations, &mut warnings);
+
+            // Lists can only be continued once all of them are parsed
+            continue_numbered_lists(&mut elements);
 
             info!(
                 "Finished parsing, producing final syntax tree ({} warnings)",
@@ -124,7 +157,9 @@ where
             // Convert TOC depth lists
             let table_of_contents = process_depths((), table_of_contents_depths)
                 .into_iter()
-                .map(|(_, items)| build_toc_list_element(&mut incrementer, items))
+                .map(|(_, items)| {
+                    build_toc_list_element(&mut incrementer, &heading_anchors, items)
+                })
                 .collect::<Vec<_>>();
 
             // Add a footnote block at the end,
@@ -138,16 +173,21 @@ where
                 });
             }
 
-            SyntaxTree::from_element_result(
-                elements,
-                warnings,
+            let parts = SyntaxTreeParts {
                 table_of_contents,
                 has_toc_block,
+                heading_anchors,
                 footnotes,
                 code,
                 html,
                 internal_links,
-            )
+                users,
+                files,
+                equations,
+                bibliography,
+            };
+
+            SyntaxTree::from_element_result(elements, warnings, parts)
         }
         Err(warning) => {
             // This path is only reachable if a very bad error occurs.
@@ -159,20 +199,14 @@ where
             let wikitext = tokenization.full_text().inner();
             let elements = vec![text!(wikitext)];
             let warnings = vec![warning];
-            let table_of_contents = vec![];
-            let footnotes = vec![];
-            let internal_links = vec![];
-
-            SyntaxTree::from_element_result(
-                elements,
-                warnings,
-                table_of_contents,
+            let parts = SyntaxTreeParts {
                 has_toc_block,
-                footnotes,
                 code,
                 html,
-                internal_links,
-            )
+                ..Default::default()
+            };
+
+            SyntaxTree::from_element_result(elements, warnings, parts)
         }
     }
 }
@@ -180,7 +214,7 @@ where
 /// Runs the parser, but returns the raw internal results prior to conversion.
 pub fn parse_internal<'r, 't>(
     page_info: &'r PageInfo<'t>,
-    page_callbacks: Rc<dyn PageCallbacks>,
+    page_callbacks: Arc<dyn PageCallbacks>,
     settings: &'r WikitextSettings,
     tokenization: &'r Tokenization<'t>,
 ) -> UnstructuredParseResult<'r, 't>
@@ -199,6 +233,12 @@ where
     let code = parser.remove_code();
     let html = parser.remove_html();
     let internal_links = parser.remove_internal_links();
+    let users = parser.remove_users();
+    let files = parser.remove_files();
+    let equations = parser.remove_equations();
+    let equation_references = parser.remove_equation_references();
+    let bibliography = parser.remove_bibliography();
+    let bibliography_citations = parser.remove_bibliography_citations();
     let has_footnote_block = parser.has_footnote_block();
     let has_toc_block = parser.has_toc_block();
 
@@ -211,6 +251,12 @@ where
         has_footnote_block,
         has_toc_block,
         internal_links,
+        users,
+        files,
+        equations,
+        equation_references,
+        bibliography,
+        bibliography_citations,
     }
 }
 
@@ -232,15 +278,106 @@ fn extract_exceptions(exceptions: Vec<ParseException>) -> Vec<ParseWarning> {
     warnings
 }
 
+/// Produces a unique ID for each heading, from its name.
+fn build_heading_anchors(
+    table_of_contents: &[(usize, String)],
+    settings: &WikitextSettings,
+) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut anchors = Vec::with_capacity(table_of_contents.len());
+
+    for (_, name) in table_of_contents {
+        let mut slug = slugify(name);
+        if slug.is_empty() {
+            slug = str!("section");
+        }
+
+        // The ID is user-controlled, so it must be isolated
+        if settings.isolate_user_ids {
+            slug = isolate_ids(&slug);
+        }
+
+        // Add a suffix to repeated headings
+        let mut anchor = slug.clone();
+        let mut suffix = 1;
+
+        while seen.contains(&anchor) {
+            anchor = format!("{slug}-{suffix}");
+            suffix += 1;
+        }
+
+        seen.insert(anchor.clone());
+        anchors.push(anchor);
+    }
+
+    anchors
+}
+
+/// Orders the bibliography entries, adding warnings for bad citations.
+///
+/// Entries are numbered in the order they are first cited,
+/// followed by any entries which are never cited. If a label is
+/// defined more than once, only the first definition is used.
+fn build_bibliography<'t>(
+    entries: Vec<(String, Vec<Element<'t>>, ParseWarning)>,
+    citations: Vec<(String, ParseWarning)>,
+    warnings: &mut Vec<ParseWarning>,
+) -> Vec<(String, Vec<Element<'t>>)> {
+    let mut entries = entries.into_iter().map(Some).collect::<Vec<_>>();
+    let mut bibliography = Vec::with_capacity(entries.len());
+
+    for (label, warning) in citations {
+        if bibliography
+            .iter()
+            .any(|(entry_label, _)| entry_label == &label)
+        {
+            continue;
+        }
+
+        let index = entries.iter().position(|entry| match entry {
+            Some((entry_label, _, _)) => entry_label == &label,
+            None => false,
+        });
+
+        match index {
+            Some(index) => {
+                let (label, contents, _) = entries[index].take().unwrap();
+                bibliography.push((label, contents));
+            }
+            None => warnings.push(warning),
+        }
+    }
+
+    for (label, contents, warning) in entries.into_iter().flatten() {
+        if bibliography
+            .iter()
+            .any(|(entry_label, _)| entry_label == &label)
+        {
+            warnings
+                .push(warning.with_kind(ParseWarningKind::BibliographyEntryDuplicate));
+        } else {
+            warnings.push(warning);
+            bibliography.push((label, contents));
+        }
+    }
+
+    bibliography
+}
+
 fn unwrap_toc_list(
     depth: usize,
     incr: &mut Incrementer,
+    anchors: &[String],
     list: DepthList<(), String>,
 ) -> Vec<Element<'static>> {
     let build_item = |item| match item {
-        DepthItem::List(_, list) => unwrap_toc_list(depth + 1, incr, list),
+        DepthItem::List(_, list) => unwrap_toc_list(depth + 1, incr, anchors, list),
         DepthItem::Item(name) => {
-            let anchor = format!("#toc{}", incr.next());
+            let index = incr.next();
+            let anchor = match anchors.get(index) {
+                Some(anchor) => format!("#{anchor}"),
+                None => format!("#toc{index}"),
+            };
             let link = Element::Link {
                 ltype: LinkType::TableOfContents,
                 link: LinkLocation::Url(Cow::Owned(anchor)),
@@ -264,9 +401,10 @@ fn unwrap_toc_list(
 
 fn build_toc_list_element(
     incr: &mut Incrementer,
+    anchors: &[String],
     list: DepthList<(), String>,
 ) -> Element<'static> {
-    let items = unwrap_toc_list(0, incr, list);
+    let items = unwrap_toc_list(0, incr, anchors, list);
     Element::Fragment(items)
 }
 
@@ -315,4 +453,22 @@ pub struct UnstructuredParseResult<'r, 't> {
 
     // The list of internal links.
     pub internal_links: Vec<PageRef<'t>>,
+
+    // The list of users referenced by [[user]].
+    pub users: Vec<Cow<'t, str>>,
+
+    // The list of attached files referenced by [[file]], [[image]], and links.
+    pub files: Vec<FileRef<'t>>,
+
+    // The list of [[math]] blocks, with their names.
+    pub equations: Vec<(Option<String>, String)>,
+
+    // The list of [[eref]] names, with the warning if they are not found.
+    pub equation_references: Vec<(String, ParseWarning)>,
+
+    // The list of [[bibliography]] entries, with the warning if they are not cited.
+    pub bibliography: Vec<(String, Vec<Element<'t>>, ParseWarning)>,
+
+    // The list of ((bibcite)) labels, with the warning if they are not found.
+    pub bibliography_citations: Vec<(String, ParseWarning)>,
 }
@@ -0,0 +1,56 @@
+/*
+ * parsing/numbering.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Resolves numbered lists which continue the numbering of a previous list.
+//!
+//! This can only be done once the whole page is parsed, since a list may be
+//! parsed several times while rules are being attempted.
+
+use crate::tree::{Element, ListType};
+use std::convert::TryFrom;
+
+/// Gives each continued numbered list the start number following the previous one.
+///
+/// Lists continue from the previous numbered list among the same sibling
+/// elements, for instance at the top level of the page, or within the same `[[div]]`.
+/// Lists which set their own start number are left as-is.
+pub fn continue_numbered_lists(elements: &mut [Element]) {
+    let mut next_number = None;
+
+    for element in elements {
+        match element {
+            Element::List {
+                ltype: ListType::Numbered(numbered),
+                items,
+                ..
+            } => {
+                if numbered.continued && numbered.start.is_none() {
+                    numbered.start = next_number.and_then(|n| i32::try_from(n).ok());
+                }
+
+                next_number = Some(numbered.next_number(items));
+            }
+            Element::Container(container) => {
+                continue_numbered_lists(container.elements_mut());
+            }
+            _ => (),
+        }
+    }
+}
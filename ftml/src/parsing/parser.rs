@@ -24,16 +24,15 @@ use super::condition::ParseCondition;
 use super::rule::Rule;
 use super::RULE_PAGE;
 use super::{parse_internal, prelude::*, UnstructuredParseResult, WikiScriptScope};
-use crate::data::{PageCallbacks, PageInfo, PageRef};
+use crate::data::{FileRef, PageCallbacks, PageInfo, PageRef};
 use crate::render::text::TextRender;
 use crate::tokenizer::Tokenization;
 use crate::tree::{AcceptsPartial, AttributeMap, Container, ContainerType, HeadingLevel};
 use regex::Regex;
 use std::borrow::Cow;
-use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::vec;
 use std::{mem, ptr};
 
@@ -55,6 +54,10 @@ bitflags! {
         const Scopes = 1 << 6;
         const Code = 1 << 7;
         const HTML = 1 << 8;
+        const Users = 1 << 9;
+        const Equations = 1 << 10;
+        const Bibliography = 1 << 11;
+        const Files = 1 << 12;
     }
 }
 
@@ -67,25 +70,45 @@ struct ParserState<'t> {
     //
     // Schema: Vec<(depth, _, name)>
     //
-    // Note: These three are in Rc<_> items so that the Parser
+    // Note: These three are in Arc<_> items so that the Parser
     //       can be cloned. This struct is intended as a
     //       cheap pointer object, with the true contents
     //       here preserved across parser child instances.
-    table_of_contents: Rc<RefCell<Vec<(usize, String)>>>,
+    //       They are behind a Mutex rather than a RefCell so
+    //       that the parser remains Send.
+    table_of_contents: Arc<Mutex<Vec<(usize, String)>>>,
 
     // Footnotes
     //
     // Schema: Vec<List of elements in a footnote>
-    footnotes: Rc<RefCell<Vec<Vec<Element<'t>>>>>,
+    footnotes: Arc<Mutex<Vec<Vec<Element<'t>>>>>,
 
     // List of [[code]]. First element is type
-    code: Rc<RefCell<Vec<(String, String)>>>,
+    code: Arc<Mutex<Vec<(String, String)>>>,
 
     // List of [[html]]
-    html: Rc<RefCell<Vec<String>>>,
+    html: Arc<Mutex<Vec<String>>>,
 
     // Internal links
-    internal_links: Rc<RefCell<Vec<PageRef<'t>>>>,
+    internal_links: Arc<Mutex<Vec<PageRef<'t>>>>,
+
+    // Referenced users, from [[user]]
+    users: Arc<Mutex<Vec<Cow<'t, str>>>>,
+
+    // Referenced attachments, from [[file]], [[image]], and local file links
+    files: Arc<Mutex<Vec<FileRef<'t>>>>,
+
+    // List of [[math]] blocks, in order. First element is the name
+    equations: Arc<Mutex<Vec<(Option<String>, String)>>>,
+
+    // References from [[eref]], with the warning to emit if it doesn't exist
+    equation_references: Arc<Mutex<Vec<(String, ParseWarning)>>>,
+
+    // Entries from [[bibliography]], with the warning to emit if never cited
+    bibliography: Arc<Mutex<Vec<(String, Vec<Element<'t>>, ParseWarning)>>>,
+
+    // Citations from ((bibcite)), with the warning to emit if it doesn't exist
+    bibliography_citations: Arc<Mutex<Vec<(String, ParseWarning)>>>,
 
     // Flags
     has_footnote_block: bool, // Whether a [[footnoteblock]] was created.
@@ -143,14 +166,14 @@ impl Drop for ParserTransaction<'_, '_, '_> {
 pub struct Parser<'r, 't> {
     // Page and parse information
     page_info: &'r PageInfo<'t>,
-    page_callbacks: Rc<dyn PageCallbacks>,
+    page_callbacks: Arc<dyn PageCallbacks>,
     settings: &'r WikitextSettings,
 
     // Parse state
     current: &'r ExtractedToken<'t>,
     remaining: &'r [ExtractedToken<'t>],
     full_text: FullText<'t>,
-    ast_cache: Rc<RefCell<HashMap<usize, (usize, ParseSuccess<'r, 't, Elements<'t>>)>>>,
+    ast_cache: Arc<Mutex<HashMap<usize, (usize, ParseSuccess<'r, 't, Elements<'t>>)>>>,
 
     // Rule state
     rule: Rule,
@@ -171,7 +194,7 @@ impl<'r, 't> Parser<'r, 't> {
     pub(crate) fn new(
         tokenization: &'r Tokenization<'t>,
         page_info: &'r PageInfo<'t>,
-        page_callbacks: Rc<dyn PageCallbacks>,
+        page_callbacks: Arc<dyn PageCallbacks>,
         settings: &'r WikitextSettings,
     ) -> Self {
         let scopes = vec![WikiScriptScope::new()];
@@ -189,6 +212,12 @@ impl<'r, 't> Parser<'r, 't> {
             code: make_shared_vec(),
             html: make_shared_vec(),
             internal_links: make_shared_vec(),
+            users: make_shared_vec(),
+            files: make_shared_vec(),
+            equations: make_shared_vec(),
+            equation_references: make_shared_vec(),
+            bibliography: make_shared_vec(),
+            bibliography_citations: make_shared_vec(),
             has_footnote_block: false,
             has_toc_block: false,
             in_footnote: false,
@@ -201,7 +230,7 @@ impl<'r, 't> Parser<'r, 't> {
             settings,
             current,
             remaining,
-            ast_cache: Rc::new(RefCell::new(HashMap::new())),
+            ast_cache: Arc::new(Mutex::new(HashMap::new())),
             full_text,
             rule: RULE_PAGE,
             depth: 0,
@@ -218,36 +247,74 @@ impl<'r, 't> Parser<'r, 't> {
         let current = self.state().clone();
 
         let cloned_toc = if flags.contains(ParserTransactionFlags::TOC) {
-            Rc::new(RefCell::new(current.table_of_contents.borrow().to_vec()))
+            Arc::new(Mutex::new(
+                current.table_of_contents.lock().unwrap().to_vec(),
+            ))
         } else {
             current.table_of_contents
         };
 
         let cloned_footnotes = if flags.contains(ParserTransactionFlags::Footnotes) {
-            Rc::new(RefCell::new(current.footnotes.borrow().to_vec()))
+            Arc::new(Mutex::new(current.footnotes.lock().unwrap().to_vec()))
         } else {
             current.footnotes
         };
 
         let cloned_code = if flags.contains(ParserTransactionFlags::Code) {
-            Rc::new(RefCell::new(current.code.borrow().to_vec()))
+            Arc::new(Mutex::new(current.code.lock().unwrap().to_vec()))
         } else {
             current.code
         };
 
         let cloned_html = if flags.contains(ParserTransactionFlags::HTML) {
-            Rc::new(RefCell::new(current.html.borrow().to_vec()))
+            Arc::new(Mutex::new(current.html.lock().unwrap().to_vec()))
         } else {
             current.html
         };
 
         let cloned_internal_links =
             if flags.contains(ParserTransactionFlags::InternalLinks) {
-                Rc::new(RefCell::new(current.internal_links.borrow().to_vec()))
+                Arc::new(Mutex::new(current.internal_links.lock().unwrap().to_vec()))
             } else {
                 current.internal_links
             };
 
+        let cloned_users = if flags.contains(ParserTransactionFlags::Users) {
+            Arc::new(Mutex::new(current.users.lock().unwrap().to_vec()))
+        } else {
+            current.users
+        };
+
+        let cloned_files = if flags.contains(ParserTransactionFlags::Files) {
+            Arc::new(Mutex::new(current.files.lock().unwrap().to_vec()))
+        } else {
+            current.files
+        };
+
+        let (cloned_equations, cloned_equation_references) =
+            if flags.contains(ParserTransactionFlags::Equations) {
+                (
+                    Arc::new(Mutex::new(current.equations.lock().unwrap().to_vec())),
+                    Arc::new(Mutex::new(
+                        current.equation_references.lock().unwrap().to_vec(),
+                    )),
+                )
+            } else {
+                (current.equations, current.equation_references)
+            };
+
+        let (cloned_bibliography, cloned_bibliography_citations) =
+            if flags.contains(ParserTransactionFlags::Bibliography) {
+                (
+                    Arc::new(Mutex::new(current.bibliography.lock().unwrap().to_vec())),
+                    Arc::new(Mutex::new(
+                        current.bibliography_citations.lock().unwrap().to_vec(),
+                    )),
+                )
+            } else {
+                (current.bibliography, current.bibliography_citations)
+            };
+
         self.state.push(ParserState {
             accepts_partial: current.accepts_partial,
             table_of_contents: cloned_toc,
@@ -255,6 +322,12 @@ impl<'r, 't> Parser<'r, 't> {
             code: cloned_code,
             html: cloned_html,
             internal_links: cloned_internal_links,
+            users: cloned_users,
+            files: cloned_files,
+            equations: cloned_equations,
+            equation_references: cloned_equation_references,
+            bibliography: cloned_bibliography,
+            bibliography_citations: cloned_bibliography_citations,
             has_footnote_block: current.has_footnote_block,
             has_toc_block: current.has_toc_block,
             in_footnote: current.in_footnote,
@@ -305,6 +378,24 @@ impl<'r, 't> Parser<'r, 't> {
             current.internal_links = last_known.internal_links;
         }
 
+        if flags.contains(ParserTransactionFlags::Users) {
+            current.users = last_known.users;
+        }
+
+        if flags.contains(ParserTransactionFlags::Files) {
+            current.files = last_known.files;
+        }
+
+        if flags.contains(ParserTransactionFlags::Equations) {
+            current.equations = last_known.equations;
+            current.equation_references = last_known.equation_references;
+        }
+
+        if flags.contains(ParserTransactionFlags::Bibliography) {
+            current.bibliography = last_known.bibliography;
+            current.bibliography_citations = last_known.bibliography_citations;
+        }
+
         if flags.contains(ParserTransactionFlags::Scopes) {
             current.scopes = last_known.scopes;
         }
@@ -374,6 +465,12 @@ impl<'r, 't> Parser<'r, 't> {
             has_footnote_block,
             has_toc_block,
             internal_links,
+            users,
+            files,
+            equations,
+            equation_references,
+            bibliography,
+            bibliography_citations,
         } = parse_internal(
             self.page_info,
             self.page_callbacks.clone(),
@@ -391,26 +488,65 @@ impl<'r, 't> Parser<'r, 't> {
                 for toc_depth in table_of_contents_depths {
                     state
                         .table_of_contents
-                        .borrow_mut()
+                        .lock()
+                        .unwrap()
                         .push(toc_depth.to_owned());
                 }
 
                 for foot in footnotes {
                     let elements =
                         foot.iter().map(|element| element.to_owned()).collect();
-                    state.footnotes.borrow_mut().push(elements);
+                    state.footnotes.lock().unwrap().push(elements);
                 }
 
                 for s in html {
-                    state.html.borrow_mut().push(s);
+                    state.html.lock().unwrap().push(s);
                 }
 
                 for s in code {
-                    state.code.borrow_mut().push(s);
+                    state.code.lock().unwrap().push(s);
                 }
 
                 for internal in internal_links {
-                    state.internal_links.borrow_mut().push(internal.to_owned());
+                    state
+                        .internal_links
+                        .lock()
+                        .unwrap()
+                        .push(internal.to_owned());
+                }
+
+                for user in users {
+                    state
+                        .users
+                        .lock()
+                        .unwrap()
+                        .push(Cow::Owned(user.into_owned()));
+                }
+
+                for file in files {
+                    state.files.lock().unwrap().push(file.to_owned());
+                }
+
+                for equation in equations {
+                    state.equations.lock().unwrap().push(equation);
+                }
+
+                for reference in equation_references {
+                    state.equation_references.lock().unwrap().push(reference);
+                }
+
+                for (label, contents, warning) in bibliography {
+                    let contents =
+                        contents.iter().map(|element| element.to_owned()).collect();
+                    state
+                        .bibliography
+                        .lock()
+                        .unwrap()
+                        .push((label, contents, warning));
+                }
+
+                for citation in bibliography_citations {
+                    state.bibliography_citations.lock().unwrap().push(citation);
                 }
 
                 state.has_footnote_block |= has_footnote_block;
@@ -438,7 +574,7 @@ impl<'r, 't> Parser<'r, 't> {
     }
 
     #[inline]
-    pub fn page_callbacks(&self) -> Rc<dyn PageCallbacks> {
+    pub fn page_callbacks(&self) -> Arc<dyn PageCallbacks> {
         self.page_callbacks.clone()
     }
 
@@ -588,13 +724,14 @@ impl<'r, 't> Parser<'r, 't> {
 
         self.state_mut()
             .table_of_contents
-            .borrow_mut()
+            .lock()
+            .unwrap()
             .push((level, name));
     }
 
     #[cold]
     pub fn remove_table_of_contents(&mut self) -> Vec<(usize, String)> {
-        mem::take(&mut self.state_mut().table_of_contents.borrow_mut())
+        mem::take(&mut self.state_mut().table_of_contents.lock().unwrap())
     }
 
     // WikiScript scopes and variables management
@@ -687,42 +824,131 @@ impl<'r, 't> Parser<'r, 't> {
 
     // Footnotes
     pub fn push_footnote(&mut self, contents: Vec<Element<'t>>) {
-        self.state_mut().footnotes.borrow_mut().push(contents);
+        self.state_mut().footnotes.lock().unwrap().push(contents);
     }
 
     #[cold]
     pub fn remove_footnotes(&mut self) -> Vec<Vec<Element<'t>>> {
-        mem::take(&mut self.state_mut().footnotes.borrow_mut())
+        mem::take(&mut self.state_mut().footnotes.lock().unwrap())
     }
 
     // Code
     pub fn push_code(&mut self, code_type: String, code: String) {
-        self.state_mut().code.borrow_mut().push((code_type, code));
+        self.state_mut()
+            .code
+            .lock()
+            .unwrap()
+            .push((code_type, code));
     }
 
     #[cold]
     pub fn remove_code(&mut self) -> Vec<(String, String)> {
-        mem::take(&mut self.state_mut().code.borrow_mut())
+        mem::take(&mut self.state_mut().code.lock().unwrap())
     }
 
     // HTML
     pub fn push_html(&mut self, html: String) {
-        self.state_mut().html.borrow_mut().push(html);
+        self.state_mut().html.lock().unwrap().push(html);
     }
 
     #[cold]
     pub fn remove_html(&mut self) -> Vec<String> {
-        mem::take(&mut self.state_mut().html.borrow_mut())
+        mem::take(&mut self.state_mut().html.lock().unwrap())
     }
 
     // Internal links
     pub fn push_internal_link(&mut self, page_ref: PageRef<'t>) {
-        self.state_mut().internal_links.borrow_mut().push(page_ref);
+        self.state_mut()
+            .internal_links
+            .lock()
+            .unwrap()
+            .push(page_ref);
     }
 
     #[cold]
     pub fn remove_internal_links(&mut self) -> Vec<PageRef<'t>> {
-        mem::take(&mut self.state_mut().internal_links.borrow_mut())
+        mem::take(&mut self.state_mut().internal_links.lock().unwrap())
+    }
+
+    // Users
+    pub fn push_user(&mut self, name: Cow<'t, str>) {
+        self.state_mut().users.lock().unwrap().push(name);
+    }
+
+    #[cold]
+    pub fn remove_users(&mut self) -> Vec<Cow<'t, str>> {
+        mem::take(&mut self.state_mut().users.lock().unwrap())
+    }
+
+    // Files
+    pub fn push_file(&mut self, file: FileRef<'t>) {
+        self.state_mut().files.lock().unwrap().push(file);
+    }
+
+    #[cold]
+    pub fn remove_files(&mut self) -> Vec<FileRef<'t>> {
+        mem::take(&mut self.state_mut().files.lock().unwrap())
+    }
+
+    // Equations
+    pub fn push_equation(&mut self, name: Option<String>, latex_source: String) {
+        self.state_mut()
+            .equations
+            .lock()
+            .unwrap()
+            .push((name, latex_source));
+    }
+
+    pub fn push_equation_reference(&mut self, name: String, warning: ParseWarning) {
+        self.state_mut()
+            .equation_references
+            .lock()
+            .unwrap()
+            .push((name, warning));
+    }
+
+    #[cold]
+    pub fn remove_equations(&mut self) -> Vec<(Option<String>, String)> {
+        mem::take(&mut self.state_mut().equations.lock().unwrap())
+    }
+
+    #[cold]
+    pub fn remove_equation_references(&mut self) -> Vec<(String, ParseWarning)> {
+        mem::take(&mut self.state_mut().equation_references.lock().unwrap())
+    }
+
+    // Bibliography
+    pub fn push_bibliography_entry(
+        &mut self,
+        label: String,
+        contents: Vec<Element<'t>>,
+        warning: ParseWarning,
+    ) {
+        self.state_mut()
+            .bibliography
+            .lock()
+            .unwrap()
+            .push((label, contents, warning));
+    }
+
+    pub fn push_bibliography_citation(&mut self, label: String, warning: ParseWarning) {
+        self.state_mut()
+            .bibliography_citations
+            .lock()
+            .unwrap()
+            .push((label, warning));
+    }
+
+    #[cold]
+    pub fn remove_bibliography(
+        &mut self,
+    ) -> Vec<(String, Vec<Element<'t>>, ParseWarning)> {
+        mem::take(&mut self.state_mut().bibliography.lock().unwrap())
+    }
+
+    #[cold]
+    pub fn remove_bibliography_citations(&mut self) -> Vec<(String, ParseWarning)> {
+        mem::take(&mut self.state_mut().bibliography_citations.lock().unwrap())
     }
 
     // Special for [[include]], appending a SyntaxTree
@@ -733,10 +959,11 @@ impl<'r, 't> Parser<'r, 't> {
     ) {
         self.state_mut()
             .table_of_contents
-            .borrow_mut()
+            .lock()
+            .unwrap()
             .append(table_of_contents);
 
-        self.state_mut().footnotes.borrow_mut().append(footnotes);
+        self.state_mut().footnotes.lock().unwrap().append(footnotes);
     }
 
     // State evaluation
@@ -858,7 +1085,7 @@ impl<'r, 't> Parser<'r, 't> {
         &self,
         offset: usize,
     ) -> Option<(usize, ParseSuccess<'r, 't, Elements<'t>>)> {
-        match self.ast_cache.borrow().get(&offset) {
+        match self.ast_cache.lock().unwrap().get(&offset) {
             Some((consumed_tokens, success)) => {
                 Some((*consumed_tokens, success.to_owned()))
             }
@@ -874,7 +1101,8 @@ impl<'r, 't> Parser<'r, 't> {
         node: ParseSuccess<'r, 't, Elements<'t>>,
     ) {
         self.ast_cache
-            .borrow_mut()
+            .lock()
+            .unwrap()
             .insert(offset, (consumed_tokens, node));
     }
 
@@ -1094,6 +1322,6 @@ impl<'r, 't> Parser<'r, 't> {
 }
 
 #[inline]
-fn make_shared_vec<T>() -> Rc<RefCell<Vec<T>>> {
-    Rc::new(RefCell::new(Vec::new()))
+fn make_shared_vec<T>() -> Arc<Mutex<Vec<T>>> {
+    Arc::new(Mutex::new(Vec::new()))
 }
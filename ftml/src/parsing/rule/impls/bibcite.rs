@@ -0,0 +1,106 @@
+/*
+ * parsing/rule/impls/bibcite.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rule for bibliography citations, `((bibcite label))`.
+//!
+//! The lexer has no tokens for `((` and `))`, so they are found
+//! within the surrounding text tokens. Any other characters in
+//! those tokens, such as trailing punctuation, are kept as text.
+
+use super::prelude::*;
+
+pub const RULE_BIBCITE: Rule = Rule {
+    name: "bibcite",
+    position: LineRequirement::Any,
+    try_consume_fn,
+};
+
+fn try_consume_fn<'p, 'r, 't>(
+    parser: &'p mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Trying to create bibliography citation");
+
+    // Opening "((", possibly after other text
+    let prefix = match parser.current().slice.strip_suffix("((") {
+        Some(prefix) => prefix,
+        None => return Err(parser.make_warn(ParseWarningKind::RuleFailed)),
+    };
+
+    // Only emitted if no such entry exists once parsing finishes, see parse()
+    let warning = parser.make_warn(ParseWarningKind::NoSuchBibliographyEntry);
+    parser.step()?;
+
+    // "bibcite" keyword, then whitespace
+    let keyword = check_step(parser, Token::Identifier, ParseWarningKind::RuleFailed)?;
+    if !keyword.slice.eq_ignore_ascii_case("bibcite") {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    check_step(parser, Token::Whitespace, ParseWarningKind::RuleFailed)?;
+
+    // Label, until the closing "))"
+    //
+    // Plain text is merged into one token, so the end of
+    // the label may be in the same token, as in "Иванов))".
+    let start = parser.current().span.start;
+    let (end, suffix) = loop {
+        let current = parser.current();
+
+        match current.token {
+            Token::Other => match current.slice.split_once("))") {
+                Some((before, after)) => {
+                    break (current.span.start + before.len(), after)
+                }
+                None => {
+                    parser.step()?;
+                }
+            },
+            Token::LineBreak | Token::ParagraphBreak | Token::InputEnd => {
+                return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+            }
+            _ => {
+                parser.step()?;
+            }
+        }
+    };
+
+    let label = parser.full_text().slice_indices(start, end).trim();
+    if label.is_empty() {
+        return Err(parser.make_warn(ParseWarningKind::RuleFailed));
+    }
+
+    parser.step()?;
+    parser.push_bibliography_citation(str!(label), warning);
+
+    // Build elements, keeping any surrounding text
+    let mut elements = Vec::with_capacity(3);
+
+    if !prefix.is_empty() {
+        elements.push(text!(prefix));
+    }
+
+    elements.push(Element::BibliographyCite(cow!(label)));
+
+    if !suffix.is_empty() {
+        elements.push(text!(suffix));
+    }
+
+    ok!(elements)
+}
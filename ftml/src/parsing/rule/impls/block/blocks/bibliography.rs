@@ -0,0 +1,91 @@
+/*
+ * parsing/rule/impls/block/blocks/bibliography.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::DefinitionListItem;
+
+pub const BLOCK_BIBLIOGRAPHY: BlockRule = BlockRule {
+    name: "block-bibliography",
+    accepts_names: &["bibliography"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing bibliography block (in-head {in_head})");
+    assert!(!flag_star, "Bibliography doesn't allow star flag");
+    assert!(!flag_score, "Bibliography doesn't allow score flag");
+    assert_block_name(&BLOCK_BIBLIOGRAPHY, name);
+
+    // Parse arguments
+    let mut arguments = parser.get_head_map(&BLOCK_BIBLIOGRAPHY, in_head)?;
+    let title = arguments.get("title");
+
+    if !arguments.is_empty() {
+        warn!("Invalid argument keys found");
+        return Err(parser.make_warn(ParseWarningKind::BlockMalformedArguments));
+    }
+
+    // Only emitted if the entry is never cited, see parse()
+    let warning = parser.make_warn(ParseWarningKind::BibliographyEntryUnused);
+
+    // Gather entries, written as a definition list.
+    //
+    // Each key is the label, and the value is the citation.
+    let (elements, exceptions, _) = parser
+        .get_body_elements(&BLOCK_BIBLIOGRAPHY, name, false)?
+        .into();
+
+    for element in elements {
+        if let Element::DefinitionList(items) = element {
+            for DefinitionListItem { key, value } in items {
+                let label = entry_label(&key);
+
+                if !label.is_empty() {
+                    parser.push_bibliography_entry(label, value, warning.clone());
+                }
+            }
+        }
+    }
+
+    ok!(Element::BibliographyBlock { title }, exceptions)
+}
+
+/// Gets the label of a bibliography entry from its definition list key.
+fn entry_label(key: &[Element]) -> String {
+    let mut label = String::new();
+
+    for element in key {
+        if let Element::Text(text) = element {
+            label.push_str(text);
+        }
+    }
+
+    str!(label.trim())
+}
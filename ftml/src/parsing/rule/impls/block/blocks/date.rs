@@ -87,7 +87,7 @@ fn parse_fn<'r, 't>(
 // Parser functions
 
 /// Parse a datetime string and produce its time value, as well as possible timezone info.
-fn parse_date(value: &str) -> Result<Date, DateParseError> {
+pub(crate) fn parse_date(value: &str) -> Result<Date, DateParseError> {
     info!("Parsing possible date value '{value}'");
 
     // Special case, current time
@@ -195,7 +195,7 @@ fn parse_timezone(value: &str) -> Result<FixedOffset, DateParseError> {
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct DateParseError;
+pub(crate) struct DateParseError;
 
 #[inline]
 fn now() -> Date {
@@ -0,0 +1,151 @@
+/*
+ * parsing/rule/impls/block/blocks/embed.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::settings::{is_valid_media_id, EmbedMedia};
+use std::borrow::Cow;
+
+pub const BLOCK_EMBED: BlockRule = BlockRule {
+    name: "block-embed",
+    accepts_names: &["embed"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_embed,
+};
+
+pub const BLOCK_EMBED_VIDEO: BlockRule = BlockRule {
+    name: "block-embed-video",
+    accepts_names: &["embedvideo"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_embed_video,
+};
+
+pub const BLOCK_EMBED_AUDIO: BlockRule = BlockRule {
+    name: "block-embed-audio",
+    accepts_names: &["embedaudio"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_embed_audio,
+};
+
+fn parse_embed<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    assert!(!flag_star, "Embed doesn't allow star flag");
+    assert!(!flag_score, "Embed doesn't allow score flag");
+    assert_block_name(&BLOCK_EMBED, name);
+
+    parse_block(parser, &BLOCK_EMBED, in_head, None)
+}
+
+fn parse_embed_video<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    assert!(!flag_star, "Embed video doesn't allow star flag");
+    assert!(!flag_score, "Embed video doesn't allow score flag");
+    assert_block_name(&BLOCK_EMBED_VIDEO, name);
+
+    parse_block(parser, &BLOCK_EMBED_VIDEO, in_head, Some(EmbedMedia::Video))
+}
+
+fn parse_embed_audio<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    assert!(!flag_star, "Embed audio doesn't allow star flag");
+    assert!(!flag_score, "Embed audio doesn't allow score flag");
+    assert_block_name(&BLOCK_EMBED_AUDIO, name);
+
+    parse_block(parser, &BLOCK_EMBED_AUDIO, in_head, Some(EmbedMedia::Audio))
+}
+
+fn parse_block<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    block_rule: &BlockRule,
+    in_head: bool,
+    media: Option<EmbedMedia>,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!(
+        "Parsing embed block (media {}, in-head {in_head})",
+        media.map_or("any", EmbedMedia::name),
+    );
+
+    let (source, mut arguments) = parser.get_head_name_map(block_rule, in_head)?;
+    let embed_settings = &parser.settings().embed;
+
+    // Either a URL, or a provider name with the media ID as an argument,
+    // as in "[[embed youtube video="..."]]"
+    let (provider, media_id) = match embed_settings.get(source) {
+        Some(provider) if media.is_none() || media == Some(provider.media) => {
+            let media_id = match arguments.get("video").or_else(|| arguments.get("id")) {
+                Some(media_id) if is_valid_media_id(&media_id) => media_id,
+                Some(_) => {
+                    return Err(
+                        parser.make_warn(ParseWarningKind::BlockMalformedArguments)
+                    )
+                }
+                None => {
+                    return Err(parser.make_warn(ParseWarningKind::BlockMissingArguments))
+                }
+            };
+
+            (Cow::Owned(provider.name.to_string()), media_id)
+        }
+        _ => match embed_settings.find(source, media) {
+            Some((provider, media_id)) => {
+                (Cow::Owned(provider.name.to_string()), cow!(media_id))
+            }
+            None => {
+                warn!("Embed URL not accepted by any provider: {source}");
+                return Err(parser.make_warn(ParseWarningKind::EmbedNotAllowed));
+            }
+        },
+    };
+
+    let width = arguments.get_value(parser, "width")?;
+    let height = arguments.get_value(parser, "height")?;
+
+    let element = Element::Embed {
+        provider,
+        media_id,
+        width,
+        height,
+    };
+
+    ok!(element)
+}
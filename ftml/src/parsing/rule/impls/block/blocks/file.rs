@@ -0,0 +1,77 @@
+/*
+ * parsing/rule/impls/block/blocks/file.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::data::FileRef;
+use std::borrow::Cow;
+
+pub const BLOCK_FILE: BlockRule = BlockRule {
+    name: "block-file",
+    accepts_names: &["file"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: false,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing file block (name '{name}', in-head {in_head})");
+    assert!(!flag_star, "File doesn't allow star flag");
+    assert!(!flag_score, "File doesn't allow score flag");
+    assert_block_name(&BLOCK_FILE, name);
+
+    let value =
+        parser.get_head_value(&BLOCK_FILE, in_head, |parser, value| match value {
+            Some(value) => Ok(value.trim()),
+            None => Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
+        })?;
+
+    // Split off the label, as in "[[file document.pdf | label]]"
+    let (source, label) = match value.split_once('|') {
+        Some((source, label)) => (source.trim(), Some(label.trim())),
+        None => (value, None),
+    };
+
+    // Files are either on this page, or "page/file" for other pages
+    let parts: Vec<&str> = source.trim_start_matches('/').split('/').collect();
+    let file = match parts.as_slice() {
+        [file] if !file.is_empty() => FileRef::new(parser.page_info().full_name(), *file),
+        [page, file] if !page.is_empty() && !file.is_empty() => {
+            FileRef::new(*page, *file)
+        }
+        _ => return Err(parser.make_warn(ParseWarningKind::BlockMalformedArguments)),
+    };
+
+    parser.push_file(file.clone());
+
+    let element = Element::File {
+        file,
+        label: label.filter(|label| !label.is_empty()).map(Cow::Borrowed),
+    };
+
+    ok!(element)
+}
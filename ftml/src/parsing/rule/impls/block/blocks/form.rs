@@ -1,8 +1,7 @@
-use std::borrow::Cow;
-
-use crate::tree::FormInput;
-
 use super::prelude::*;
+use crate::tree::{ButtonType, FormInput, SelectOption};
+use std::borrow::Cow;
+use std::convert::TryFrom;
 
 pub const BLOCK_FORM: BlockRule = BlockRule {
     name: "block-form",
@@ -78,9 +77,284 @@ fn parse_form_input<'r, 't>(
 
     let arguments = parser.get_head_map(&BLOCK_FORM, in_head)?;
 
-    let element = Element::FormInput(FormInput {
+    let element = Element::FormInput(FormInput::Input {
         attributes: arguments.to_attribute_map(parser.settings()),
     });
 
     ok!(true; element, vec![])
 }
+
+// Attributes permitted on each kind of form control.
+// Anything else is dropped, the control-specific state is set by the block.
+
+const CHECKBOX_ATTRIBUTES: &[&str] = &[
+    "class", "disabled", "id", "name", "required", "style", "title", "value",
+];
+
+const RADIO_ATTRIBUTES: &[&str] = &[
+    "class", "disabled", "id", "required", "style", "title", "value",
+];
+
+const SELECT_ATTRIBUTES: &[&str] = &[
+    "class", "disabled", "id", "multiple", "name", "required", "size", "style", "title",
+];
+
+const TEXTAREA_ATTRIBUTES: &[&str] = &[
+    "class",
+    "cols",
+    "disabled",
+    "id",
+    "maxlength",
+    "name",
+    "placeholder",
+    "readonly",
+    "required",
+    "rows",
+    "style",
+    "title",
+    "wrap",
+];
+
+const BUTTON_ATTRIBUTES: &[&str] =
+    &["class", "disabled", "id", "name", "style", "title", "value"];
+
+pub const BLOCK_CHECKBOX: BlockRule = BlockRule {
+    name: "block-checkbox",
+    accepts_names: &["checkbox"],
+    accepts_star: true,
+    accepts_score: false,
+    accepts_newlines: false,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_checkbox,
+};
+
+fn parse_checkbox<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing checkbox block (name '{name}', in-head {in_head}, star {flag_star})");
+    assert!(!flag_score, "Checkbox doesn't allow score flag");
+    assert_block_name(&BLOCK_CHECKBOX, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_CHECKBOX, in_head)?;
+    let label = arguments.get("label");
+    let checked = flag_star || arguments.get_bool(parser, "checked")?.unwrap_or(false);
+
+    let mut attributes = arguments.to_attribute_map(parser.settings());
+    attributes.retain_only(CHECKBOX_ATTRIBUTES);
+
+    let element = Element::FormInput(FormInput::Checkbox {
+        label,
+        checked,
+        attributes,
+    });
+
+    ok!(element)
+}
+
+pub const BLOCK_RADIO: BlockRule = BlockRule {
+    name: "block-radio",
+    accepts_names: &["radio", "radio-button"],
+    accepts_star: true,
+    accepts_score: false,
+    accepts_newlines: false,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_radio,
+};
+
+fn parse_radio<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing radio block (name '{name}', in-head {in_head}, star {flag_star})");
+    assert!(!flag_score, "Radio doesn't allow score flag");
+    assert_block_name(&BLOCK_RADIO, name);
+
+    // The value is the name of the radio group
+    let (group, mut arguments) = parser.get_head_name_map(&BLOCK_RADIO, in_head)?;
+    let label = arguments.get("label");
+    let checked = flag_star || arguments.get_bool(parser, "checked")?.unwrap_or(false);
+
+    let mut attributes = arguments.to_attribute_map(parser.settings());
+    attributes.retain_only(RADIO_ATTRIBUTES);
+
+    let element = Element::FormInput(FormInput::Radio {
+        group: cow!(group),
+        label,
+        checked,
+        attributes,
+    });
+
+    ok!(element)
+}
+
+pub const BLOCK_SELECT: BlockRule = BlockRule {
+    name: "block-select",
+    accepts_names: &["select"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_select,
+};
+
+fn parse_select<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing select block (name '{name}', in-head {in_head})");
+    assert!(!flag_star, "Select doesn't allow star flag");
+    assert!(!flag_score, "Select doesn't allow score flag");
+    assert_block_name(&BLOCK_SELECT, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_SELECT, in_head)?;
+    let label = arguments.get("label");
+    let selected = arguments.get("selected");
+
+    let mut attributes = arguments.to_attribute_map(parser.settings());
+    attributes.retain_only(SELECT_ATTRIBUTES);
+
+    let body = parser.get_body_text(&BLOCK_SELECT, name)?;
+    let options = parse_options(body, selected.as_deref());
+
+    let element = Element::FormInput(FormInput::Select {
+        label,
+        options,
+        attributes,
+    });
+
+    ok!(element)
+}
+
+/// Parses the list of options, each a line of the form `: value | label`.
+fn parse_options<'t>(body: &'t str, selected: Option<&str>) -> Vec<SelectOption<'t>> {
+    let mut options = Vec::new();
+
+    for line in body.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let entry = match line.strip_prefix(':') {
+            Some(entry) => entry,
+            None => {
+                warn!("Select line is not a list entry, skipping: {line}");
+                continue;
+            }
+        };
+
+        let (value, label) = match entry.split_once('|') {
+            Some((value, label)) => (value.trim(), label.trim()),
+            None => (entry.trim(), entry.trim()),
+        };
+
+        if value.is_empty() {
+            warn!("Select option has no value, skipping");
+            continue;
+        }
+
+        options.push(SelectOption {
+            value: cow!(value),
+            label: cow!(label),
+            selected: selected == Some(value),
+        });
+    }
+
+    options
+}
+
+pub const BLOCK_TEXTAREA: BlockRule = BlockRule {
+    name: "block-textarea",
+    accepts_names: &["textarea"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_textarea,
+};
+
+fn parse_textarea<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing textarea block (name '{name}', in-head {in_head})");
+    assert!(!flag_star, "Textarea doesn't allow star flag");
+    assert!(!flag_score, "Textarea doesn't allow score flag");
+    assert_block_name(&BLOCK_TEXTAREA, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_TEXTAREA, in_head)?;
+    let label = arguments.get("label");
+
+    let mut attributes = arguments.to_attribute_map(parser.settings());
+    attributes.retain_only(TEXTAREA_ATTRIBUTES);
+
+    let contents = parser.get_body_text(&BLOCK_TEXTAREA, name)?;
+    let contents = contents.strip_prefix('\n').unwrap_or(contents);
+
+    let element = Element::FormInput(FormInput::TextArea {
+        label,
+        contents: cow!(contents),
+        attributes,
+    });
+
+    ok!(element)
+}
+
+pub const BLOCK_BUTTON: BlockRule = BlockRule {
+    name: "block-button",
+    accepts_names: &["button"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: false,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_button,
+};
+
+fn parse_button<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing button block (name '{name}', in-head {in_head})");
+    assert!(!flag_star, "Button doesn't allow star flag");
+    assert!(!flag_score, "Button doesn't allow score flag");
+    assert_block_name(&BLOCK_BUTTON, name);
+
+    let (button_type, mut arguments) =
+        parser.get_head_name_map(&BLOCK_BUTTON, in_head)?;
+    let button_type = match ButtonType::try_from(button_type) {
+        Ok(button_type) => button_type,
+        Err(_) => {
+            warn!("Invalid button type: {button_type}");
+            return Err(parser.make_warn(ParseWarningKind::BlockMalformedArguments));
+        }
+    };
+
+    let label = arguments.get("label");
+
+    let mut attributes = arguments.to_attribute_map(parser.settings());
+    attributes.retain_only(BUTTON_ATTRIBUTES);
+
+    let element = Element::FormInput(FormInput::Button {
+        button_type,
+        label,
+        attributes,
+    });
+
+    ok!(element)
+}
@@ -0,0 +1,208 @@
+/*
+ * parsing/rule/impls/block/blocks/gallery.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::data::PageRef;
+use crate::tree::{GalleryItem, GallerySize, ImageSource};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+/// File extensions which are shown in galleries listing all attachments.
+const IMAGE_EXTENSIONS: [&str; 9] = [
+    "apng", "avif", "bmp", "gif", "jpeg", "jpg", "png", "svg", "webp",
+];
+
+pub const BLOCK_GALLERY: BlockRule = BlockRule {
+    name: "block-gallery",
+    accepts_names: &["gallery"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing gallery block (in-head {in_head})");
+    assert!(!flag_star, "Gallery doesn't allow star flag");
+    assert!(!flag_score, "Gallery doesn't allow score flag");
+    assert_block_name(&BLOCK_GALLERY, name);
+
+    // Parse arguments
+    let mut arguments = parser.get_head_map(&BLOCK_GALLERY, in_head)?;
+
+    let size = match arguments.get("size") {
+        Some(size) => match GallerySize::try_from(size.as_ref()) {
+            Ok(size) => size,
+            Err(_) => {
+                warn!("Invalid gallery size: {size}");
+                return Err(parser.make_warn(ParseWarningKind::BlockMalformedArguments));
+            }
+        },
+        None => GallerySize::default(),
+    };
+
+    let sort_descending = match arguments.get("order").as_deref() {
+        None | Some("none") => None,
+        Some("name") => Some(false),
+        Some("name desc") => Some(true),
+        Some(order) => {
+            warn!("Invalid gallery order: {order}");
+            return Err(parser.make_warn(ParseWarningKind::BlockMalformedArguments));
+        }
+    };
+
+    let viewer = arguments.get_bool(parser, "viewer")?.unwrap_or(true);
+    let attributes = arguments.to_attribute_map(parser.settings());
+
+    // The body is optional, so see if there is one.
+    //
+    // Without a body, the gallery lists all images attached to the page.
+    let mut items = if has_body(parser, name) {
+        let body = parser.get_body_text(&BLOCK_GALLERY, name)?;
+        parse_items(body)
+    } else {
+        attached_items(parser)
+    };
+
+    if let Some(descending) = sort_descending {
+        items.sort_by(|a, b| a.source.file_name().cmp(b.source.file_name()));
+
+        if descending {
+            items.reverse();
+        }
+    }
+
+    for item in &items {
+        if let Some(file) = item.source.file_ref(parser.page_info()) {
+            parser.push_file(file);
+        }
+    }
+
+    ok!(Element::Gallery {
+        items,
+        size,
+        viewer,
+        attributes,
+    })
+}
+
+/// Determines if the gallery has a body, only looking at the next line.
+///
+/// A body starts with a `: file` item, or is immediately closed. Searching any
+/// further would take a later gallery's end block as this one's.
+fn has_body(parser: &Parser, name: &str) -> bool {
+    // The head already took the line break after it
+    let mut parser = parser.clone();
+    if parser.get_optional_space().is_err() {
+        return false;
+    }
+
+    match parser.current().token {
+        Token::Colon => true,
+        Token::LeftBlockEnd => match parser.get_end_block() {
+            Ok(end_name) => end_name.eq_ignore_ascii_case(name),
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
+
+/// Parses the explicit list of images, each a line of the form `: file | caption`.
+fn parse_items<'t>(body: &'t str) -> Vec<GalleryItem<'t>> {
+    let mut items = Vec::new();
+
+    for line in body.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let entry = match line.strip_prefix(':') {
+            Some(entry) => entry,
+            None => {
+                warn!("Gallery line is not a list entry, skipping: {line}");
+                continue;
+            }
+        };
+
+        let (file, caption) = match entry.split_once('|') {
+            Some((file, caption)) => (file.trim(), Some(caption.trim())),
+            None => (entry.trim(), None),
+        };
+
+        match ImageSource::parse(file) {
+            Some(source) => items.push(GalleryItem {
+                source,
+                caption: caption
+                    .filter(|caption| !caption.is_empty())
+                    .map(Cow::Borrowed),
+            }),
+            None => warn!("Invalid gallery image source, skipping: {file}"),
+        }
+    }
+
+    items
+}
+
+/// Gets all the images attached to the current page.
+fn attached_items<'t>(parser: &Parser<'_, 't>) -> Vec<GalleryItem<'t>> {
+    // Attachments are local files, which may not be allowed
+    if !parser.settings().allow_local_paths {
+        warn!("Gallery of attached files when local paths are disabled");
+        return Vec::new();
+    }
+
+    let page_info = parser.page_info();
+    let page_ref = PageRef::new2(
+        page_info
+            .category
+            .as_deref()
+            .unwrap_or("_default")
+            .to_owned(),
+        page_info.page.as_ref().to_owned(),
+    );
+
+    parser
+        .page_callbacks()
+        .list_page_files(&page_ref)
+        .into_iter()
+        .filter(|file| is_image_file(file))
+        .map(|file| GalleryItem {
+            source: ImageSource::File1 { file },
+            caption: None,
+        })
+        .collect()
+}
+
+fn is_image_file(name: &str) -> bool {
+    match name.rsplit_once('.') {
+        Some((_, extension)) => IMAGE_EXTENSIONS
+            .iter()
+            .any(|image_extension| extension.eq_ignore_ascii_case(image_extension)),
+        None => false,
+    }
+}
@@ -0,0 +1,116 @@
+/*
+ * parsing/rule/impls/block/blocks/hidden.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+
+pub const BLOCK_HIDDEN: BlockRule = BlockRule {
+    name: "block-hidden",
+    accepts_names: &["hidden"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_hidden,
+};
+
+pub const BLOCK_INVISIBLE: BlockRule = BlockRule {
+    name: "block-invisible",
+    accepts_names: &["invisible"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_invisible,
+};
+
+fn parse_hidden<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing hidden block (name '{name}', in-head {in_head})");
+    assert!(!flag_star, "Hidden doesn't allow star flag");
+    assert!(!flag_score, "Hidden doesn't allow score flag");
+    assert_block_name(&BLOCK_HIDDEN, name);
+
+    parse_block(
+        parser,
+        &BLOCK_HIDDEN,
+        name,
+        in_head,
+        (ContainerType::Hidden, ContainerType::HiddenBlock),
+    )
+}
+
+fn parse_invisible<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing invisible block (name '{name}', in-head {in_head})");
+    assert!(!flag_star, "Invisible doesn't allow star flag");
+    assert!(!flag_score, "Invisible doesn't allow score flag");
+    assert_block_name(&BLOCK_INVISIBLE, name);
+
+    parse_block(
+        parser,
+        &BLOCK_INVISIBLE,
+        name,
+        in_head,
+        (ContainerType::Invisible, ContainerType::InvisibleBlock),
+    )
+}
+
+fn parse_block<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    block_rule: &BlockRule,
+    name: &'t str,
+    in_head: bool,
+    (inline_type, block_type): (ContainerType, ContainerType),
+) -> ParseResult<'r, 't, Elements<'t>> {
+    let arguments = parser.get_head_map(block_rule, in_head)?;
+    let attributes = arguments.to_attribute_map(parser.settings());
+
+    // If the head is followed by a newline, this is the block variant,
+    // which is wrapped in paragraphs like a div.
+    //
+    // Otherwise it's inline, like a span.
+    if parser.start_of_line() {
+        let (elements, exceptions, _) =
+            parser.get_body_elements(block_rule, name, true)?.into();
+
+        let element =
+            Element::Container(Container::new(block_type, elements, attributes));
+
+        ok!(false; element, exceptions)
+    } else {
+        let (elements, exceptions, paragraph_safe) =
+            parser.get_body_elements(block_rule, name, false)?.into();
+
+        let element =
+            Element::Container(Container::new(inline_type, elements, attributes));
+
+        ok!(paragraph_safe; element, exceptions)
+    }
+}
@@ -81,6 +81,10 @@ fn parse_fn<'r, 't>(
         None => return Err(parser.make_warn(ParseWarningKind::BlockMalformedArguments)),
     };
 
+    if let Some(file) = source.file_ref(parser.page_info()) {
+        parser.push_file(file);
+    }
+
     // Build image
     let element = Element::Image {
         source,
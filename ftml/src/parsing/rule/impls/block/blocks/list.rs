@@ -19,9 +19,13 @@
  */
 
 use super::prelude::*;
+use crate::parsing::rule::impls::block::Arguments;
 use crate::parsing::strip::strip_whitespace;
 use crate::parsing::strip_newlines;
-use crate::tree::{AcceptsPartial, ListItem, ListType, PartialElement};
+use crate::tree::{
+    AcceptsPartial, ListItem, ListType, NumberStyle, NumberedList, PartialElement,
+};
+use std::convert::TryFrom;
 
 // Definitions
 
@@ -80,7 +84,7 @@ fn parse_ordered_block<'r, 't>(
     in_head: bool,
 ) -> ParseResult<'r, 't, Elements<'t>> {
     parse_list_block(
-        (&BLOCK_OL, ListType::Numbered),
+        (&BLOCK_OL, ListType::Numbered(NumberedList::default())),
         parser,
         name,
         flag_star,
@@ -92,7 +96,7 @@ fn parse_ordered_block<'r, 't>(
 // List block
 
 fn parse_list_block<'r, 't>(
-    (block_rule, list_type): (&BlockRule, ListType),
+    (block_rule, mut list_type): (&BlockRule, ListType),
     parser: &mut Parser<'r, 't>,
     name: &'t str,
     flag_star: bool,
@@ -116,7 +120,13 @@ fn parse_list_block<'r, 't>(
     let strip_line_breaks = flag_score;
 
     // Get attributes
-    let arguments = parser.get_head_map(block_rule, in_head)?;
+    let mut arguments = parser.get_head_map(block_rule, in_head)?;
+
+    // Numbered lists have their numbering validated, rather than passed through
+    if let ListType::Numbered(ref mut numbered) = list_type {
+        *numbered = parse_numbering(parser, &mut arguments)?;
+    }
+
     let attributes = arguments.to_attribute_map(parser.settings());
 
     // Get body and convert into list form.
@@ -179,6 +189,32 @@ fn parse_list_block<'r, 't>(
     ok!(false; element, exceptions)
 }
 
+fn parse_numbering<'t>(
+    parser: &Parser<'_, 't>,
+    arguments: &mut Arguments<'t>,
+) -> Result<NumberedList, ParseWarning> {
+    let start = arguments.get_value(parser, "start")?;
+    let reversed = arguments.get_bool(parser, "reversed")?.unwrap_or(false);
+    let continued = arguments.get_bool(parser, "continue")?.unwrap_or(false);
+    let style = match arguments.get("type") {
+        Some(value) => match NumberStyle::try_from(value.as_ref()) {
+            Ok(style) => style,
+            Err(_) => {
+                warn!("Invalid list numbering type: {value}");
+                return Err(parser.make_warn(ParseWarningKind::BlockMalformedArguments));
+            }
+        },
+        None => NumberStyle::Decimal,
+    };
+
+    Ok(NumberedList {
+        start,
+        style,
+        reversed,
+        continued,
+    })
+}
+
 // List item
 
 fn parse_list_item<'r, 't>(
@@ -19,6 +19,7 @@
  */
 
 use super::prelude::*;
+use crate::latex;
 
 pub const BLOCK_MATH: BlockRule = BlockRule {
     name: "block-math",
@@ -30,6 +31,16 @@ pub const BLOCK_MATH: BlockRule = BlockRule {
     parse_fn,
 };
 
+pub const BLOCK_EQUATION_REFERENCE: BlockRule = BlockRule {
+    name: "block-equation-reference",
+    accepts_names: &["equation", "eref", "eqref"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: false,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn: parse_reference,
+};
+
 fn parse_fn<'r, 't>(
     parser: &mut Parser<'r, 't>,
     name: &'t str,
@@ -53,6 +64,11 @@ fn parse_fn<'r, 't>(
         return Err(parser.make_warn(ParseWarningKind::RuleFailed));
     }
 
+    // Blocks with only macro definitions aren't numbered
+    if !latex::is_definitions_only(latex_source) {
+        parser.push_equation(name.as_ref().map(|name| str!(name)), str!(latex_source));
+    }
+
     let element = Element::Math {
         name,
         latex_source: cow!(latex_source),
@@ -60,3 +76,31 @@ fn parse_fn<'r, 't>(
 
     ok!(element)
 }
+
+fn parse_reference<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing equation reference block (name '{name}', in-head {in_head})");
+    assert!(!flag_star, "Equation reference doesn't allow star flag");
+    assert!(!flag_score, "Equation reference doesn't allow score flag");
+    assert_block_name(&BLOCK_EQUATION_REFERENCE, name);
+
+    // Only emitted if no such equation exists once parsing finishes, see parse()
+    let warning = parser.make_warn(ParseWarningKind::NoSuchEquation);
+
+    let name =
+        parser.get_head_value(&BLOCK_EQUATION_REFERENCE, in_head, |parser, value| {
+            match value {
+                Some(name) if !name.trim().is_empty() => Ok(name.trim()),
+                _ => Err(parser.make_warn(ParseWarningKind::BlockMissingArguments)),
+            }
+        })?;
+
+    parser.push_equation_reference(str!(name), warning);
+
+    ok!(Element::EquationReference(cow!(name)))
+}
@@ -67,6 +67,7 @@ mod align_justify;
 mod align_left;
 mod align_right;
 mod anchor;
+mod bibliography;
 mod blockquote;
 mod bold;
 mod char;
@@ -75,9 +76,13 @@ mod collapsible;
 mod date;
 mod del;
 mod div;
+mod embed;
 mod expression;
+mod file;
 mod footnote;
 mod form;
+mod gallery;
+mod hidden;
 mod html;
 mod ifcategory;
 mod iframe;
@@ -91,6 +96,7 @@ mod mark;
 mod math;
 mod module;
 mod monospace;
+mod note;
 mod paragraph;
 mod ruby;
 mod size;
@@ -110,16 +116,25 @@ pub use self::align_justify::BLOCK_ALIGN_JUSTIFY;
 pub use self::align_left::BLOCK_ALIGN_LEFT;
 pub use self::align_right::BLOCK_ALIGN_RIGHT;
 pub use self::anchor::BLOCK_ANCHOR;
+pub use self::bibliography::BLOCK_BIBLIOGRAPHY;
 pub use self::blockquote::BLOCK_BLOCKQUOTE;
 pub use self::bold::BLOCK_BOLD;
 pub use self::char::BLOCK_CHAR;
 pub use self::code::BLOCK_CODE;
 pub use self::collapsible::BLOCK_COLLAPSIBLE;
+pub(crate) use self::date::parse_date;
 pub use self::date::BLOCK_DATE;
 pub use self::del::BLOCK_DEL;
 pub use self::div::BLOCK_DIV;
+pub use self::embed::{BLOCK_EMBED, BLOCK_EMBED_AUDIO, BLOCK_EMBED_VIDEO};
+pub use self::file::BLOCK_FILE;
 pub use self::footnote::{BLOCK_FOOTNOTE, BLOCK_FOOTNOTE_BLOCK};
-pub use self::form::{BLOCK_FORM, BLOCK_FORM_INPUT};
+pub use self::form::{
+    BLOCK_BUTTON, BLOCK_CHECKBOX, BLOCK_FORM, BLOCK_FORM_INPUT, BLOCK_RADIO,
+    BLOCK_SELECT, BLOCK_TEXTAREA,
+};
+pub use self::gallery::BLOCK_GALLERY;
+pub use self::hidden::{BLOCK_HIDDEN, BLOCK_INVISIBLE};
 pub use self::html::BLOCK_HTML;
 pub use self::ifcategory::BLOCK_IFCATEGORY;
 pub use self::iframe::BLOCK_IFRAME;
@@ -130,8 +145,10 @@ pub use self::italics::BLOCK_ITALICS;
 pub use self::lines::BLOCK_LINES;
 pub use self::list::{BLOCK_LI, BLOCK_OL, BLOCK_UL};
 pub use self::mark::BLOCK_MARK;
+pub use self::math::{BLOCK_EQUATION_REFERENCE, BLOCK_MATH};
 pub use self::module::BLOCK_MODULE;
 pub use self::monospace::BLOCK_MONOSPACE;
+pub use self::note::BLOCK_NOTE;
 pub use self::paragraph::BLOCK_PARAGRAPH;
 pub use self::ruby::{BLOCK_RB, BLOCK_RT, BLOCK_RUBY};
 pub use self::size::BLOCK_SIZE;
@@ -0,0 +1,75 @@
+/*
+ * parsing/rule/impls/block/blocks/note.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::NoteType;
+use std::convert::TryFrom;
+
+pub const BLOCK_NOTE: BlockRule = BlockRule {
+    name: "block-note",
+    accepts_names: &["note"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    accepts_partial: AcceptsPartial::None,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing note block (name '{name}', in-head {in_head})");
+    assert!(!flag_star, "Note doesn't allow star flag");
+    assert!(!flag_score, "Note doesn't allow score flag");
+    assert_block_name(&BLOCK_NOTE, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_NOTE, in_head)?;
+
+    let note_type = match arguments.get("type") {
+        Some(value) => match NoteType::try_from(value.as_ref()) {
+            Ok(note_type) => note_type,
+            Err(_) => {
+                warn!("Invalid note type: {value}");
+                return Err(parser.make_warn(ParseWarningKind::BlockMalformedArguments));
+            }
+        },
+        None => NoteType::default(),
+    };
+
+    let title = arguments.get("title");
+
+    // Get body content, with paragraphs.
+    // Discard paragraph_safe, since notes never are.
+    let (elements, exceptions, _) =
+        parser.get_body_elements(&BLOCK_NOTE, name, true)?.into();
+
+    let element = Element::Note {
+        note_type,
+        title,
+        elements,
+        attributes: arguments.to_attribute_map(parser.settings()),
+    };
+
+    ok!(element, exceptions)
+}
@@ -19,10 +19,14 @@
  */
 
 use super::prelude::*;
+use crate::parsing::rule::impls::block::Arguments;
+use crate::parsing::rule::impls::table::check_column_types;
 use crate::parsing::strip_whitespace;
 use crate::tree::{
-    AcceptsPartial, AttributeMap, PartialElement, Table, TableCell, TableRow,
+    AcceptsPartial, ColumnType, PartialElement, Table, TableCell, TableRow, TableSection,
+    MAX_COLUMN_SPAN, MAX_ROW_SPAN,
 };
+use std::convert::TryFrom;
 use std::num::NonZeroU32;
 
 pub const BLOCK_TABLE: BlockRule = BlockRule {
@@ -70,7 +74,7 @@ pub const BLOCK_TABLE_CELL_HEADER: BlockRule = BlockRule {
 #[derive(Debug)]
 struct ParsedBlock<'t> {
     elements: Vec<Element<'t>>,
-    attributes: AttributeMap<'t>,
+    arguments: Arguments<'t>,
     exceptions: Vec<ParseException>,
 }
 
@@ -97,9 +101,8 @@ where
     );
     assert_block_name(block_rule, name);
 
-    // Get attributes
+    // Get arguments
     let arguments = parser.get_head_map(block_rule, in_head)?;
-    let attributes = arguments.to_attribute_map(parser.settings());
 
     // Get body elements
     let (elements, exceptions, _) =
@@ -108,7 +111,7 @@ where
     // Return result
     Ok(ParsedBlock {
         elements,
-        attributes,
+        arguments,
         exceptions,
     })
 }
@@ -148,8 +151,8 @@ fn parse_table<'r, 't>(
     // Get block contents.
     let ParsedBlock {
         elements,
-        attributes,
-        exceptions,
+        mut arguments,
+        mut exceptions,
     } = parse_block(
         parser,
         name,
@@ -160,9 +163,43 @@ fn parse_table<'r, 't>(
     )?;
 
     let rows = extract_table_items!(parser, elements; TableRow, TableContainsNonRow);
+    let caption = arguments.get("caption");
+
+    // Column types are a comma-separated list, like "text, number, date"
+    let mut column_types = Vec::new();
+    if let Some(value) = arguments.get("types") {
+        for name in value.split(',') {
+            match ColumnType::try_from(name.trim()) {
+                Ok(column_type) => column_types.push(column_type),
+                Err(_) => {
+                    warn!("Invalid table column type: {name}");
+                    return Err(
+                        parser.make_warn(ParseWarningKind::BlockMalformedArguments)
+                    );
+                }
+            }
+        }
+    }
+
+    let sortable = arguments
+        .get_bool(parser, "sortable")?
+        .unwrap_or(!column_types.is_empty());
+
+    let attributes = arguments.to_attribute_map(parser.settings());
 
     // Build and return table element
-    let element = Element::Table(Table { rows, attributes });
+    let mut table = Table {
+        rows,
+        caption,
+        sortable,
+        column_types,
+        attributes,
+    };
+    table.clamp_row_spans();
+    table.detect_head_rows();
+    check_column_types(parser, &mut table, &mut exceptions);
+
+    let element = Element::Table(table);
 
     ok!(false; element, exceptions)
 }
@@ -179,7 +216,7 @@ fn parse_row<'r, 't>(
     // Get block contents.
     let ParsedBlock {
         elements,
-        attributes,
+        mut arguments,
         exceptions,
     } = parse_block(
         parser,
@@ -193,9 +230,23 @@ fn parse_row<'r, 't>(
     let cells =
         extract_table_items!(parser, elements; TableCell, TableRowContainsNonCell);
 
+    let section = match arguments.get("section") {
+        Some(value) => match TableSection::try_from(value.as_ref()) {
+            Ok(section) => section,
+            Err(_) => {
+                warn!("Invalid table section: {value}");
+                return Err(parser.make_warn(ParseWarningKind::BlockMalformedArguments));
+            }
+        },
+        None => TableSection::Body,
+    };
+
     // Build and return table row
-    let element =
-        Element::Partial(PartialElement::TableRow(TableRow { cells, attributes }));
+    let element = Element::Partial(PartialElement::TableRow(TableRow {
+        cells,
+        section,
+        attributes: arguments.to_attribute_map(parser.settings()),
+    }));
 
     ok!(false; element, exceptions)
 }
@@ -212,7 +263,7 @@ fn parse_cell_regular<'r, 't>(
     // Get block contents.
     let ParsedBlock {
         elements,
-        attributes,
+        arguments,
         exceptions,
     } = parse_block(
         parser,
@@ -223,7 +274,7 @@ fn parse_cell_regular<'r, 't>(
         (&BLOCK_TABLE_CELL_REGULAR, "table cell (regular)"),
     )?;
 
-    parse_cell(elements, attributes, exceptions, false)
+    parse_cell(parser, elements, arguments, exceptions, false)
 }
 
 fn parse_cell_header<'r, 't>(
@@ -236,7 +287,7 @@ fn parse_cell_header<'r, 't>(
     // Get block contents.
     let ParsedBlock {
         elements,
-        attributes,
+        arguments,
         exceptions,
     } = parse_block(
         parser,
@@ -247,12 +298,13 @@ fn parse_cell_header<'r, 't>(
         (&BLOCK_TABLE_CELL_HEADER, "table cell (header)"),
     )?;
 
-    parse_cell(elements, attributes, exceptions, true)
+    parse_cell(parser, elements, arguments, exceptions, true)
 }
 
 fn parse_cell<'r, 't>(
+    parser: &Parser<'r, 't>,
     mut elements: Vec<Element<'t>>,
-    mut attributes: AttributeMap<'t>,
+    mut arguments: Arguments<'t>,
     exceptions: Vec<ParseException>,
     header: bool,
 ) -> ParseResult<'r, 't, Elements<'t>> {
@@ -263,17 +315,26 @@ fn parse_cell<'r, 't>(
     // Remove leading and trailing whitespace
     strip_whitespace(&mut elements);
 
+    // Extract row-span, which must be a positive integer.
+    // It is limited to the rows in the table once it is built.
+    let row_span: NonZeroU32 = arguments.get_value(parser, "rowspan")?.unwrap_or(*ONE);
+    let row_span = NonZeroU32::new(row_span.get().min(MAX_ROW_SPAN)).unwrap();
+
     // Extract column-span if specified via attributes.
     // If not specified, then the default.
-    let column_span = match attributes.remove("colspan") {
+    let mut attributes = arguments.to_attribute_map(parser.settings());
+    let column_span: NonZeroU32 = match attributes.remove("colspan") {
         Some(value) => value.parse().unwrap_or(*ONE),
         None => *ONE,
     };
+    let column_span = NonZeroU32::new(column_span.get().min(MAX_COLUMN_SPAN)).unwrap();
 
     let element = Element::Partial(PartialElement::TableCell(TableCell {
         header,
         column_span,
+        row_span,
         align: None,
+        sort_value: None,
         elements,
         attributes,
     }));
@@ -50,6 +50,7 @@ fn parse_fn<'r, 't>(
     let mut name_with_vars = cow!(name);
 
     parser.replace_variables(name_with_vars.to_mut());
+    parser.push_user(name_with_vars.clone());
 
     let element = Element::User {
         name: name_with_vars,
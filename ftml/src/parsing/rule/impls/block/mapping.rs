@@ -22,42 +22,56 @@ use super::{blocks::*, BlockRule};
 use std::collections::HashMap;
 use unicase::UniCase;
 
-pub const BLOCK_RULES: [BlockRule; 56] = [
+pub const BLOCK_RULES: [BlockRule; 72] = [
     BLOCK_ALIGN_CENTER,
     BLOCK_ALIGN_JUSTIFY,
     BLOCK_ALIGN_LEFT,
     BLOCK_ALIGN_RIGHT,
     BLOCK_ANCHOR,
+    BLOCK_BIBLIOGRAPHY,
     BLOCK_BLOCKQUOTE,
     BLOCK_BOLD,
+    BLOCK_BUTTON,
     BLOCK_CHAR,
+    BLOCK_CHECKBOX,
     BLOCK_CODE,
     BLOCK_COLLAPSIBLE,
     BLOCK_DATE,
     BLOCK_DEL,
     BLOCK_DIV,
+    BLOCK_EMBED,
+    BLOCK_EMBED_AUDIO,
+    BLOCK_EMBED_VIDEO,
+    BLOCK_EQUATION_REFERENCE,
+    BLOCK_FILE,
     BLOCK_FOOTNOTE,
     BLOCK_FOOTNOTE_BLOCK,
     BLOCK_FORM,
     BLOCK_FORM_INPUT,
+    BLOCK_GALLERY,
+    BLOCK_HIDDEN,
     BLOCK_HTML,
     BLOCK_IFCATEGORY,
     BLOCK_IFRAME,
     BLOCK_IFTAGS,
     BLOCK_IMAGE,
     BLOCK_INS,
+    BLOCK_INVISIBLE,
     BLOCK_ITALICS,
     BLOCK_LI,
     BLOCK_LINES,
     BLOCK_MARK,
-    //BLOCK_MATH,
+    BLOCK_MATH,
     BLOCK_MODULE,
     BLOCK_MONOSPACE,
+    BLOCK_NOTE,
     BLOCK_OL,
     BLOCK_PARAGRAPH,
+    BLOCK_RADIO,
     BLOCK_RB,
     BLOCK_RT,
     BLOCK_RUBY,
+    BLOCK_SELECT,
     BLOCK_SIZE,
     BLOCK_SPAN,
     BLOCK_STRIKETHROUGH,
@@ -70,6 +84,7 @@ pub const BLOCK_RULES: [BlockRule; 56] = [
     BLOCK_TABLE_OF_CONTENTS,
     BLOCK_TABLE_ROW,
     BLOCK_TABVIEW,
+    BLOCK_TEXTAREA,
     BLOCK_UL,
     BLOCK_UNDERLINE,
     BLOCK_USER,
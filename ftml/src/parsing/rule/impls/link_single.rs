@@ -25,6 +25,7 @@
 //! Its syntax is `[https://example.com/ Label text]`.
 
 use super::prelude::*;
+use crate::data::FileRef;
 use crate::tree::{AnchorTarget, LinkLabel, LinkLocation, LinkType};
 use crate::url::validate_href;
 
@@ -83,6 +84,11 @@ fn try_consume_link<'p, 'r, 't>(
         LinkType::Direct
     };
 
+    // Record links to attached files
+    if let Some(file) = FileRef::parse_url(url) {
+        parser.push_file(file);
+    }
+
     debug!("Retrieved URL '{url}' for link, now fetching label");
 
     // Gather label for link
@@ -30,6 +30,7 @@
 
 use super::prelude::*;
 use crate::{
+    data::FileRef,
     tree::{AnchorTarget, LinkLabel, LinkLocation},
     url::validate_href,
 };
@@ -129,6 +130,10 @@ fn build_same<'p, 'r, 't>(
             if !validate_href(url, true) {
                 return Err(parser.make_warn(ParseWarningKind::RuleFailed));
             }
+
+            if let Some(file) = FileRef::parse_url(url) {
+                parser.push_file(file.to_owned());
+            }
         }
         _ => return Err(parser.make_warn(ParseWarningKind::CrossSiteRef)),
     }
@@ -198,6 +203,10 @@ fn build_separate<'p, 'r, 't>(
             if !validate_href(url, true) {
                 return Err(parser.make_warn(ParseWarningKind::RuleFailed));
             }
+
+            if let Some(file) = FileRef::parse_url(url) {
+                parser.push_file(file.to_owned());
+            }
         }
     }
 
@@ -21,14 +21,14 @@
 use super::prelude::*;
 use crate::parsing::strip::strip_whitespace;
 use crate::parsing::{process_depths, DepthItem, DepthList};
-use crate::tree::{AttributeMap, ListItem, ListType};
+use crate::tree::{AttributeMap, ListItem, ListType, NumberedList};
 
 const MAX_LIST_DEPTH: usize = 20;
 
-const fn get_list_type(token: Token) -> Option<ListType> {
+fn get_list_type(token: Token) -> Option<ListType> {
     match token {
         Token::BulletItem => Some(ListType::Bullet),
-        Token::NumberedItem => Some(ListType::Numbered),
+        Token::NumberedItem => Some(ListType::Numbered(NumberedList::default())),
         _ => None,
     }
 }
@@ -48,6 +48,7 @@ fn try_consume_fn<'p, 'r, 't>(
     // Context variables
     let mut depths = Vec::new();
     let mut exceptions = Vec::new();
+    let mut continued = false;
 
     // Blockquotes are always paragraph-unsafe,
     // but we need this binding for chain().
@@ -94,6 +95,19 @@ fn try_consume_fn<'p, 'r, 't>(
         };
         parser.step()?;
 
+        // Check for "#+", which continues the numbering of the previous list.
+        // This only applies to the first item of the list.
+        if depths.is_empty()
+            && depth == 0
+            && matches!(list_type, ListType::Numbered(_))
+            && parser.current().token == Token::Heading
+            && parser.current().slice == "+"
+        {
+            debug!("Found list continuation marker");
+            continued = true;
+            parser.step()?;
+        }
+
         debug!("Parsing list item '{}'", list_type.name());
 
         // For now, always expect whitespace after the bullet
@@ -146,11 +160,22 @@ fn try_consume_fn<'p, 'r, 't>(
     }
 
     let depth_lists = process_depths(ListType::Generic, depths);
-    let elements: Vec<Element> = depth_lists
+    let mut elements: Vec<Element> = depth_lists
         .into_iter()
         .map(|(ltype, depth_list)| build_list_element(ltype, depth_list))
         .collect();
 
+    // Mark the list for continuation, which is resolved once parsing is finished
+    if continued {
+        if let Some(Element::List {
+            ltype: ListType::Numbered(numbered),
+            ..
+        }) = elements.first_mut()
+        {
+            numbered.continued = true;
+        }
+    }
+
     ok!(paragraph_safe; elements, exceptions)
 }
 
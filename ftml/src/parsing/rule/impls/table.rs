@@ -18,8 +18,16 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use super::block::blocks::parse_date;
 use super::prelude::*;
-use crate::tree::{Alignment, Table, TableCell, TableRow};
+use crate::i18n;
+use crate::parsing::ParseException;
+use crate::tree::{
+    Alignment, ColumnType, Table, TableCell, TableRow, TableSection, MAX_COLUMN_SPAN,
+    MAX_ROW_SPAN,
+};
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::mem;
 use std::num::NonZeroU32;
 
@@ -28,6 +36,7 @@ struct TableCellStart {
     align: Option<Alignment>,
     header: bool,
     column_span: NonZeroU32,
+    column_type: Option<ColumnType>,
 }
 
 pub const RULE_TABLE: Rule = Rule {
@@ -41,6 +50,7 @@ fn try_consume_fn<'p, 'r, 't>(
 ) -> ParseResult<'r, 't, Elements<'t>> {
     info!("Trying to parse simple table");
     let mut rows = Vec::new();
+    let mut column_types = Vec::new();
     let mut exceptions = Vec::new();
     let mut _paragraph_break = false;
 
@@ -48,11 +58,13 @@ fn try_consume_fn<'p, 'r, 't>(
         info!("Parsing next table row");
 
         let mut cells = Vec::new();
+        let mut column = 0;
 
         macro_rules! build_row {
             () => {
                 rows.push(TableRow {
                     cells: mem::take(&mut cells),
+                    section: TableSection::Body,
                     attributes: AttributeMap::new(),
                 })
             };
@@ -78,18 +90,32 @@ fn try_consume_fn<'p, 'r, 't>(
                 align,
                 header,
                 column_span,
+                column_type,
             } = match parse_cell_start(parser)? {
                 Some(cell_start) => cell_start,
                 None => finish_table!(),
             };
 
+            // Record the type declared by a header cell, if any
+            let end_column = column + column_span.get() as usize;
+            if let Some(column_type) = column_type {
+                if column_types.len() < end_column {
+                    column_types.resize(end_column, ColumnType::Text);
+                }
+
+                column_types[column..end_column].fill(column_type);
+            }
+            column = end_column;
+
             macro_rules! build_cell {
                 () => {
                     cells.push(TableCell {
                         elements: mem::take(&mut elements),
                         header,
                         column_span,
+                        row_span: NonZeroU32::new(1).unwrap(),
                         align,
+                        sort_value: None,
                         attributes: AttributeMap::new(),
                     })
                 };
@@ -182,11 +208,22 @@ fn try_consume_fn<'p, 'r, 't>(
         build_row!();
     }
 
+    merge_row_continuations(&mut rows);
+
     // Build table
     let mut attributes = AttributeMap::new();
     attributes.insert("class", cow!("wiki-content-table"));
 
-    let table = Table { rows, attributes };
+    // Declaring any column types makes the table sortable
+    let mut table = Table {
+        rows,
+        caption: None,
+        sortable: !column_types.is_empty(),
+        column_types,
+        attributes,
+    };
+    table.detect_head_rows();
+    check_column_types(parser, &mut table, &mut exceptions);
     ok!(false; Element::Table(table), exceptions)
 }
 
@@ -238,12 +275,266 @@ fn parse_cell_start(parser: &mut Parser) -> Result<Option<TableCellStart>, Parse
         }
     };
 
-    let column_span =
-        NonZeroU32::new(span).expect("Cell start exited without column span");
+    let column_span = NonZeroU32::new(span.min(MAX_COLUMN_SPAN))
+        .expect("Cell start exited without column span");
+
+    // Header cells may declare a column type, like "||~[number] Score"
+    let column_type = if header {
+        parse_column_type(parser)?
+    } else {
+        None
+    };
 
     Ok(Some(TableCellStart {
         align,
         header,
         column_span,
+        column_type,
     }))
 }
+
+/// Parse out a column type immediately following a header cell start.
+///
+/// If the tokens aren't a bracketed, valid column type, nothing is consumed
+/// and they're parsed as part of the cell contents instead.
+fn parse_column_type(parser: &mut Parser) -> Result<Option<ColumnType>, ParseWarning> {
+    if parser.current().token != Token::LeftBracket {
+        return Ok(None);
+    }
+
+    let column_type = match (parser.look_ahead(0), parser.look_ahead(1)) {
+        (Some(name), Some(close))
+            if name.token == Token::Identifier && close.token == Token::RightBracket =>
+        {
+            match ColumnType::try_from(name.slice) {
+                Ok(column_type) => column_type,
+                Err(_) => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    debug!("Found column type '{}'", column_type.name());
+    parser.step_n(3)?;
+    Ok(Some(column_type))
+}
+
+/// Merges cells containing only `^` into the cell above them.
+///
+/// The cell above has its row span extended, and the `^` cell is removed.
+/// Cells are matched by column, taking into account the column spans of
+/// the preceding cells. If there is no cell directly above which ends on
+/// the previous row, the `^` is kept as regular text.
+///
+/// Rows left without any cells are removed, along with the row
+/// spans extended through them, so no empty rows are output.
+fn merge_row_continuations(rows: &mut Vec<TableRow>) {
+    // For each row, which cell (as a row and cell index) covers each column.
+    let mut grid: Vec<Vec<(usize, usize)>> = Vec::with_capacity(rows.len());
+    let mut removed = Vec::new();
+
+    for row_index in 0..rows.len() {
+        let mut covered = Vec::new();
+
+        for cell_index in 0..rows[row_index].cells.len() {
+            let column = covered.len();
+            let cell = &rows[row_index].cells[cell_index];
+            let above = match row_index.checked_sub(1) {
+                Some(above_index) if is_continuation(cell) => grid[above_index]
+                    .get(column)
+                    .copied()
+                    .filter(|&(start_row, start_cell)| {
+                        let row_span = rows[start_row].cells[start_cell].row_span.get();
+                        start_row + row_span as usize == row_index
+                            && row_span < MAX_ROW_SPAN
+                    }),
+                _ => None,
+            };
+
+            let (owner, column_span) = match above {
+                Some((start_row, start_cell)) => {
+                    let above_cell = &mut rows[start_row].cells[start_cell];
+                    above_cell.row_span = above_cell.row_span.saturating_add(1);
+                    removed.push((row_index, cell_index));
+
+                    ((start_row, start_cell), above_cell.column_span)
+                }
+                None => ((row_index, cell_index), cell.column_span),
+            };
+
+            for _ in 0..column_span.get() {
+                covered.push(owner);
+            }
+        }
+
+        grid.push(covered);
+    }
+
+    // Remove afterwards, so the indices above stay valid
+    for (row_index, cell_index) in removed.into_iter().rev() {
+        rows[row_index].cells.remove(cell_index);
+    }
+
+    for row_index in (0..rows.len()).rev() {
+        if !rows[row_index].cells.is_empty() {
+            continue;
+        }
+
+        for (start_row, row) in rows[..row_index].iter_mut().enumerate() {
+            for cell in &mut row.cells {
+                let row_span = cell.row_span.get() as usize;
+
+                if start_row + row_span > row_index {
+                    cell.row_span = NonZeroU32::new(cell.row_span.get() - 1)
+                        .expect("Row span covering a later row is at least two");
+                }
+            }
+        }
+
+        rows.remove(row_index);
+    }
+}
+
+fn is_continuation(cell: &TableCell) -> bool {
+    matches!(cell.elements.as_slice(), [Element::Text(text)] if text == "^")
+}
+
+/// Type-checks the body cells of a sortable table against their column types.
+///
+/// Each cell which matches is given the value it should be sorted by, which
+/// may differ from how it's displayed. Each which doesn't adds a warning.
+/// Empty cells and text columns are left as-is.
+pub(crate) fn check_column_types<'t>(
+    parser: &Parser<'_, 't>,
+    table: &mut Table<'t>,
+    exceptions: &mut Vec<ParseException>,
+) {
+    if !table.sortable {
+        return;
+    }
+
+    let starts = table.column_starts();
+    let Table {
+        rows, column_types, ..
+    } = table;
+
+    for (row, starts) in rows.iter_mut().zip(starts) {
+        if row.section == TableSection::Head {
+            continue;
+        }
+
+        for (cell, column) in row.cells.iter_mut().zip(starts) {
+            if cell.header {
+                continue;
+            }
+
+            let column_type = column_types.get(column).copied().unwrap_or_default();
+            match get_sort_value(parser, column_type, &cell.elements) {
+                Ok(sort_value) => cell.sort_value = sort_value.map(Cow::Owned),
+                Err(()) => {
+                    warn!(
+                        "Table cell doesn't match column type '{}'",
+                        column_type.name(),
+                    );
+
+                    exceptions.push(ParseException::Warning(
+                        parser.make_warn(ParseWarningKind::TableCellTypeMismatch),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn get_sort_value(
+    parser: &Parser,
+    column_type: ColumnType,
+    elements: &[Element],
+) -> Result<Option<String>, ()> {
+    // Dates sort by their value, regardless of how they're formatted
+    if column_type == ColumnType::Date {
+        if let [Element::Date { value, .. }] = elements {
+            return Ok(Some(value.timestamp().to_string()));
+        }
+    }
+
+    let mut text = String::new();
+    collect_cell_text(elements, &mut text);
+
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let sort_value = match column_type {
+        ColumnType::Text => return Ok(None),
+        ColumnType::Number => {
+            let language = &parser.page_info().language;
+            let value = parse_number(text, language).ok_or(())?;
+            value.to_string()
+        }
+        ColumnType::Date => {
+            let date = parse_date(text).map_err(|_| ())?;
+            date.timestamp().to_string()
+        }
+        ColumnType::Rating => {
+            // Ratings are written like "+25" or "-3"
+            let value = text.strip_prefix('+').unwrap_or(text);
+            value.parse::<i64>().map_err(|_| ())?.to_string()
+        }
+    };
+
+    Ok(Some(sort_value))
+}
+
+/// Parses a number written in the conventions of the given language.
+///
+/// Digits may be grouped in threes, like "1,024" or "1 024", with spaces
+/// in any language and commas where they aren't the decimal separator.
+fn parse_number(text: &str, language: &str) -> Option<f64> {
+    let decimal = i18n::decimal_separator(language);
+    let is_group_separator = |c: char| {
+        matches!(c, ' ' | '\u{a0}' | '\u{202f}') || (decimal != ',' && c == ',')
+    };
+
+    let (integer, fraction) = match text.split_once(decimal) {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (text, None),
+    };
+
+    // Only the leading group may be shorter, so "1,5" isn't read as 15
+    let mut groups = integer.split(is_group_separator);
+    let mut number = String::from(groups.next().unwrap_or(""));
+
+    for group in groups {
+        if group.len() != 3 || !group.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        number.push_str(group);
+    }
+
+    if let Some(fraction) = fraction {
+        number.push('.');
+        number.push_str(fraction);
+    }
+
+    let value = number.parse::<f64>().ok()?;
+    if value.is_finite() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+fn collect_cell_text(elements: &[Element], text: &mut String) {
+    for element in elements {
+        match element {
+            Element::Text(value) | Element::Raw(value) => text.push_str(value),
+            Element::Container(container) => {
+                collect_cell_text(container.elements(), text)
+            }
+            _ => (),
+        }
+    }
+}
@@ -105,7 +105,7 @@ lazy_static! {
             Token::RightComment => vec![],
 
             // Fallback
-            Token::Other => vec![RULE_TEXT],
+            Token::Other => vec![RULE_BIBCITE, RULE_TEXT],
         }
     };
 }
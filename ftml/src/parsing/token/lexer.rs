@@ -0,0 +1,461 @@
+/*
+ * parsing/token/lexer.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Hand-written lexer, producing the same tokens as `parsing/lexer.pest`.
+//!
+//! Each rule of the grammar is matched by looking at the first byte,
+//! then checking the same alternatives in the same order as the pest
+//! grammar does. Bytes which cannot start any token are looked up in
+//! a table and skipped in bulk, becoming a single `Token::Other`.
+//!
+//! Since every token begins with an ASCII character, or a non-breaking
+//! space (`0xC2 0xA0` in UTF-8), all token boundaries fall on character
+//! boundaries even though the input is scanned byte by byte.
+
+use super::{ExtractedToken, Token};
+
+/// Which bytes may begin a token other than `Token::Other`.
+static TOKEN_START: [bool; 256] = {
+    let mut table = [false; 256];
+    let mut byte = 0;
+
+    while byte < 256 {
+        table[byte] = matches!(
+            byte as u8,
+            b'0'..=b'9'
+                | b'a'..=b'z'
+                | b'A'..=b'Z'
+                | b'@'
+                | b'&'
+                | b'>'
+                | b'<'
+                | b'['
+                | b']'
+                | b'-'
+                | b'{'
+                | b'}'
+                | b'\\'
+                | b'"'
+                | b'$'
+                | b'*'
+                | b'/'
+                | b'_'
+                | b'^'
+                | b','
+                | b'#'
+                | b'|'
+                | b'~'
+                | b'='
+                | b':'
+                | b'+'
+                | b'\n'
+                | b'\r'
+                | b' '
+                | b'\t'
+                | 0xC2 // first byte of U+00A0
+        );
+
+        byte += 1;
+    }
+
+    table
+};
+
+pub fn lex(text: &str) -> Vec<ExtractedToken<'_>> {
+    let mut lexer = Lexer {
+        text,
+        bytes: text.as_bytes(),
+        tokens: Vec::with_capacity(text.len() / 4 + 2),
+        other_start: None,
+    };
+
+    lexer.push(Token::InputStart, 0, 0);
+    lexer.run();
+    lexer.push(Token::InputEnd, text.len(), text.len());
+    lexer.tokens
+}
+
+#[derive(Debug)]
+struct Lexer<'t> {
+    text: &'t str,
+    bytes: &'t [u8],
+    tokens: Vec<ExtractedToken<'t>>,
+    other_start: Option<usize>,
+}
+
+impl<'t> Lexer<'t> {
+    fn run(&mut self) {
+        let mut pos = 0;
+
+        while pos < self.bytes.len() {
+            let byte = self.bytes[pos];
+
+            // Fast path, plain text
+            if !TOKEN_START[usize::from(byte)] {
+                self.other_start.get_or_insert(pos);
+                pos += 1;
+                continue;
+            }
+
+            // Special case to handle those pesky "[[[[" and "]]]]"s
+            // These are [[[ triple links surrounded by constant [ brackets.
+            if self.starts_with(pos, "[[[[") {
+                self.flush_other(pos);
+                self.push(Token::LeftBracket, pos, pos + 1);
+                self.push(Token::LeftLink, pos + 1, pos + 4);
+                pos += 4;
+                continue;
+            }
+
+            if self.starts_with(pos, "]]]]") {
+                self.flush_other(pos);
+                self.push(Token::RightLink, pos, pos + 3);
+                self.push(Token::RightBracket, pos + 3, pos + 4);
+                pos += 4;
+                continue;
+            }
+
+            match self.match_token(pos) {
+                Some((token, len)) => {
+                    self.flush_other(pos);
+                    self.push(token, pos, pos + len);
+                    pos += len;
+                }
+                None if byte == b'@' => {
+                    // A lone "@" is kept as its own token, since
+                    // the raw rule checks for "@@@@@" as Raw Raw Other("@").
+                    self.flush_other(pos);
+                    self.push(Token::Other, pos, pos + 1);
+                    pos += 1;
+                }
+                None => {
+                    self.other_start.get_or_insert(pos);
+                    pos += 1;
+                }
+            }
+        }
+
+        self.flush_other(pos);
+    }
+
+    /// Attempts to match a token at the given position.
+    ///
+    /// Alternatives are tried in the same order as the pest grammar,
+    /// restricted to those which can begin with the byte at `pos`.
+    fn match_token(&self, pos: usize) -> Option<(Token, usize)> {
+        let byte = self.bytes[pos];
+        let next = self.byte_at(pos + 1);
+
+        let matched = match byte {
+            b'@' => match next {
+                Some(b'@') if self.byte_at(pos + 2) != Some(b'<') => (Token::Raw, 2),
+                Some(b'<') => (Token::LeftRaw, 2),
+                _ => return None,
+            },
+            b'&' => return self.html_entity(pos).map(|len| (Token::HtmlEntity, len)),
+            b'>' => {
+                if next == Some(b'@') {
+                    (Token::RightRaw, 2)
+                } else if self.quote_follows(pos + 1) {
+                    (Token::Quote, 1)
+                } else if next == Some(b'>') {
+                    (Token::RightDoubleAngle, 2)
+                } else {
+                    return None;
+                }
+            }
+            b'<' if next == Some(b'<') => (Token::LeftDoubleAngle, 2),
+            b'[' => {
+                if self.starts_with(pos, "[!--") {
+                    (Token::LeftComment, 4)
+                } else if self.starts_with(pos, "[[[") {
+                    (Token::LeftLink, 3)
+                } else if self.starts_with(pos, "[[$") {
+                    (Token::LeftMath, 3)
+                } else if self.starts_with(pos, "[[/") {
+                    (Token::LeftBlockEnd, 3)
+                } else if next == Some(b'[') {
+                    (Token::LeftBlock, 2)
+                } else {
+                    (Token::LeftBracket, 1)
+                }
+            }
+            b']' => {
+                if self.starts_with(pos, "]]]") {
+                    (Token::RightLink, 3)
+                } else if next == Some(b']') {
+                    (Token::RightBlock, 2)
+                } else {
+                    (Token::RightBracket, 1)
+                }
+            }
+            b'-' => {
+                if self.starts_with(pos, "--]") {
+                    (Token::RightComment, 3)
+                } else {
+                    match self.count_repeated(pos, b'-') {
+                        0 | 1 => return None,
+                        2 => (Token::DoubleDash, 2),
+                        count => (Token::TripleDash, count),
+                    }
+                }
+            }
+            b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' => {
+                // Note: The pest grammar's email rule can never match,
+                // since its first repetition also consumes the "@" and ".".
+                // So it is not implemented here.
+                match self.url(pos) {
+                    Some(len) => (Token::Url, len),
+                    None => (Token::Identifier, self.count_while(pos, is_alphanumeric)),
+                }
+            }
+            b'{' => match next {
+                Some(b'$') => match self.variable(pos) {
+                    Some(len) => (Token::Variable, len),
+                    None => return None,
+                },
+                Some(b'@') => match self.variable(pos) {
+                    Some(len) => (Token::WSVariable, len),
+                    None => return None,
+                },
+                Some(b'{') => (Token::LeftMonospace, 2),
+                _ => return None,
+            },
+            b'}' if next == Some(b'}') => (Token::RightMonospace, 2),
+            b'\\' => match next {
+                Some(b'\\') => (Token::StringQuoteDoubleEscape, 2),
+                Some(b'"') => (Token::StringQuoteEscape, 2),
+                _ => return None,
+            },
+            b'"' => (Token::StringQuote, 1),
+            b'$' if self.starts_with(pos, "$]]") => (Token::RightMath, 3),
+            b'*' if next == Some(b'*') => (Token::Bold, 2),
+            b'*' => (Token::BulletItem, 1),
+            b'/' if next == Some(b'/') => (Token::Italics, 2),
+            b'_' if next == Some(b'_') => (Token::Underline, 2),
+            b'_' => (Token::Underscore, 1),
+            b'^' if next == Some(b'^') => (Token::Superscript, 2),
+            b',' if next == Some(b',') => (Token::Subscript, 2),
+            b'#' if next == Some(b'#') => (Token::Color, 2),
+            b'#' => (Token::NumberedItem, 1),
+            b'|' => {
+                if next == Some(b'|') {
+                    match self.byte_at(pos + 2) {
+                        Some(b'~') => (Token::TableColumnTitle, 3),
+                        Some(b'>') => (Token::TableColumnRight, 3),
+                        Some(b'=') => (Token::TableColumnCenter, 3),
+                        _ => (Token::TableColumn, 2),
+                    }
+                } else {
+                    (Token::Pipe, 1)
+                }
+            }
+            b'~' => match self.count_repeated(pos, b'~') {
+                0..=2 => return None,
+                count => match self.byte_at(pos + count) {
+                    Some(b'<') => (Token::ClearFloatLeft, count + 1),
+                    Some(b'>') => (Token::ClearFloatRight, count + 1),
+                    _ => (Token::ClearFloatBoth, count),
+                },
+            },
+            b'=' => (Token::Equals, 1),
+            b':' => (Token::Colon, 1),
+            b'+' => {
+                let mut len = self.count_repeated(pos, b'+').min(6);
+
+                if self.byte_at(pos + len) == Some(b'*')
+                    && self.byte_at(pos + len + 1) != Some(b'*')
+                {
+                    len += 1;
+                }
+
+                (Token::Heading, len)
+            }
+            b'\n' | b'\r' => {
+                let mut len = 0;
+                let mut count = 0;
+
+                while let Some(newline) = self.newline(pos + len) {
+                    len += newline;
+                    count += 1;
+                }
+
+                if count >= 2 {
+                    (Token::ParagraphBreak, len)
+                } else {
+                    (Token::LineBreak, len)
+                }
+            }
+            b' ' | b'\t' | 0xC2 => match self.spaces(pos) {
+                0 => return None,
+                len => (Token::Whitespace, len),
+            },
+            _ => return None,
+        };
+
+        Some(matched)
+    }
+
+    // Rule helpers
+
+    /// `"&" ~ "#"? ~ (ASCII_ALPHANUMERIC)+ ~ ";"?`
+    fn html_entity(&self, pos: usize) -> Option<usize> {
+        let mut len = 1;
+
+        if self.byte_at(pos + len) == Some(b'#') {
+            len += 1;
+        }
+
+        match self.count_while(pos + len, is_alphanumeric) {
+            0 => return None,
+            count => len += count,
+        }
+
+        if self.byte_at(pos + len) == Some(b';') {
+            len += 1;
+        }
+
+        Some(len)
+    }
+
+    /// `">" ~ !!(space | NEWLINE | quote)`, checking the part after the `">"`.
+    fn quote_follows(&self, mut pos: usize) -> bool {
+        loop {
+            match self.byte_at(pos) {
+                Some(b'>') => pos += 1,
+                Some(b'\n' | b'\r') => return true,
+                Some(_) => return self.spaces(pos) > 0,
+                None => return false,
+            }
+        }
+    }
+
+    /// `(("http" ~ "s"?) | "ftp") ~ "://" ~ (!(NEWLINE | " " | "|" | "[" | "]" | "\"" | "@@") ~ ANY)+`
+    fn url(&self, pos: usize) -> Option<usize> {
+        let scheme = if self.starts_with(pos, "https://") {
+            8
+        } else if self.starts_with(pos, "http://") {
+            7
+        } else if self.starts_with(pos, "ftp://") {
+            6
+        } else {
+            return None;
+        };
+
+        let mut len = scheme;
+
+        while let Some(byte) = self.byte_at(pos + len) {
+            match byte {
+                b'\n' | b'\r' | b' ' | b'|' | b'[' | b']' | b'"' => break,
+                b'@' if self.byte_at(pos + len + 1) == Some(b'@') => break,
+                _ => len += 1,
+            }
+        }
+
+        if len == scheme {
+            None
+        } else {
+            Some(len)
+        }
+    }
+
+    /// `"{$" ~ (ASCII_ALPHANUMERIC | "-" | "_" )+ ~ "}"`, and the same with `"{@"`.
+    fn variable(&self, pos: usize) -> Option<usize> {
+        let name = self.count_while(pos + 2, |byte| {
+            is_alphanumeric(byte) || byte == b'-' || byte == b'_'
+        });
+
+        if name > 0 && self.byte_at(pos + 2 + name) == Some(b'}') {
+            Some(name + 3)
+        } else {
+            None
+        }
+    }
+
+    /// `NEWLINE`, which is `"\n" | "\r\n" | "\r"`.
+    fn newline(&self, pos: usize) -> Option<usize> {
+        match self.byte_at(pos) {
+            Some(b'\n') => Some(1),
+            Some(b'\r') if self.byte_at(pos + 1) == Some(b'\n') => Some(2),
+            Some(b'\r') => Some(1),
+            _ => None,
+        }
+    }
+
+    /// `(" " | "\t" | "\xA0")*`
+    fn spaces(&self, pos: usize) -> usize {
+        let mut len = 0;
+
+        loop {
+            match self.byte_at(pos + len) {
+                Some(b' ' | b'\t') => len += 1,
+                Some(0xC2) if self.byte_at(pos + len + 1) == Some(0xA0) => len += 2,
+                _ => return len,
+            }
+        }
+    }
+
+    // Byte helpers
+
+    #[inline]
+    fn byte_at(&self, pos: usize) -> Option<u8> {
+        self.bytes.get(pos).copied()
+    }
+
+    #[inline]
+    fn starts_with(&self, pos: usize, prefix: &str) -> bool {
+        self.bytes[pos..].starts_with(prefix.as_bytes())
+    }
+
+    #[inline]
+    fn count_repeated(&self, pos: usize, byte: u8) -> usize {
+        self.count_while(pos, |b| b == byte)
+    }
+
+    #[inline]
+    fn count_while<F>(&self, pos: usize, f: F) -> usize
+    where
+        F: Fn(u8) -> bool,
+    {
+        self.bytes[pos..].iter().take_while(|&&b| f(b)).count()
+    }
+
+    // Output
+
+    fn flush_other(&mut self, pos: usize) {
+        if let Some(start) = self.other_start.take() {
+            self.push(Token::Other, start, pos);
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, token: Token, start: usize, end: usize) {
+        self.tokens.push(ExtractedToken {
+            token,
+            slice: &self.text[start..end],
+            span: start..end,
+        });
+    }
+}
+
+#[inline]
+fn is_alphanumeric(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric()
+}
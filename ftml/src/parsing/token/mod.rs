@@ -18,7 +18,9 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-mod lexer {
+mod lexer;
+
+mod grammar {
     // Since pest makes enums automatically that clippy doesn't like
     #![allow(clippy::upper_case_acronyms)]
 
@@ -28,7 +30,7 @@ mod lexer {
     pub struct TokenLexer;
 }
 
-use self::lexer::*;
+use self::grammar::*;
 use crate::utf16::Utf16IndexMap;
 use pest::iterators::Pair;
 use pest::Parser;
@@ -160,6 +162,16 @@ impl Token {
     pub(crate) fn extract_all(text: &str) -> Vec<ExtractedToken> {
         info!("Running lexer on input");
 
+        self::lexer::lex(text)
+    }
+
+    /// Runs the original pest lexer, used as a reference for the hand-written one.
+    ///
+    /// Runs of `Token::Other` are merged the same way, so the output
+    /// of both lexers can be compared directly.
+    pub(crate) fn extract_all_pest(text: &str) -> Vec<ExtractedToken<'_>> {
+        info!("Running pest lexer on input");
+
         match TokenLexer::parse(Rule::document, text) {
             Ok(pairs) => {
                 info!("Lexer produced pairs for processing");
@@ -174,7 +186,7 @@ impl Token {
 
                 let mut tokens = vec![start];
                 tokens.extend(pairs.map(Token::convert_pair));
-                tokens
+                Token::merge_other(text, tokens)
             }
             Err(error) => {
                 // Return all of the input as one big raw text
@@ -190,6 +202,32 @@ impl Token {
         }
     }
 
+    /// Combines adjacent `Token::Other`s into one, except for lone `"@"`s.
+    fn merge_other<'a>(
+        text: &'a str,
+        tokens: Vec<ExtractedToken<'a>>,
+    ) -> Vec<ExtractedToken<'a>> {
+        let mut merged: Vec<ExtractedToken> = Vec::with_capacity(tokens.len());
+
+        for extracted in tokens {
+            if let Some(last) = merged.last_mut() {
+                if last.token == Token::Other
+                    && extracted.token == Token::Other
+                    && last.slice != "@"
+                    && extracted.slice != "@"
+                {
+                    last.span.end = extracted.span.end;
+                    last.slice = &text[last.span.clone()];
+                    continue;
+                }
+            }
+
+            merged.push(extracted);
+        }
+
+        merged
+    }
+
     /// Converts a single `Pair` from pest into its corresponding `ExtractedToken`.
     fn convert_pair(pair: Pair<Rule>) -> ExtractedToken {
         // Extract values from the Pair
@@ -1,23 +1,37 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
 
 use pyo3::prelude::*;
 use pyo3::types::{PyBool, PyFloat, PyInt, PyString};
 use wikidot_normalize::normalize;
 
-use crate::data::{ExpressionResult, PageRef, PartialPageInfo};
+use crate::data::{
+    ExpressionResult, FileInfo, FileRef, PageRef, PartialPageInfo, UserInfo,
+};
 use crate::includes::{FetchedPage, IncludeRef, NullIncluder};
 use crate::info::VERSION;
 use crate::prelude::*;
-use crate::render::html::HtmlRender;
-use crate::render::text::TextRender;
+use crate::render::excerpt::{Excerpt, ExcerptLength, ExcerptRender};
+use crate::render::html::{
+    fill_placeholders as fill_module_placeholders, HtmlRender, ModuleInvocation,
+};
+use crate::render::text::{ReadableTextRender, TextRender};
+use crate::render::ModuleRenderMode;
+use crate::tree::GallerySize;
 
 fn page_refs_to_string(refs: &Vec<PageRef>) -> Vec<String> {
     refs.iter().map(|x| x.to_string()).collect()
 }
 
+fn file_refs_to_tuples(refs: &[FileRef]) -> Vec<(String, String)> {
+    refs.iter()
+        .map(|x| (x.page.to_string(), x.file.to_string()))
+        .collect()
+}
+
 fn page_refs_to_owned(refs: &Vec<PageRef>) -> Vec<PageRef<'static>> {
     refs.iter().map(|x| x.to_owned()).collect()
 }
@@ -28,17 +42,22 @@ fn render<R: Render>(
     page_info: PageInfo,
     callbacks: Py<PyAny>,
     mode: WikitextMode,
+    defer_modules: bool,
+    id_namespace: Option<String>,
 ) -> (
     R::Output,
     Vec<String>,
     Vec<String>,
     Vec<(String, String)>,
+    Vec<(String, String)>,
     Vec<String>,
 ) {
     let mut settings = WikitextSettings::from_mode(mode);
     settings.use_include_compatibility = true;
+    settings.defer_modules = defer_modules;
+    settings.id_namespace = id_namespace;
 
-    let page_callbacks = Rc::new(PythonCallbacks {
+    let page_callbacks = Arc::new(PythonCallbacks {
         callbacks: Box::new(callbacks.clone()),
     });
 
@@ -75,6 +94,7 @@ fn render<R: Render>(
         output,
         page_refs_to_string(&included_pages),
         page_refs_to_string(&tree.internal_links),
+        file_refs_to_tuples(&tree.files),
         tree.code,
         tree.html,
     )
@@ -89,9 +109,61 @@ struct PyRenderResult {
     #[pyo3(get)]
     pub linked_pages: Vec<String>,
     #[pyo3(get)]
+    pub linked_files: Vec<(String, String)>,
+    #[pyo3(get)]
     pub code: Vec<(String, String)>,
     #[pyo3(get)]
     pub html: Vec<String>,
+    #[pyo3(get)]
+    pub modules: Vec<PyModuleInvocation>,
+    #[pyo3(get)]
+    pub meta: Vec<(String, String, String)>,
+    #[pyo3(get)]
+    pub json_ld: Option<String>,
+}
+
+#[pyclass(name = "ModuleInvocation")]
+#[derive(Clone)]
+struct PyModuleInvocation {
+    #[pyo3(get)]
+    pub id: String,
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub params: HashMap<String, String>,
+    #[pyo3(get)]
+    pub body: String,
+}
+
+impl From<ModuleInvocation> for PyModuleInvocation {
+    fn from(module: ModuleInvocation) -> Self {
+        Self {
+            id: module.id,
+            name: module.name,
+            params: module.params,
+            body: module.body,
+        }
+    }
+}
+
+#[pyclass(name = "Excerpt")]
+struct PyExcerpt {
+    #[pyo3(get)]
+    pub text: String,
+    #[pyo3(get)]
+    pub html: String,
+    #[pyo3(get)]
+    pub truncated: bool,
+}
+
+impl From<Excerpt> for PyExcerpt {
+    fn from(excerpt: Excerpt) -> Self {
+        Self {
+            text: excerpt.text,
+            html: excerpt.html,
+            truncated: excerpt.truncated,
+        }
+    }
 }
 
 #[pyclass(name = "Parts")]
@@ -193,6 +265,91 @@ impl PyPartialPageInfo {
     }
 }
 
+#[pyclass(name = "UserInfo")]
+struct PyUserInfo {
+    name: String,
+    id: Option<i64>,
+    display_name: Option<String>,
+    profile_url: Option<String>,
+    avatar_url: Option<String>,
+    exists: bool,
+}
+
+#[pymethods]
+impl PyUserInfo {
+    #[new]
+    fn new(
+        name: String,
+        id: Option<i64>,
+        display_name: Option<String>,
+        profile_url: Option<String>,
+        avatar_url: Option<String>,
+        exists: Option<bool>,
+    ) -> Self {
+        Self {
+            name,
+            id,
+            display_name,
+            profile_url,
+            avatar_url,
+            exists: exists.unwrap_or(false),
+        }
+    }
+}
+
+impl PyUserInfo {
+    fn to_user_info(&self) -> UserInfo<'static> {
+        UserInfo {
+            name: Cow::Owned(self.name.clone()),
+            id: self.id,
+            display_name: self.display_name.clone().map(Cow::Owned),
+            profile_url: self.profile_url.clone().map(Cow::Owned),
+            avatar_url: self.avatar_url.clone().map(Cow::Owned),
+            exists: self.exists,
+        }
+    }
+}
+
+#[pyclass(name = "FileInfo")]
+struct PyFileInfo {
+    page: String,
+    file: String,
+    size: Option<u64>,
+    mime_type: Option<String>,
+    exists: bool,
+}
+
+#[pymethods]
+impl PyFileInfo {
+    #[new]
+    fn new(
+        page: String,
+        file: String,
+        size: Option<u64>,
+        mime_type: Option<String>,
+        exists: Option<bool>,
+    ) -> Self {
+        Self {
+            page,
+            file,
+            size,
+            mime_type,
+            exists: exists.unwrap_or(false),
+        }
+    }
+}
+
+impl PyFileInfo {
+    fn to_file_info(&self) -> FileInfo<'static> {
+        FileInfo {
+            file: FileRef::new(self.page.clone(), self.file.clone()),
+            size: self.size,
+            mime_type: self.mime_type.clone().map(Cow::Owned),
+            exists: self.exists,
+        }
+    }
+}
+
 #[pyclass(name = "PageInfo")]
 struct PyPageInfo {
     page: String,
@@ -313,7 +470,12 @@ impl PageCallbacks for PythonCallbacks {
         module_name: Cow<str>,
         params: HashMap<Cow<str>, Cow<str>>,
         body: Cow<str>,
+        mode: ModuleRenderMode,
     ) -> Cow<'static, str> {
+        let py_mode = match mode {
+            ModuleRenderMode::Html => "html",
+            ModuleRenderMode::Text => "text",
+        };
         let py_params: HashMap<String, String> =
             params.keys().fold(HashMap::new(), |mut acc, k| {
                 acc.insert(k.to_string(), params.get(k).unwrap().to_string());
@@ -323,22 +485,7 @@ impl PageCallbacks for PythonCallbacks {
             return self
                 .callbacks
                 .getattr(py, "render_module")?
-                .call(py, (module_name, py_params, body), None)?
-                .extract(py);
-        });
-        log_python_error(&result);
-        match result {
-            Ok(result) => Cow::from(result),
-            Err(_) => Cow::from(""),
-        }
-    }
-
-    fn render_user(&self, user: Cow<str>, avatar: bool) -> Cow<'static, str> {
-        let result: PyResult<String> = Python::with_gil(|py| {
-            return self
-                .callbacks
-                .getattr(py, "render_user")?
-                .call(py, (user, avatar), None)?
+                .call(py, (module_name, py_params, body, py_mode), None)?
                 .extract(py);
         });
         log_python_error(&result);
@@ -348,18 +495,22 @@ impl PageCallbacks for PythonCallbacks {
         }
     }
 
-    fn get_i18n_message<'a>(&self, message_id: Cow<str>) -> Cow<'static, str> {
-        let result: PyResult<String> = Python::with_gil(|py| {
+    fn get_i18n_message<'a>(
+        &self,
+        language: Cow<str>,
+        message_id: Cow<str>,
+    ) -> Option<Cow<'static, str>> {
+        let result: PyResult<Option<String>> = Python::with_gil(|py| {
             return self
                 .callbacks
                 .getattr(py, "get_i18n_message")?
-                .call(py, (message_id,), None)?
+                .call(py, (language, message_id), None)?
                 .extract(py);
         });
         log_python_error(&result);
         match result {
-            Ok(result) => Cow::from(result),
-            Err(_) => Cow::from("?"),
+            Ok(result) => result.map(Cow::from),
+            Err(_) => None,
         }
     }
 
@@ -400,6 +551,78 @@ impl PageCallbacks for PythonCallbacks {
         }
     }
 
+    fn get_user_info<'a>(&self, names: &[Cow<'a, str>]) -> Vec<UserInfo<'static>> {
+        let py_names: Vec<String> = names.iter().map(|x| x.to_string()).collect();
+        let result: PyResult<Vec<UserInfo<'static>>> = Python::with_gil(|py| {
+            Ok(self
+                .callbacks
+                .getattr(py, "fetch_users")?
+                .call(py, (py_names,), None)?
+                .extract::<Vec<PyRef<PyUserInfo>>>(py)?
+                .iter()
+                .map(|x| x.to_user_info())
+                .collect())
+        });
+        log_python_error(&result);
+        match result {
+            Ok(info) => info,
+            Err(_) => vec![],
+        }
+    }
+
+    fn get_file_info<'a>(&self, files: &[FileRef<'a>]) -> Vec<FileInfo<'static>> {
+        let py_files = file_refs_to_tuples(files);
+        let result: PyResult<Vec<FileInfo<'static>>> = Python::with_gil(|py| {
+            Ok(self
+                .callbacks
+                .getattr(py, "fetch_files")?
+                .call(py, (py_files,), None)?
+                .extract::<Vec<PyRef<PyFileInfo>>>(py)?
+                .iter()
+                .map(|x| x.to_file_info())
+                .collect())
+        });
+        log_python_error(&result);
+        match result {
+            Ok(info) => info,
+            Err(_) => vec![],
+        }
+    }
+
+    fn list_page_files<'a>(&self, page_ref: &PageRef<'a>) -> Vec<Cow<'static, str>> {
+        let result: PyResult<Vec<String>> = Python::with_gil(|py| {
+            return self
+                .callbacks
+                .getattr(py, "list_page_files")?
+                .call(py, (page_ref.to_string(),), None)?
+                .extract(py);
+        });
+        log_python_error(&result);
+        match result {
+            Ok(files) => files.into_iter().map(Cow::from).collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn get_thumbnail_url<'a>(
+        &self,
+        image_url: Cow<str>,
+        size: GallerySize,
+    ) -> Option<Cow<'static, str>> {
+        let result: PyResult<Option<String>> = Python::with_gil(|py| {
+            return self
+                .callbacks
+                .getattr(py, "get_thumbnail_url")?
+                .call(py, (image_url, size.name()), None)?
+                .extract(py);
+        });
+        log_python_error(&result);
+        match result {
+            Ok(result) => result.map(Cow::from),
+            Err(_) => None,
+        }
+    }
+
     fn evaluate_expression<'a>(&self, expression: Cow<str>) -> ExpressionResult<'static> {
         let result: PyResult<ExpressionResult<'static>> = Python::with_gil(|py| {
             let v: PyObject = self.callbacks.getattr(py, "evaluate_expression")?.call(
@@ -506,16 +729,35 @@ impl Callbacks {
         module_name: String,
         _params: HashMap<String, String>,
         _body: String,
+        mode: String,
     ) -> PyResult<String> {
+        if mode == "text" {
+            return Ok(format!("[module {module_name}]"));
+        }
+
         return Ok(format!("UnimplementedModule[{module_name}]").to_string());
     }
 
-    pub fn render_user(&self, user: String, _avatar: bool) -> PyResult<String> {
-        return Ok(format!("UnimplementedUser[{user}]").to_string());
+    pub fn fetch_users(&self, names: Vec<String>) -> PyResult<Vec<PyUserInfo>> {
+        return Ok(names
+            .iter()
+            .map(|x| PyUserInfo {
+                name: x.to_owned(),
+                id: None,
+                display_name: None,
+                profile_url: None,
+                avatar_url: None,
+                exists: false,
+            })
+            .collect());
     }
 
-    pub fn get_i18n_message(&self, _message_id: String) -> PyResult<String> {
-        return Ok(String::from("?"));
+    pub fn get_i18n_message(
+        &self,
+        _language: String,
+        _message_id: String,
+    ) -> PyResult<Option<String>> {
+        return Ok(None);
     }
 
     pub fn render_include_not_found(&self, full_name: String) -> PyResult<String> {
@@ -549,6 +791,25 @@ impl Callbacks {
             .collect());
     }
 
+    pub fn fetch_files(
+        &self,
+        _files: Vec<(String, String)>,
+    ) -> PyResult<Vec<PyFileInfo>> {
+        return Ok(vec![]);
+    }
+
+    pub fn list_page_files(&self, _full_name: String) -> PyResult<Vec<String>> {
+        return Ok(vec![]);
+    }
+
+    pub fn get_thumbnail_url(
+        &self,
+        _image_url: String,
+        _size: String,
+    ) -> PyResult<Option<String>> {
+        return Ok(None);
+    }
+
     pub fn evaluate_expression(&self, _expression: String) -> PyResult<Option<&PyAny>> {
         return Ok(None);
     }
@@ -577,83 +838,166 @@ fn render_html(
     callbacks: Py<PyAny>,
     page_info: &PyPageInfo,
     mode: String,
+    defer_modules: Option<bool>,
+    id_namespace: Option<String>,
+    py: Python,
 ) -> PyResult<PyRenderResult> {
-    let (html_output, included_pages, linked_pages, code, html) = render(
-        &mut source.to_string(),
-        &HtmlRender,
-        page_info.to_page_info(),
-        callbacks,
-        mode_to_wikitext_mode(mode),
-    );
+    let page_info = page_info.to_page_info();
+    let (html_output, included_pages, linked_pages, linked_files, code, html) = py
+        .allow_threads(move || {
+            render(
+                &mut source.to_string(),
+                &HtmlRender,
+                page_info,
+                callbacks,
+                mode_to_wikitext_mode(mode),
+                defer_modules.unwrap_or(false),
+                id_namespace,
+            )
+        });
 
     Ok(PyRenderResult {
         body: html_output.body,
         included_pages,
         linked_pages,
+        linked_files,
         code,
         html,
+        modules: html_output
+            .modules
+            .into_iter()
+            .map(PyModuleInvocation::from)
+            .collect(),
+        meta: html_output
+            .meta
+            .into_iter()
+            .map(|meta| (str!(meta.tag_type.tag_name()), meta.name, meta.value))
+            .collect(),
+        json_ld: html_output.json_ld,
     })
 }
 
+#[pyfunction]
+fn fill_placeholders(body: String, rendered: HashMap<String, String>) -> String {
+    fill_module_placeholders(&body, &rendered)
+}
+
 #[pyfunction]
 fn render_text(
     source: String,
     callbacks: Py<PyAny>,
     page_info: &PyPageInfo,
     mode: String,
+    readable: Option<bool>,
+    wrap_width: Option<usize>,
+    py: Python,
 ) -> PyResult<PyRenderResult> {
-    let (text_output, included_pages, linked_pages, code, html) = render(
-        &mut source.to_string(),
-        &TextRender,
-        page_info.to_page_info(),
-        callbacks,
-        mode_to_wikitext_mode(mode),
-    );
+    let page_info = page_info.to_page_info();
+    let mode = mode_to_wikitext_mode(mode);
+    let (text_output, included_pages, linked_pages, linked_files, code, html) = py
+        .allow_threads(move || {
+            let source = &mut source.to_string();
+
+            if readable.unwrap_or(false) {
+                let renderer =
+                    ReadableTextRender::new(wrap_width.and_then(NonZeroUsize::new));
+                render(source, &renderer, page_info, callbacks, mode, false, None)
+            } else {
+                render(source, &TextRender, page_info, callbacks, mode, false, None)
+            }
+        });
 
     Ok(PyRenderResult {
         body: text_output,
         included_pages,
         linked_pages,
+        linked_files,
         code,
         html,
+        modules: vec![],
+        meta: vec![],
+        json_ld: None,
     })
 }
 
+#[pyfunction]
+fn render_excerpt(
+    source: String,
+    callbacks: Py<PyAny>,
+    page_info: &PyPageInfo,
+    mode: String,
+    characters: Option<usize>,
+    sentences: Option<usize>,
+    py: Python,
+) -> PyResult<PyExcerpt> {
+    let page_info = page_info.to_page_info();
+    let mode = mode_to_wikitext_mode(mode);
+    let mut renderer = ExcerptRender::default();
+
+    if let Some(sentences) = sentences {
+        renderer.length = ExcerptLength::Sentences(sentences);
+    } else if let Some(characters) = characters {
+        renderer.length = ExcerptLength::Characters(characters);
+    }
+
+    let (excerpt, _, _, _, _, _) = py.allow_threads(move || {
+        render(
+            &mut source.to_string(),
+            &renderer,
+            page_info,
+            callbacks,
+            mode,
+            false,
+            None,
+        )
+    });
+
+    Ok(PyExcerpt::from(excerpt))
+}
+
 #[pyfunction]
 fn collect_backlinks(
     source: String,
     callbacks: Py<PyAny>,
     page_info: &PyPageInfo,
     mode: String,
+    py: Python,
 ) -> PyResult<PyRenderResult> {
     let mut settings = WikitextSettings::from_mode(mode_to_wikitext_mode(mode));
     settings.use_include_compatibility = true;
 
-    let page_callbacks = Rc::new(PythonCallbacks {
+    let page_callbacks = Arc::new(PythonCallbacks {
         callbacks: Box::new(callbacks.clone()),
     });
 
-    let includer = NullIncluder {};
-
-    let text = &mut source.clone();
-    preprocess(text);
-    let (included_text, included_pages) = include(&text, &settings, includer, || {
-        panic!("Mismatched includer page count")
-    })
-    .unwrap_or((source.to_owned(), vec![]));
-
-    let text = &mut included_text.clone();
-    let tokens = tokenize(text);
     let page_info = page_info.to_page_info();
-    let (tree, _warnings) =
-        parse(&tokens, &page_info, page_callbacks.clone(), &settings).into();
 
-    Ok(PyRenderResult {
-        body: String::from(""),
-        included_pages: page_refs_to_string(&included_pages),
-        linked_pages: page_refs_to_string(&tree.internal_links),
-        code: tree.code,
-        html: tree.html,
+    py.allow_threads(move || {
+        let includer = NullIncluder {};
+
+        let text = &mut source.clone();
+        preprocess(text);
+        let (included_text, included_pages) = include(&text, &settings, includer, || {
+            panic!("Mismatched includer page count")
+        })
+        .unwrap_or((source.to_owned(), vec![]));
+
+        let text = &mut included_text.clone();
+        let tokens = tokenize(text);
+        let (tree, _warnings) =
+            parse(&tokens, &page_info, page_callbacks.clone(), &settings).into();
+
+        Ok(PyRenderResult {
+            body: String::from(""),
+            included_pages: page_refs_to_string(&included_pages),
+            linked_pages: page_refs_to_string(&tree.internal_links),
+            linked_files: file_refs_to_tuples(&tree.files),
+            code: tree.code,
+            html: tree.html,
+            modules: vec![],
+            meta: vec![],
+            json_ld: None,
+        })
     })
 }
 
@@ -663,32 +1007,37 @@ fn collect_code_and_html(
     callbacks: Py<PyAny>,
     page_info: &PyPageInfo,
     mode: String,
+    py: Python,
 ) -> PyResult<PyParts> {
     let mut settings = WikitextSettings::from_mode(mode_to_wikitext_mode(mode));
     settings.use_include_compatibility = true;
 
-    let page_callbacks = Rc::new(PythonCallbacks {
+    let page_callbacks = Arc::new(PythonCallbacks {
         callbacks: Box::new(callbacks.clone()),
     });
 
-    let includer = NullIncluder {};
+    let page_info = page_info.to_page_info();
 
-    let text = &mut source.clone();
-    preprocess(text);
-    let (included_text, _included_pages) = include(&text, &settings, includer, || {
-        panic!("Mismatched includer page count")
-    })
-    .unwrap_or((source.to_owned(), vec![]));
+    py.allow_threads(move || {
+        let includer = NullIncluder {};
 
-    let text = &mut included_text.clone();
-    let tokens = tokenize(text);
-    let page_info = page_info.to_page_info();
-    let (tree, _warnings) =
-        parse(&tokens, &page_info, page_callbacks.clone(), &settings).into();
+        let text = &mut source.clone();
+        preprocess(text);
+        let (included_text, _included_pages) =
+            include(&text, &settings, includer, || {
+                panic!("Mismatched includer page count")
+            })
+            .unwrap_or((source.to_owned(), vec![]));
+
+        let text = &mut included_text.clone();
+        let tokens = tokenize(text);
+        let (tree, _warnings) =
+            parse(&tokens, &page_info, page_callbacks.clone(), &settings).into();
 
-    Ok(PyParts {
-        code: tree.code,
-        html: tree.html,
+        Ok(PyParts {
+            code: tree.code,
+            html: tree.html,
+        })
     })
 }
 
@@ -697,13 +1046,19 @@ fn ftml(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add("ftml_version", VERSION.to_string())?;
     m.add_function(wrap_pyfunction!(render_html, m)?)?;
     m.add_function(wrap_pyfunction!(render_text, m)?)?;
+    m.add_function(wrap_pyfunction!(render_excerpt, m)?)?;
     m.add_function(wrap_pyfunction!(collect_backlinks, m)?)?;
     m.add_function(wrap_pyfunction!(collect_code_and_html, m)?)?;
+    m.add_function(wrap_pyfunction!(fill_placeholders, m)?)?;
     m.add_class::<Callbacks>()?;
     m.add_class::<PyPageInfo>()?;
     m.add_class::<PyIncludeRef>()?;
     m.add_class::<PyFetchedPage>()?;
     m.add_class::<PyPartialPageInfo>()?;
+    m.add_class::<PyUserInfo>()?;
+    m.add_class::<PyFileInfo>()?;
+    m.add_class::<PyModuleInvocation>()?;
+    m.add_class::<PyExcerpt>()?;
 
     Ok(())
 }
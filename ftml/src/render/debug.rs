@@ -22,7 +22,7 @@
 
 use super::prelude::*;
 use crate::data::PageCallbacks;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct DebugRender;
@@ -35,7 +35,7 @@ impl Render for DebugRender {
         &self,
         tree: &SyntaxTree,
         page_info: &PageInfo,
-        page_callbacks: Rc<dyn PageCallbacks>,
+        page_callbacks: Arc<dyn PageCallbacks>,
         settings: &WikitextSettings,
     ) -> String {
         info!("Running debug logger on syntax tree");
@@ -0,0 +1,618 @@
+/*
+ * render/excerpt.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Generation of short page excerpts, such as for previews and listings.
+//!
+//! The syntax tree is walked in document order, collecting its visible text
+//! with whitespace collapsed and block boundaries turned into spaces.
+//! The cut point is determined first, then the tree is walked again to produce
+//! both the plain text and an HTML version which keeps inline formatting.
+//! Since each tag is closed as the walk returns, the HTML is always balanced.
+
+use super::prelude::*;
+use crate::data::PageCallbacks;
+use crate::render::html::{escape, escape_char};
+use crate::render::{Handle, ModuleRenderMode};
+use crate::tree::{
+    ContainerType, DefinitionListItem, Element, ListItem, Module, Tab, VariableScopes,
+};
+use std::sync::Arc;
+
+/// How long an excerpt may be.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExcerptLength {
+    /// At most this many characters, cut on a word boundary.
+    Characters(usize),
+
+    /// At most this many sentences.
+    Sentences(usize),
+}
+
+/// Renders a short excerpt of the page's visible text.
+///
+/// Each of the `skip_*` fields excludes a kind of element from the excerpt,
+/// they are all enabled by default.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExcerptRender {
+    pub length: ExcerptLength,
+
+    /// Appended when the text is cut by [`ExcerptLength::Characters`].
+    pub ellipsis: String,
+
+    pub skip_modules: bool,
+    pub skip_hidden: bool,
+    pub skip_footnotes: bool,
+    pub skip_table_of_contents: bool,
+    pub skip_code: bool,
+    pub skip_tables: bool,
+}
+
+impl ExcerptRender {
+    #[inline]
+    pub fn new(length: ExcerptLength) -> Self {
+        ExcerptRender {
+            length,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for ExcerptRender {
+    fn default() -> Self {
+        ExcerptRender {
+            length: ExcerptLength::Characters(300),
+            ellipsis: str!("…"),
+            skip_modules: true,
+            skip_hidden: true,
+            skip_footnotes: true,
+            skip_table_of_contents: true,
+            skip_code: true,
+            skip_tables: true,
+        }
+    }
+}
+
+/// The output of [`ExcerptRender`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Excerpt {
+    /// The excerpt as plain text, on a single line.
+    pub text: String,
+
+    /// The excerpt as HTML, containing only inline formatting tags.
+    pub html: String,
+
+    /// Whether the page has more text than is in the excerpt.
+    pub truncated: bool,
+}
+
+impl Render for ExcerptRender {
+    type Output = Excerpt;
+
+    fn render(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        page_callbacks: Arc<dyn PageCallbacks>,
+        settings: &WikitextSettings,
+    ) -> Excerpt {
+        info!(
+            "Rendering excerpt (site {}, page {}, category {})",
+            page_info.site.as_ref(),
+            page_info.page.as_ref(),
+            match &page_info.category {
+                Some(category) => category.as_ref(),
+                None => "_default",
+            },
+        );
+
+        let handle = Handle::new(
+            page_callbacks.clone(),
+            &page_info.language,
+            &vec![],
+            &[],
+            &[],
+        );
+        let mut ctx = ExcerptContext {
+            render: self,
+            callbacks: page_callbacks,
+            handle: &handle,
+            settings,
+            table_of_contents: &tree.table_of_contents,
+            footnotes: &tree.footnotes,
+            variables: VariableScopes::new(),
+            modules: Vec::new(),
+            module_index: 0,
+            footnote_index: 0,
+            text: String::new(),
+            html: None,
+            length: 0,
+            limit: None,
+            cut: None,
+            last_space: None,
+            sentences: 0,
+            sentence_end: false,
+            pending_space: false,
+        };
+
+        // First, find where the excerpt should be cut
+        render_elements(&mut ctx, &tree.elements);
+        let cut = ctx.cut;
+
+        // Then render it again up to that point, this time with HTML.
+        // Rendered modules are kept, so they are only requested once.
+        ctx.reset(cut);
+        render_elements(&mut ctx, &tree.elements);
+
+        let mut text = ctx.text;
+        let mut html = ctx.html.unwrap_or_default();
+        let truncated = cut.is_some();
+
+        if truncated && matches!(self.length, ExcerptLength::Characters(_)) {
+            text.push_str(&self.ellipsis);
+            escape(&mut html, &self.ellipsis);
+        }
+
+        Excerpt {
+            text,
+            html,
+            truncated,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ExcerptContext<'r, 'e> {
+    render: &'r ExcerptRender,
+    callbacks: Arc<dyn PageCallbacks>,
+    handle: &'r Handle<'r>,
+    settings: &'r WikitextSettings,
+    table_of_contents: &'r [Element<'e>],
+    footnotes: &'r [Vec<Element<'e>>],
+    variables: VariableScopes,
+
+    /// Module output, in document order.
+    modules: Vec<String>,
+    module_index: usize,
+    footnote_index: usize,
+
+    text: String,
+    html: Option<String>,
+
+    /// How many characters have been written to `text`.
+    length: usize,
+
+    /// How many characters to write before stopping, if known.
+    limit: Option<usize>,
+
+    /// Where the excerpt is to be cut, once found.
+    cut: Option<usize>,
+    last_space: Option<usize>,
+    sentences: usize,
+    sentence_end: bool,
+    pending_space: bool,
+}
+
+impl ExcerptContext<'_, '_> {
+    /// Prepares for the second pass, which stops at the given cut point.
+    fn reset(&mut self, cut: Option<usize>) {
+        self.variables = VariableScopes::new();
+        self.module_index = 0;
+        self.footnote_index = 0;
+        self.text.clear();
+        self.html = Some(String::new());
+        self.length = 0;
+        self.limit = cut;
+        self.cut = None;
+        self.last_space = None;
+        self.sentences = 0;
+        self.sentence_end = false;
+        self.pending_space = false;
+    }
+
+    #[inline]
+    fn done(&self) -> bool {
+        match self.limit {
+            Some(limit) => self.length >= limit,
+            None => self.cut.is_some(),
+        }
+    }
+
+    /// Separates blocks, which are joined with a space.
+    #[inline]
+    fn push_break(&mut self) {
+        if self.length > 0 {
+            self.pending_space = true;
+        }
+    }
+
+    fn push_str(&mut self, s: &str) {
+        for ch in s.chars() {
+            if self.done() {
+                return;
+            }
+
+            if ch.is_whitespace() {
+                self.push_break();
+                continue;
+            }
+
+            if self.pending_space {
+                self.pending_space = false;
+                self.push_char(' ');
+
+                if self.done() {
+                    return;
+                }
+            }
+
+            self.push_char(ch);
+        }
+    }
+
+    fn push_char(&mut self, ch: char) {
+        // Check if the excerpt's end has been reached
+        if self.limit.is_none() {
+            match self.render.length {
+                ExcerptLength::Characters(max) if self.length == max => {
+                    self.cut = Some(match ch {
+                        ' ' => max,
+                        _ => self.last_space.unwrap_or(max),
+                    });
+
+                    return;
+                }
+                ExcerptLength::Sentences(max) if ch == ' ' && self.sentence_end => {
+                    self.sentences += 1;
+
+                    if self.sentences >= max {
+                        self.cut = Some(self.length);
+                        return;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        match ch {
+            ' ' => self.last_space = Some(self.length),
+            '.' | '!' | '?' | '…' | '。' | '！' | '？' => self.sentence_end = true,
+            '"' | '\'' | ')' | ']' | '»' | '”' | '’' => (),
+            _ => self.sentence_end = false,
+        }
+
+        self.text.push(ch);
+        self.length += 1;
+
+        if let Some(ref mut html) = self.html {
+            match escape_char(ch) {
+                Some(escaped) => html.push_str(escaped),
+                None => html.push(ch),
+            }
+        }
+    }
+
+    /// Opens an HTML tag, returning whether it needs to be closed.
+    ///
+    /// Nothing is opened during the first pass, or once the excerpt is done.
+    fn open_tag(&mut self, tag: &str) -> bool {
+        if self.done() {
+            return false;
+        }
+
+        // Whitespace goes before the tag, not inside it
+        if self.pending_space {
+            self.pending_space = false;
+            self.push_char(' ');
+
+            if self.done() {
+                return false;
+            }
+        }
+
+        match self.html {
+            Some(ref mut html) => {
+                str_write!(html, "<{tag}>");
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn close_tag(&mut self, tag: &str) {
+        if let Some(ref mut html) = self.html {
+            str_write!(html, "</{tag}>");
+        }
+    }
+
+    fn render_module(&mut self, module: &Module) -> String {
+        // Reuse the output from the first pass
+        if let Some(rendered) = self.modules.get(self.module_index) {
+            self.module_index += 1;
+            return rendered.clone();
+        }
+
+        let rendered = self.callbacks.render_module(
+            module.name().clone(),
+            module.params().to_owned(),
+            module.text().clone(),
+            ModuleRenderMode::Text,
+        );
+        let rendered = rendered.into_owned();
+
+        self.modules.push(rendered.clone());
+        self.module_index += 1;
+        rendered
+    }
+}
+
+fn render_elements(ctx: &mut ExcerptContext, elements: &[Element]) {
+    for element in elements {
+        if ctx.done() {
+            return;
+        }
+
+        render_element(ctx, element);
+    }
+}
+
+fn render_element(ctx: &mut ExcerptContext, element: &Element) {
+    match element {
+        Element::Fragment(elements) => render_elements(ctx, elements),
+        Element::Container(container) => {
+            let tag = match container.ctype() {
+                ContainerType::Hidden | ContainerType::HiddenBlock
+                    if ctx.render.skip_hidden =>
+                {
+                    return;
+                }
+                ContainerType::Invisible | ContainerType::InvisibleBlock
+                    if ctx.render.skip_hidden =>
+                {
+                    // Invisible text still takes up space
+                    ctx.push_break();
+                    return;
+                }
+                ContainerType::Div
+                | ContainerType::HiddenBlock
+                | ContainerType::InvisibleBlock
+                | ContainerType::Paragraph
+                | ContainerType::Blockquote
+                | ContainerType::Form
+                | ContainerType::Align(_)
+                | ContainerType::Header(_) => {
+                    ctx.push_break();
+                    render_elements(ctx, container.elements());
+                    ctx.push_break();
+                    return;
+                }
+                ContainerType::RubyText => {
+                    ctx.push_str("(");
+                    render_elements(ctx, container.elements());
+                    ctx.push_str(")");
+                    return;
+                }
+                ContainerType::Bold => "strong",
+                ContainerType::Italics => "em",
+                ContainerType::Underline => "u",
+                ContainerType::Superscript => "sup",
+                ContainerType::Subscript => "sub",
+                ContainerType::Strikethrough => "s",
+                ContainerType::Monospace => "code",
+                ContainerType::Mark => "mark",
+                ContainerType::Insertion => "ins",
+                ContainerType::Deletion => "del",
+                _ => {
+                    render_elements(ctx, container.elements());
+                    return;
+                }
+            };
+
+            let opened = ctx.open_tag(tag);
+            render_elements(ctx, container.elements());
+
+            if opened {
+                ctx.close_tag(tag);
+            }
+        }
+        Element::Module(module) => {
+            if ctx.render.skip_modules || ctx.settings.no_modules {
+                return;
+            }
+
+            let rendered = ctx.render_module(module);
+            ctx.push_break();
+            ctx.push_str(&rendered);
+            ctx.push_break();
+        }
+        Element::Text(text)
+        | Element::Raw(text)
+        | Element::Email(text)
+        | Element::HtmlEntity(text) => ctx.push_str(text),
+        Element::Variable(name) => {
+            let value = match ctx.variables.get(name) {
+                Some(value) => str!(value),
+                None => format!("{{${name}}}"),
+            };
+
+            ctx.push_str(&value);
+        }
+        Element::Table(table) => {
+            if ctx.render.skip_tables {
+                return;
+            }
+
+            if let Some(caption) = &table.caption {
+                ctx.push_break();
+                ctx.push_str(caption);
+            }
+
+            for row in &table.rows {
+                for cell in &row.cells {
+                    ctx.push_break();
+                    render_elements(ctx, &cell.elements);
+                }
+            }
+
+            ctx.push_break();
+        }
+        Element::TabView(tabs) => {
+            for Tab { elements, .. } in tabs {
+                ctx.push_break();
+                render_elements(ctx, elements);
+            }
+
+            ctx.push_break();
+        }
+        Element::Anchor { elements, .. } | Element::Color { elements, .. } => {
+            render_elements(ctx, elements)
+        }
+        Element::Link { link, label, .. } => {
+            let mut text = String::new();
+            ctx.handle.get_link_label(link, label, |label| {
+                text.push_str(label);
+            });
+
+            ctx.push_str(&text);
+        }
+        Element::File { file, label } => {
+            ctx.push_str(label.as_deref().unwrap_or(&file.file));
+        }
+        Element::List { items, .. } => {
+            for item in items {
+                match item {
+                    ListItem::Elements { elements, .. } => {
+                        ctx.push_break();
+                        render_elements(ctx, elements);
+                    }
+                    ListItem::SubList { element } => render_element(ctx, element),
+                }
+            }
+
+            ctx.push_break();
+        }
+        Element::DefinitionList(items) => {
+            for DefinitionListItem { key, value } in items {
+                ctx.push_break();
+                render_elements(ctx, key);
+                ctx.push_break();
+                render_elements(ctx, value);
+            }
+
+            ctx.push_break();
+        }
+        Element::Collapsible { elements, .. } | Element::Note { elements, .. } => {
+            ctx.push_break();
+            render_elements(ctx, elements);
+            ctx.push_break();
+        }
+        Element::TableOfContents { .. } => {
+            if ctx.render.skip_table_of_contents {
+                return;
+            }
+
+            ctx.push_break();
+            render_elements(ctx, ctx.table_of_contents);
+            ctx.push_break();
+        }
+        Element::Footnote => {
+            if ctx.render.skip_footnotes {
+                return;
+            }
+
+            ctx.footnote_index += 1;
+            let marker = format!("[{}]", ctx.footnote_index);
+
+            let opened = ctx.open_tag("sup");
+            ctx.push_str(&marker);
+
+            if opened {
+                ctx.close_tag("sup");
+            }
+        }
+        Element::FootnoteBlock { hide, .. } => {
+            if ctx.render.skip_footnotes || *hide {
+                return;
+            }
+
+            for (index, contents) in ctx.footnotes.iter().enumerate() {
+                ctx.push_break();
+                ctx.push_str(&format!("{}.", index + 1));
+                ctx.push_break();
+                render_elements(ctx, contents);
+            }
+
+            ctx.push_break();
+        }
+        Element::User { name, .. } => ctx.push_str(name),
+        Element::Date { value, .. } => {
+            let formatted = value.format(Some((*value).default_format_string()));
+            ctx.push_str(&formatted);
+        }
+        Element::Code { contents, .. } => {
+            if ctx.render.skip_code {
+                return;
+            }
+
+            ctx.push_break();
+
+            let opened = ctx.open_tag("code");
+            ctx.push_str(contents);
+
+            if opened {
+                ctx.close_tag("code");
+            }
+
+            ctx.push_break();
+        }
+        Element::Include {
+            variables,
+            elements,
+            ..
+        } => {
+            ctx.variables.push_scope(variables);
+            render_elements(ctx, elements);
+            ctx.variables.pop_scope();
+        }
+        Element::LineBreak
+        | Element::LineBreaks(_)
+        | Element::HorizontalRule
+        | Element::ClearFloat(_) => ctx.push_break(),
+        Element::Partial(_) => panic!("Encountered partial element during parsing"),
+
+        // Elements which have no text of their own
+        Element::AlignMarker(_)
+        | Element::AnchorName(_)
+        | Element::Image { .. }
+        | Element::Embed { .. }
+        | Element::Gallery { .. }
+        | Element::FormInput(_)
+        | Element::Math { .. }
+        | Element::MathInline { .. }
+        | Element::EquationReference(_)
+        | Element::BibliographyCite(_)
+        | Element::BibliographyBlock { .. }
+        | Element::Html { .. }
+        | Element::Iframe { .. }
+        | Element::Void => (),
+    }
+}
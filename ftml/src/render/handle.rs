@@ -18,34 +18,54 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use crate::data::{PageInfo, PageRef, PartialPageInfo};
+use crate::data::{FileInfo, FileRef, PageInfo, PageRef, PartialPageInfo, UserInfo};
+use crate::i18n;
 use crate::prelude::PageCallbacks;
 use crate::settings::WikitextSettings;
-use crate::tree::{ImageSource, LinkLabel, LinkLocation};
+use crate::tree::{GallerySize, ImageSource, LinkLabel, LinkLocation, NoteType};
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::sync::Arc;
 use strum_macros::IntoStaticStr;
 
 #[derive(Debug)]
 pub struct Handle<'t> {
-    callbacks: Rc<dyn PageCallbacks>,
+    callbacks: Arc<dyn PageCallbacks>,
+    language: String,
     internal_links: HashMap<PageRef<'t>, PartialPageInfo<'t>>,
+    users: HashMap<Cow<'t, str>, UserInfo<'t>>,
+    files: HashMap<FileRef<'t>, FileInfo<'t>>,
 }
 
 impl<'t> Handle<'t> {
     pub fn new(
-        callbacks: Rc<dyn PageCallbacks>,
+        callbacks: Arc<dyn PageCallbacks>,
+        language: &str,
         raw_internal_links: &Vec<PartialPageInfo<'t>>,
+        raw_users: &[UserInfo<'t>],
+        raw_files: &[FileInfo<'t>],
     ) -> Self {
         let mut internal_links = HashMap::new();
         for info in raw_internal_links {
             internal_links.insert(info.page_ref.to_owned(), info.to_owned());
         }
 
+        let mut users = HashMap::new();
+        for info in raw_users {
+            users.insert(info.name.clone(), info.clone());
+        }
+
+        let mut files = HashMap::new();
+        for info in raw_files {
+            files.insert(info.file.clone(), info.clone());
+        }
+
         Handle {
             callbacks,
+            language: str!(language),
             internal_links,
+            users,
+            files,
         }
     }
 
@@ -71,6 +91,37 @@ impl<'t> Handle<'t> {
         }
     }
 
+    pub fn get_user_info(&self, name: &str) -> Option<&UserInfo<'t>> {
+        info!("Fetching user information");
+
+        self.users.get(name)
+    }
+
+    pub fn get_file_info(&self, file: &FileRef<'t>) -> Option<&FileInfo<'t>> {
+        info!("Fetching file information");
+
+        self.files.get(file)
+    }
+
+    pub fn get_file_link(
+        &self,
+        file: &FileRef,
+        info: &PageInfo,
+        settings: &WikitextSettings,
+    ) -> Option<String> {
+        info!("Getting link for attached file");
+
+        if !settings.allow_local_paths {
+            warn!("Specified attached file when local paths are disabled");
+            return None;
+        }
+
+        Some(format!(
+            "//{}/local--files/{}/{}",
+            &info.media_domain, &file.page, &file.file,
+        ))
+    }
+
     pub fn get_image_link<'a>(
         &self,
         source: &ImageSource<'a>,
@@ -79,26 +130,32 @@ impl<'t> Handle<'t> {
     ) -> Option<Cow<'a, str>> {
         info!("Getting file link for image");
 
-        let (site, page, file): (&str, Cow<str>, &str) = match source {
-            ImageSource::Url(url) => return Some(Cow::clone(url)),
-            ImageSource::File1 { .. } | ImageSource::File2 { .. }
-                if !settings.allow_local_paths =>
-            {
-                warn!("Specified path image source when local paths are disabled");
-                return None;
+        match source {
+            ImageSource::Url(url) => Some(Cow::clone(url)),
+            _ => {
+                let file = source.file_ref(info)?;
+                self.get_file_link(&file, info, settings).map(Cow::Owned)
             }
-            ImageSource::File1 { file } => (&info.site, info.full_name(), file),
-            ImageSource::File2 { page, file } => (&info.site, page.to_owned(), file),
-        };
+        }
+    }
+
+    pub fn get_thumbnail_link<'a>(
+        &self,
+        image_url: &'a str,
+        size: GallerySize,
+    ) -> Cow<'a, str> {
+        info!("Getting thumbnail link for image (size {})", size.name());
+
+        if size == GallerySize::Original {
+            return Cow::Borrowed(image_url);
+        }
 
-        // cross-site images are unsupported
-        if info.site != site {
-            None
-        } else {
-            Some(Cow::Owned(format!(
-                "//{}/local--files/{page}/{file}",
-                &info.media_domain
-            )))
+        match self
+            .callbacks
+            .get_thumbnail_url(Cow::Borrowed(image_url), size)
+        {
+            Some(url) => Cow::Owned(url.into_owned()),
+            None => Cow::Borrowed(image_url),
         }
     }
 
@@ -146,11 +203,34 @@ impl<'t> Handle<'t> {
         f(label_text.as_ref());
     }
 
+    /// Gets a message in the page's language, preferring any override from the callbacks.
     pub fn get_message(&self, message: &str) -> String {
-        self.callbacks
-            .get_i18n_message(Cow::from(message))
-            .as_ref()
-            .to_owned()
+        let language = Cow::from(self.language.as_str());
+
+        if let Some(message) = self
+            .callbacks
+            .get_i18n_message(language, Cow::from(message))
+        {
+            return message.into_owned();
+        }
+
+        match i18n::get_message(&self.language, message) {
+            Some(message) => str!(message),
+            None => {
+                error!("Unknown message requested (key {message})");
+                str!("?")
+            }
+        }
+    }
+
+    /// Gets the default title for a note of the given type.
+    pub fn get_note_title(&self, note_type: NoteType) -> String {
+        match note_type {
+            NoteType::Info => self.get_message("note-info"),
+            NoteType::Warning => self.get_message("note-warning"),
+            NoteType::Danger => self.get_message("note-danger"),
+            NoteType::Tip => self.get_message("note-tip"),
+        }
     }
 
     pub fn get_html_injected_code(&self, html_id: &str) -> String {
@@ -25,7 +25,7 @@ use std::collections::HashSet;
 
 macro_rules! tag_method {
     ($tag:tt) => {
-        pub fn $tag(self) -> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
+        pub fn $tag(self) -> HtmlBuilderTag<'c, 'i, 'h, 'e, 't, 'w> {
             self.tag(stringify!($tag))
         }
     };
@@ -40,25 +40,25 @@ const SOLO_HTML_TAGS: [&str; 14] = [
 // Main struct
 
 #[derive(Debug)]
-pub struct HtmlBuilder<'c, 'i, 'h, 'e, 't>
+pub struct HtmlBuilder<'c, 'i, 'h, 'e, 't, 'w>
 where
     'e: 't,
 {
-    ctx: &'c mut HtmlContext<'i, 'h, 'e, 't>,
+    ctx: &'c mut HtmlContext<'i, 'h, 'e, 't, 'w>,
 }
 
-impl<'c, 'i, 'h, 'e, 't> HtmlBuilder<'c, 'i, 'h, 'e, 't>
+impl<'c, 'i, 'h, 'e, 't, 'w> HtmlBuilder<'c, 'i, 'h, 'e, 't, 'w>
 where
     'e: 't,
 {
     #[inline]
-    pub fn new(ctx: &'c mut HtmlContext<'i, 'h, 'e, 't>) -> Self {
+    pub fn new(ctx: &'c mut HtmlContext<'i, 'h, 'e, 't, 'w>) -> Self {
         HtmlBuilder { ctx }
     }
 
     /// Create a new HTML element with the given tag type.
     #[inline]
-    pub fn tag(self, tag: &'t str) -> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
+    pub fn tag(self, tag: &'t str) -> HtmlBuilderTag<'c, 'i, 'h, 'e, 't, 'w> {
         debug_assert!(is_alphanumeric(tag));
 
         let HtmlBuilder { ctx } = self;
@@ -67,14 +67,14 @@ where
 
     /// Create a new custom element. Tag must start with `wj-`.
     #[inline]
-    pub fn element(self, tag: &'t str) -> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
+    pub fn element(self, tag: &'t str) -> HtmlBuilderTag<'c, 'i, 'h, 'e, 't, 'w> {
         debug_assert!(tag.starts_with("wj-"));
 
         self.tag(tag)
     }
 
     #[inline]
-    pub fn table_cell(self, header: bool) -> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
+    pub fn table_cell(self, header: bool) -> HtmlBuilderTag<'c, 'i, 'h, 'e, 't, 'w> {
         if header {
             self.tag("th")
         } else {
@@ -118,18 +118,18 @@ where
 // Helper structs
 
 #[derive(Debug)]
-pub struct HtmlBuilderTag<'c, 'i, 'h, 'e, 't>
+pub struct HtmlBuilderTag<'c, 'i, 'h, 'e, 't, 'w>
 where
     'e: 't,
 {
-    ctx: &'c mut HtmlContext<'i, 'h, 'e, 't>,
+    ctx: &'c mut HtmlContext<'i, 'h, 'e, 't, 'w>,
     tag: &'t str,
     in_tag: bool,
     in_contents: bool,
 }
 
-impl<'c, 'i, 'h, 'e, 't> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
-    pub fn new(ctx: &'c mut HtmlContext<'i, 'h, 'e, 't>, tag: &'t str) -> Self {
+impl<'c, 'i, 'h, 'e, 't, 'w> HtmlBuilderTag<'c, 'i, 'h, 'e, 't, 'w> {
+    pub fn new(ctx: &'c mut HtmlContext<'i, 'h, 'e, 't, 'w>, tag: &'t str) -> Self {
         ctx.push_raw('<');
         ctx.push_raw_str(tag);
 
@@ -272,7 +272,7 @@ impl<'c, 'i, 'h, 'e, 't> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
     }
 }
 
-impl<'c, 'i, 'h, 'e, 't> Drop for HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
+impl<'c, 'i, 'h, 'e, 't, 'w> Drop for HtmlBuilderTag<'c, 'i, 'h, 'e, 't, 'w> {
     fn drop(&mut self) {
         if self.in_tag && !self.in_contents {
             self.ctx.push_raw('>');
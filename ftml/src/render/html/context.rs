@@ -21,30 +21,36 @@
 use super::builder::HtmlBuilder;
 use super::escape::escape;
 use super::meta::{HtmlMeta, HtmlMetaType};
-use super::output::HtmlOutput;
+use super::output::HtmlStreamOutput;
+use super::placeholder::ModuleInvocation;
 use super::random::Random;
+use super::writer::HtmlWriter;
 use crate::data::{Backlinks, PageInfo};
 use crate::data::{PageCallbacks, PageRef};
 use crate::info;
+use crate::latex::LatexMacros;
 use crate::next_index::{NextIndex, TableOfContentsIndex};
 use crate::render::Handle;
 use crate::settings::WikitextSettings;
-use crate::tree::{Element, VariableScopes};
+use crate::tree::{Element, Module, VariableScopes};
+use std::borrow::Cow;
 use std::fmt::{self, Write};
 use std::num::NonZeroUsize;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug)]
-pub struct HtmlContext<'i, 'h, 'e, 't>
+pub struct HtmlContext<'i, 'h, 'e, 't, 'w>
 where
     'e: 't,
 {
-    body: String,
+    writer: HtmlWriter<'w>,
     styles: Vec<String>,
     meta: Vec<HtmlMeta>,
     backlinks: Backlinks<'static>,
+    modules: Vec<ModuleInvocation>,
+    json_ld: Option<String>,
     info: &'i PageInfo<'i>,
-    callbacks: Rc<dyn PageCallbacks>,
+    callbacks: Arc<dyn PageCallbacks>,
     handle: &'h Handle<'t>,
     settings: &'e WikitextSettings,
     random: Random,
@@ -59,33 +65,45 @@ where
     //
     table_of_contents: &'e [Element<'t>],
     has_toc_block: bool,
+    heading_anchors: &'e [String],
     footnotes: &'e [Vec<Element<'t>>],
+    equations: &'e [(Option<String>, String)],
+    bibliography: &'e [(String, Vec<Element<'t>>)],
 
     //
     // Other fields to track
     //
+    latex_macros: LatexMacros,
     code_snippet_index: NonZeroUsize,
     table_of_contents_index: usize,
     equation_index: NonZeroUsize,
     footnote_index: NonZeroUsize,
+    module_nonce: Option<String>,
 }
 
-impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
+impl<'i, 'h, 'e, 't, 'w> HtmlContext<'i, 'h, 'e, 't, 'w> {
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         info: &'i PageInfo<'i>,
-        callbacks: Rc<dyn PageCallbacks>,
+        callbacks: Arc<dyn PageCallbacks>,
         handle: &'h Handle<'t>,
         settings: &'e WikitextSettings,
         table_of_contents: &'e [Element<'t>],
         has_toc_block: bool,
+        heading_anchors: &'e [String],
         footnotes: &'e [Vec<Element<'t>>],
+        equations: &'e [(Option<String>, String)],
+        bibliography: &'e [(String, Vec<Element<'t>>)],
+        writer: HtmlWriter<'w>,
     ) -> Self {
         HtmlContext {
-            body: String::new(),
+            writer,
             styles: Vec::new(),
             meta: Self::initial_metadata(info),
             backlinks: Backlinks::new(),
+            modules: Vec::new(),
+            json_ld: None,
             info,
             callbacks,
             handle,
@@ -94,11 +112,16 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
             variables: VariableScopes::new(),
             table_of_contents,
             has_toc_block,
+            heading_anchors,
             footnotes,
+            equations,
+            bibliography,
+            latex_macros: LatexMacros::new(),
             code_snippet_index: NonZeroUsize::new(1).unwrap(),
             table_of_contents_index: 0,
             equation_index: NonZeroUsize::new(1).unwrap(),
             footnote_index: NonZeroUsize::new(1).unwrap(),
+            module_nonce: None,
         }
     }
 
@@ -139,12 +162,12 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
 
     // Field access
     #[inline]
-    pub fn info(&self) -> &PageInfo<'i> {
+    pub fn info(&self) -> &'i PageInfo<'i> {
         self.info
     }
 
     #[inline]
-    pub fn callbacks(&self) -> Rc<dyn PageCallbacks> {
+    pub fn callbacks(&self) -> Arc<dyn PageCallbacks> {
         self.callbacks.clone()
     }
 
@@ -183,11 +206,64 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
         self.has_toc_block
     }
 
+    /// Adds the ID namespace from the settings, if there is one.
+    ///
+    /// See `WikitextSettings::id_namespace`.
+    pub fn namespaced_id<'a>(&self, id: &'a str) -> Cow<'a, str> {
+        match self.settings.id_namespace {
+            Some(ref namespace) => Cow::Owned(format!("{namespace}-{id}")),
+            None => Cow::Borrowed(id),
+        }
+    }
+
+    /// Gets the text-derived ID for the heading with the given TOC index, if any.
+    #[inline]
+    pub fn heading_anchor(&self, index: usize) -> Option<&'e str> {
+        self.heading_anchors
+            .get(index)
+            .map(|anchor| anchor.as_str())
+    }
+
     #[inline]
     pub fn footnotes(&self) -> &'e [Vec<Element<'t>>] {
         self.footnotes
     }
 
+    /// Gets the number and LaTeX source of the equation with the given name, if it exists.
+    pub fn equation(&self, name: &str) -> Option<(NonZeroUsize, &'e str)> {
+        self.equations
+            .iter()
+            .enumerate()
+            .find(|(_, (equation_name, _))| equation_name.as_deref() == Some(name))
+            .map(|(index, (_, latex_source))| {
+                (NonZeroUsize::new(index + 1).unwrap(), latex_source.as_str())
+            })
+    }
+
+    #[inline]
+    pub fn bibliography(&self) -> &'e [(String, Vec<Element<'t>>)] {
+        self.bibliography
+    }
+
+    /// Gets the number of the bibliography entry with the given label, if it exists.
+    pub fn bibliography_number(&self, label: &str) -> Option<NonZeroUsize> {
+        self.bibliography
+            .iter()
+            .position(|(entry_label, _)| entry_label == label)
+            .map(|index| NonZeroUsize::new(index + 1).unwrap())
+    }
+
+    /// Gets the LaTeX macros defined so far on the page.
+    #[inline]
+    pub fn latex_macros(&self) -> &LatexMacros {
+        &self.latex_macros
+    }
+
+    #[inline]
+    pub fn latex_macros_mut(&mut self) -> &mut LatexMacros {
+        &mut self.latex_macros
+    }
+
     pub fn next_code_snippet_index(&mut self) -> NonZeroUsize {
         let index = self.code_snippet_index;
         self.code_snippet_index = NonZeroUsize::new(index.get() + 1).unwrap();
@@ -212,6 +288,34 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
         index
     }
 
+    /// Records a module invocation for the host to render, returning its placeholder ID.
+    pub fn add_deferred_module(&mut self, module: &Module) -> String {
+        let nonce = match self.module_nonce {
+            Some(ref nonce) => nonce,
+            None => {
+                let mut nonce = String::new();
+                self.random.generate_token_into(&mut nonce);
+                self.module_nonce.insert(nonce)
+            }
+        };
+
+        let id = format!("{nonce}-{}", self.modules.len());
+        let params = module
+            .params()
+            .iter()
+            .map(|(key, value)| (str!(key), str!(value)))
+            .collect();
+
+        self.modules.push(ModuleInvocation {
+            id: id.clone(),
+            name: str!(module.name()),
+            params,
+            body: str!(module.text()),
+        });
+
+        id
+    }
+
     #[inline]
     pub fn get_footnote(&self, index_one: NonZeroUsize) -> Option<&'e [Element<'t>]> {
         self.footnotes
@@ -226,7 +330,7 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
     // Buffer management
     #[inline]
     pub fn buffer(&mut self) -> &mut String {
-        &mut self.body
+        self.writer.buffer()
     }
 
     #[inline]
@@ -234,6 +338,16 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
         self.styles.push(style);
     }
 
+    #[inline]
+    pub fn add_meta(&mut self, meta: HtmlMeta) {
+        self.meta.push(meta);
+    }
+
+    #[inline]
+    pub fn set_json_ld(&mut self, json_ld: String) {
+        self.json_ld = Some(json_ld);
+    }
+
     #[inline]
     pub fn push_raw(&mut self, ch: char) {
         self.buffer().push(ch);
@@ -250,39 +364,44 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
     }
 
     #[inline]
-    pub fn html(&mut self) -> HtmlBuilder<'_, 'i, 'h, 'e, 't> {
+    pub fn html(&mut self) -> HtmlBuilder<'_, 'i, 'h, 'e, 't, 'w> {
         HtmlBuilder::new(self)
     }
-}
 
-impl<'i, 'h, 'e, 't> From<HtmlContext<'i, 'h, 'e, 't>> for HtmlOutput {
-    #[inline]
-    fn from(ctx: HtmlContext<'i, 'h, 'e, 't>) -> HtmlOutput {
+    /// Flushes the remaining output, returning the collected side outputs.
+    pub fn finish(self) -> Result<HtmlStreamOutput, fmt::Error> {
         let HtmlContext {
-            body,
+            writer,
             styles,
             meta,
             backlinks,
+            modules,
+            json_ld,
             ..
-        } = ctx;
+        } = self;
+
+        writer.finish()?;
 
-        HtmlOutput {
-            body,
+        Ok(HtmlStreamOutput {
             styles,
             meta,
             backlinks,
-        }
+            modules,
+            json_ld,
+        })
     }
 }
 
-impl<'i, 'h, 'e, 't> Write for HtmlContext<'i, 'h, 'e, 't> {
+impl<'i, 'h, 'e, 't, 'w> Write for HtmlContext<'i, 'h, 'e, 't, 'w> {
     #[inline]
     fn write_str(&mut self, s: &str) -> fmt::Result {
         self.buffer().write_str(s)
     }
 }
 
-impl<'i, 'h, 'e, 't> NextIndex<TableOfContentsIndex> for HtmlContext<'i, 'h, 'e, 't> {
+impl<'i, 'h, 'e, 't, 'w> NextIndex<TableOfContentsIndex>
+    for HtmlContext<'i, 'h, 'e, 't, 'w>
+{
     #[inline]
     fn next(&mut self) -> usize {
         self.next_table_of_contents_index()
@@ -0,0 +1,90 @@
+/*
+ * render/html/element/bibliography.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+
+pub fn render_bibcite(ctx: &mut HtmlContext, label: &str) {
+    info!("Rendering bibliography citation (label '{label}')");
+
+    match ctx.bibliography_number(label) {
+        Some(index) => {
+            let href = format!("#{}", ctx.namespaced_id(&format!("bibitem-{index}")));
+
+            str_write!(ctx, "[");
+            ctx.html()
+                .a()
+                .attr(attr!(
+                    "class" => "bibcite w-bibcite",
+                    "href" => &href,
+                    "data-label" => label,
+                ))
+                .inner(index.to_string());
+            str_write!(ctx, "]");
+        }
+        None => {
+            ctx.html()
+                .span()
+                .attr(attr!(
+                    "class" => "bibcite w-bibcite-missing",
+                    "data-label" => label,
+                ))
+                .inner("[?]");
+        }
+    }
+}
+
+pub fn render_bibliography_block(ctx: &mut HtmlContext, title: Option<&str>) {
+    info!(
+        "Rendering bibliography block (title {})",
+        title.unwrap_or("<default>"),
+    );
+
+    let title = match title {
+        Some(title) => String::from(title),
+        None => ctx.handle().get_message("bibliography-block-title"),
+    };
+
+    ctx.html()
+        .div()
+        .attr(attr!("class" => "bibitems"))
+        .contents(|ctx| {
+            ctx.html()
+                .div()
+                .attr(attr!("class" => "title"))
+                .inner(&title);
+
+            for (index, (label, contents)) in ctx.bibliography().iter().enumerate() {
+                let index = index + 1;
+                let id = ctx.namespaced_id(&format!("bibitem-{index}")).into_owned();
+
+                ctx.html()
+                    .div()
+                    .attr(attr!(
+                        "id" => &id,
+                        "class" => "bibitem",
+                        "data-label" => label,
+                    ))
+                    .contents(|ctx| {
+                        str_write!(ctx, "{index}. ");
+                        render_elements(ctx, contents);
+                    });
+            }
+        });
+}
@@ -21,6 +21,7 @@
 use regex::{Regex, RegexBuilder};
 
 use super::prelude::*;
+use crate::settings::HeadingAnchors;
 use crate::tree::{Container, ContainerType, HtmlTag};
 
 pub fn render_container(ctx: &mut HtmlContext, container: &Container) {
@@ -67,16 +68,37 @@ pub fn render_container(ctx: &mut HtmlContext, container: &Container) {
                 .inner(container.elements());
         }
 
-        ContainerType::Header(_) => {
-            let tag_spec = container.ctype().html_tag(ctx);
-            let mut tag = ctx.html().tag(tag_spec.tag());
-            match tag_spec {
-                HtmlTag::TagAndId { id, .. } if has_toc_block => {
-                    tag.attr(attr!("id" => &id));
-                }
-                _ => {}
+        ContainerType::Header(heading) => {
+            let (index_id, anchor) = if heading.has_toc {
+                let index = ctx.next_table_of_contents_index();
+                let index_id = ctx.namespaced_id(&format!("toc{index}")).into_owned();
+                let anchor = ctx
+                    .heading_anchor(index)
+                    .map(|anchor| ctx.namespaced_id(anchor).into_owned());
+
+                (Some(index_id), anchor)
+            } else {
+                (None, None)
+            };
+
+            // Positional IDs are only used with a table of contents,
+            // while those from the heading text are always used.
+            let index_id = index_id.filter(|_| has_toc_block);
+            let both = ctx.settings().heading_anchors == HeadingAnchors::SlugAndIndex;
+
+            let mut tag = ctx.html().tag(heading.level.html_tag());
+            if let Some(id) = anchor.as_deref().or(index_id.as_deref()) {
+                tag.attr(attr!("id" => id));
             }
+
             tag.contents(|ctx| {
+                // Keep the older positional ID available too
+                if let Some(ref id) = index_id {
+                    if both && anchor.is_some() {
+                        ctx.html().a().attr(attr!("id" => id));
+                    }
+                }
+
                 ctx.html().span().inner(container.elements());
             });
         }
@@ -0,0 +1,91 @@
+/*
+ * render/html/element/embed.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::settings::{is_valid_media_id, EmbedMedia};
+
+pub fn render_embed(
+    ctx: &mut HtmlContext,
+    provider: &str,
+    media_id: &str,
+    width: Option<u32>,
+    height: Option<u32>,
+) {
+    info!("Rendering embed (provider '{provider}', media ID '{media_id}')");
+
+    // The tree may not have come from this parser, so check it again
+    if !is_valid_media_id(media_id) {
+        warn!("Invalid media ID for embed, skipping");
+        return;
+    }
+
+    let (url, media, iframe) = match ctx.settings().embed.get(provider) {
+        Some(provider) => (
+            provider.build_embed_url(media_id),
+            provider.media,
+            provider.iframe,
+        ),
+        None => {
+            warn!("No embed provider named '{provider}', skipping");
+            return;
+        }
+    };
+
+    let width = width.map(|width| width.to_string());
+    let height = height.map(|height| height.to_string());
+    let class = format!("wj-embed wj-embed-{}", provider.to_ascii_lowercase());
+
+    ctx.html()
+        .div()
+        .attr(attr!(
+            "class" => &class,
+            "data-embed-provider" => provider,
+            "data-media-id" => media_id,
+        ))
+        .contents(|ctx| {
+            let tag = match (iframe, media) {
+                (true, _) => "iframe",
+                (false, EmbedMedia::Video) => "video",
+                (false, EmbedMedia::Audio) => "audio",
+            };
+
+            let mut tag = ctx.html().tag(tag);
+            tag.attr(attr!(
+                "src" => &url,
+                "width" => width.as_deref().unwrap_or(""); if width.is_some(),
+                "height" => height.as_deref().unwrap_or(""); if height.is_some(),
+            ));
+
+            if iframe {
+                tag.attr(attr!(
+                    "sandbox" => "allow-scripts allow-same-origin allow-popups allow-presentation",
+                    "allow" => "autoplay; encrypted-media; fullscreen; picture-in-picture",
+                    "allowfullscreen" => "",
+                    "referrerpolicy" => "strict-origin-when-cross-origin",
+                    "loading" => "lazy",
+                ));
+            } else {
+                tag.attr(attr!(
+                    "controls" => "",
+                    "preload" => "metadata",
+                ));
+            }
+        });
+}
@@ -24,6 +24,10 @@ pub fn render_footnote(ctx: &mut HtmlContext) {
     info!("Rendering footnote reference");
 
     let index = ctx.next_footnote_index();
+    let id = ctx
+        .namespaced_id(&format!("footnoteref-{index}"))
+        .into_owned();
+    let href = format!("#{}", ctx.namespaced_id(&format!("footnote-{index}")));
 
     ctx.html()
         .sup()
@@ -32,9 +36,9 @@ pub fn render_footnote(ctx: &mut HtmlContext) {
             ctx.html()
                 .a()
                 .attr(attr!(
-                    "id" => format!("footnoteref-{}", index).as_str(),
+                    "id" => &id,
                     "class" => "footnoteref w-footnoteref",
-                    "href" => format!("#footnote-{}", index).as_str(),
+                    "href" => &href,
                 ))
                 .inner(index.to_string());
         });
@@ -61,13 +65,17 @@ pub fn render_footnote_block(ctx: &mut HtmlContext, title: Option<&str>) {
                 .inner(&title);
             for (index, contents) in ctx.footnotes().iter().enumerate() {
                 let index = index + 1;
+                let id = ctx.namespaced_id(&format!("footnote-{index}")).into_owned();
+                let href =
+                    format!("#{}", ctx.namespaced_id(&format!("footnoteref-{index}")));
+
                 ctx.html()
                     .div()
-                    .attr(attr!("id" => &format!("footnote-{}", index), "class" => "footnote-footer"))
+                    .attr(attr!("id" => &id, "class" => "footnote-footer"))
                     .contents(|ctx| {
                         ctx.html()
                             .a()
-                            .attr(attr!("href" => &format!("#footnoteref-{}", index)))
+                            .attr(attr!("href" => &href))
                             .inner(index.to_string());
                         str_write!(ctx, ". ");
                         render_elements(ctx, contents);
@@ -19,10 +19,141 @@
  */
 
 use super::prelude::*;
-use crate::tree::FormInput;
+use crate::tree::{AttributeMap, ButtonType, FormInput, SelectOption};
+use std::borrow::Cow;
 
 pub fn render_form_input(ctx: &mut HtmlContext, input: &FormInput) {
     info!("Rendering form input");
 
-    ctx.html().input().attr(attr!(;; input.attributes));
+    match input {
+        FormInput::Input { attributes } => {
+            ctx.html().input().attr(attr!(;; attributes));
+        }
+        FormInput::Checkbox {
+            label,
+            checked,
+            attributes,
+        } => render_choice(ctx, "checkbox", None, label, *checked, attributes),
+        FormInput::Radio {
+            group,
+            label,
+            checked,
+            attributes,
+        } => render_choice(ctx, "radio", Some(group), label, *checked, attributes),
+        FormInput::Select {
+            label,
+            options,
+            attributes,
+        } => render_select(ctx, label, options, attributes),
+        FormInput::TextArea {
+            label,
+            contents,
+            attributes,
+        } => render_textarea(ctx, label, contents, attributes),
+        FormInput::Button {
+            button_type,
+            label,
+            attributes,
+        } => {
+            let label = match label {
+                Some(label) => label.to_string(),
+                None => match button_type {
+                    ButtonType::Submit => ctx.handle().get_message("button-submit"),
+                    ButtonType::Reset => ctx.handle().get_message("button-reset"),
+                },
+            };
+
+            ctx.html()
+                .tag("button")
+                .attr(attr!(
+                    "type" => button_type.name(),
+                    "class" => "wj-form-button";;
+                    attributes
+                ))
+                .inner(&label);
+        }
+    }
+}
+
+/// Renders a checkbox or radio button, wrapped in its label.
+fn render_choice(
+    ctx: &mut HtmlContext,
+    input_type: &str,
+    group: Option<&str>,
+    label: &Option<Cow<str>>,
+    checked: bool,
+    attributes: &AttributeMap,
+) {
+    let class = format!("wj-form-{input_type}");
+
+    ctx.html()
+        .tag("label")
+        .attr(attr!("class" => &class))
+        .contents(|ctx| {
+            ctx.html().input().attr(attr!(
+                "type" => input_type,
+                "name" => group.unwrap_or(""); if group.is_some(),
+                "checked"; if checked;;
+                attributes
+            ));
+
+            render_label_text(ctx, label);
+        });
+}
+
+fn render_select(
+    ctx: &mut HtmlContext,
+    label: &Option<Cow<str>>,
+    options: &[SelectOption],
+    attributes: &AttributeMap,
+) {
+    ctx.html()
+        .tag("label")
+        .attr(attr!("class" => "wj-form-select"))
+        .contents(|ctx| {
+            render_label_text(ctx, label);
+
+            ctx.html()
+                .tag("select")
+                .attr(attr!(;; attributes))
+                .contents(|ctx| {
+                    for option in options {
+                        ctx.html()
+                            .tag("option")
+                            .attr(attr!(
+                                "value" => &option.value,
+                                "selected"; if option.selected,
+                            ))
+                            .inner(&option.label);
+                    }
+                });
+        });
+}
+
+fn render_textarea(
+    ctx: &mut HtmlContext,
+    label: &Option<Cow<str>>,
+    contents: &str,
+    attributes: &AttributeMap,
+) {
+    ctx.html()
+        .tag("label")
+        .attr(attr!("class" => "wj-form-textarea"))
+        .contents(|ctx| {
+            render_label_text(ctx, label);
+
+            ctx.html()
+                .tag("textarea")
+                .attr(attr!(;; attributes))
+                .inner(contents);
+        });
+}
+
+fn render_label_text(ctx: &mut HtmlContext, label: &Option<Cow<str>>) {
+    if let Some(label) = label {
+        ctx.html()
+            .span()
+            .attr(attr!("class" => "wj-form-label"))
+            .inner(label);
+    }
 }
@@ -0,0 +1,102 @@
+/*
+ * render/html/element/gallery.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::{AttributeMap, GalleryItem, GallerySize};
+
+pub fn render_gallery(
+    ctx: &mut HtmlContext,
+    items: &[GalleryItem],
+    size: GallerySize,
+    viewer: bool,
+    attributes: &AttributeMap,
+) {
+    info!(
+        "Rendering gallery (items {}, size {}, viewer {viewer})",
+        items.len(),
+        size.name(),
+    );
+
+    // Resolve image URLs, skipping any which are missing or not allowed
+    let images = items
+        .iter()
+        .filter_map(|GalleryItem { source, caption }| {
+            let url = ctx
+                .handle()
+                .get_image_link(source, ctx.info(), ctx.settings())?;
+
+            Some((source, url, caption))
+        })
+        .collect::<Vec<_>>();
+
+    if images.is_empty() {
+        debug!("No gallery images could be resolved, skipping");
+        return;
+    }
+
+    let class = format!("wj-gallery wj-gallery-{}", size.name());
+
+    ctx.html()
+        .div()
+        .attr(attr!("class" => &class;; attributes))
+        .contents(|ctx| {
+            for (source, url, caption) in &images {
+                let thumbnail_url = ctx.handle().get_thumbnail_link(url, size);
+                let alt = match caption {
+                    Some(caption) => caption.as_ref(),
+                    None => source.file_name(),
+                };
+
+                let build_image = |ctx: &mut HtmlContext| {
+                    ctx.html().img().attr(attr!(
+                        "class" => "wj-gallery-image",
+                        "src" => &thumbnail_url,
+                        "alt" => alt,
+                        "loading" => "lazy",
+                    ));
+                };
+
+                ctx.html()
+                    .tag("figure")
+                    .attr(attr!("class" => "wj-gallery-item"))
+                    .contents(|ctx| {
+                        if viewer {
+                            ctx.html()
+                                .a()
+                                .attr(attr!(
+                                    "class" => "wj-gallery-link",
+                                    "href" => url,
+                                    "target" => "_blank",
+                                ))
+                                .contents(build_image);
+                        } else {
+                            build_image(ctx);
+                        }
+
+                        if let Some(caption) = caption {
+                            ctx.html()
+                                .tag("figcaption")
+                                .attr(attr!("class" => "wj-gallery-caption"))
+                                .inner(caption.as_ref());
+                        }
+                    });
+            }
+        });
+}
@@ -21,6 +21,7 @@
 use std::borrow::Cow;
 
 use super::prelude::*;
+use crate::data::{FileInfo, FileRef};
 use crate::tree::{
     AnchorTarget, AttributeMap, Element, LinkLabel, LinkLocation, LinkType,
 };
@@ -73,6 +74,28 @@ pub fn render_link(
                 None => String::from(""),
             }
         )),
+        // Table of contents entries link to generated heading IDs
+        LinkLocation::Url(url) if ltype == LinkType::TableOfContents => {
+            match url.strip_prefix('#') {
+                Some(id) => Cow::Owned(format!("#{}", ctx.namespaced_id(id))),
+                None => url.to_owned(),
+            }
+        }
+        // Attached files are served from the media domain
+        LinkLocation::Url(url) if FileRef::parse_url(url).is_some() => {
+            let file = FileRef::parse_url(url).unwrap();
+
+            match ctx
+                .handle()
+                .get_file_link(&file, ctx.info(), ctx.settings())
+            {
+                Some(url) => Cow::Owned(url),
+                None => {
+                    ctx.push_escaped(&label);
+                    return;
+                }
+            }
+        }
         LinkLocation::Url(url) => url.to_owned(),
     };
 
@@ -82,7 +105,10 @@ pub fn render_link(
     };
 
     let css_class = match link {
-        LinkLocation::Url(_) => None,
+        LinkLocation::Url(url) => match FileRef::parse_url(url) {
+            Some(file) if file_missing(ctx, &file) => Some("wj-file-missing"),
+            _ => None,
+        },
         LinkLocation::Page(page, _) => {
             if ctx.page_exists(page) {
                 None
@@ -102,3 +128,89 @@ pub fn render_link(
     // Add <a> internals, i.e. the link name
     tag.inner(label);
 }
+
+pub fn render_file(ctx: &mut HtmlContext, file: &FileRef, label: Option<&str>) {
+    info!("Rendering file link (file '{file}')");
+
+    let label = label.unwrap_or(&file.file);
+    let url = match ctx.handle().get_file_link(file, ctx.info(), ctx.settings()) {
+        Some(url) => url,
+        None => {
+            // Local files are disabled, so there is nothing to link to
+            ctx.html()
+                .span()
+                .attr(attr!("class" => "wj-file"))
+                .inner(label);
+
+            return;
+        }
+    };
+
+    let info = ctx.handle().get_file_info(file).map(FileInfo::to_owned);
+    let css_class = if file_missing(ctx, file) {
+        "wj-file wj-file-missing"
+    } else {
+        "wj-file"
+    };
+
+    ctx.html()
+        .a()
+        .attr(attr!(
+            "class" => css_class,
+            "href" => &url,
+            "data-file-name" => &file.file,
+        ))
+        .inner(label);
+
+    // Show the size and type, if the host gave them
+    if let Some(info) = info {
+        let mut details = Vec::new();
+
+        if let Some(size) = info.size {
+            details.push(format_file_size(size));
+        }
+
+        if let Some(mime_type) = info.mime_type {
+            details.push(mime_type.into_owned());
+        }
+
+        if !details.is_empty() {
+            ctx.push_raw(' ');
+            ctx.html()
+                .span()
+                .attr(attr!("class" => "wj-file-info"))
+                .inner(format!("({})", details.join(", ")));
+        }
+    }
+}
+
+/// Whether the host reported this file as not existing.
+fn file_missing(ctx: &HtmlContext, file: &FileRef) -> bool {
+    match ctx.handle().get_file_info(file) {
+        Some(info) => !info.exists,
+        None => false,
+    }
+}
+
+/// Formats a file size in bytes for display, such as `12.3 kB`.
+fn format_file_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+
+    if size < 1024 {
+        return format!("{size} B");
+    }
+
+    let mut value = size as f64 / 1024.0;
+    let mut unit = UNITS[0];
+
+    for next_unit in &UNITS[1..] {
+        if value < 1024.0 {
+            break;
+        }
+
+        value /= 1024.0;
+        unit = next_unit;
+    }
+
+    format!("{value:.1} {unit}")
+}
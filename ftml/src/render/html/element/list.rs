@@ -19,7 +19,7 @@
  */
 
 use super::prelude::*;
-use crate::tree::{AttributeMap, ListItem, ListType};
+use crate::tree::{AttributeMap, ListItem, ListType, NumberStyle, NumberedList};
 
 pub fn render_list(
     ctx: &mut HtmlContext,
@@ -35,7 +35,20 @@ pub fn render_list(
     let list_tag = ltype.html_tag();
     let mut tag = ctx.html().tag(list_tag);
 
-    tag.attr(attr!(;; attributes)).contents(|ctx| {
+    // Add numbering for ordered lists, if not the default
+    let numbered = match ltype {
+        ListType::Numbered(numbered) => numbered,
+        _ => NumberedList::default(),
+    };
+    let start = numbered.start.map(|start| start.to_string());
+
+    tag.attr(attr!(
+        "start" => start.as_deref().unwrap_or(""); if start.is_some(),
+        "type" => numbered.style.html_type(); if numbered.style != NumberStyle::Decimal,
+        "reversed"; if numbered.reversed;;
+        attributes,
+    ))
+    .contents(|ctx| {
         for list_item in list_items {
             match list_item {
                 ListItem::Elements {
@@ -19,6 +19,10 @@
  */
 
 use super::prelude::*;
+use crate::latex::{self, LatexError, Token};
+
+#[cfg(feature = "mathml")]
+use crate::render::html::escape;
 use cfg_if::cfg_if;
 use std::num::NonZeroUsize;
 
@@ -42,6 +46,13 @@ pub fn render_math_block(ctx: &mut HtmlContext, name: Option<&str>, latex_source
         latex_source,
     );
 
+    // Blocks with only macro definitions are a preamble, and aren't numbered or shown
+    if latex::is_definitions_only(latex_source) {
+        info!("Math block only has definitions, adding macros");
+        let _ = ctx.latex_macros_mut().expand(latex_source);
+        return;
+    }
+
     let index = ctx.next_equation_index();
 
     render_latex(ctx, name, Some(index), latex_source, DisplayStyle::Block);
@@ -49,6 +60,13 @@ pub fn render_math_block(ctx: &mut HtmlContext, name: Option<&str>, latex_source
 
 pub fn render_math_inline(ctx: &mut HtmlContext, latex_source: &str) {
     info!("Rendering math inline (source '{latex_source}'");
+
+    if latex::is_definitions_only(latex_source) {
+        info!("Inline math only has definitions, adding macros");
+        let _ = ctx.latex_macros_mut().expand(latex_source);
+        return;
+    }
+
     render_latex(ctx, None, None, latex_source, DisplayStyle::Inline);
 }
 
@@ -59,17 +77,24 @@ fn render_latex(
     latex_source: &str,
     display: DisplayStyle,
 ) {
-    // error_type is unused if MathML is disabled
-    let (html_tag, wj_type, _error_type) = match display {
+    let (html_tag, wj_type, error_type) = match display {
         DisplayStyle::Block => ("div", "wj-math-block", "wj-error-block"),
         DisplayStyle::Inline => ("span", "wj-math-inline", "wj-error-inline"),
     };
 
+    // Numbered equations can be linked to
+    let id =
+        index.map(|index| ctx.namespaced_id(&format!("equation-{index}")).into_owned());
+
+    // Apply macros, and add any defined here for later math
+    let tokens = ctx.latex_macros_mut().expand(latex_source);
+
     // Outer container
     ctx.html()
         .tag(html_tag)
         .attr(attr!(
             "class" => "wj-math " wj_type,
+            "id" => id.as_deref().unwrap_or(""); if id.is_some(),
             "data-name" => name.unwrap_or(""); if name.is_some(),
         ))
         .contents(|ctx| {
@@ -110,55 +135,183 @@ fn render_latex(
                 .inner(latex_source);
 
             // Add generated MathML
-            cfg_if! {
-                if #[cfg(feature = "mathml")] {
-                    match latex_to_mathml(latex_source, display) {
-                        Ok(mathml) => {
-                            info!("Processed LaTeX -> MathML");
-
-                            // Inject MathML elements
-                            ctx.html()
-                                .element("wj-math-ml")
-                                .attr(attr!("class" => "wj-math-ml"))
-                                .contents(|ctx| ctx.push_raw_str(&mathml));
-                        }
-                        Err(error) => {
-                            warn!("Error processing LaTeX -> MathML: {error}");
-                            let error = str!(error);
-
-                            ctx.html()
-                                .span()
-                                .attr(attr!("class" => _error_type))
-                                .inner(error);
-                        }
-                    }
+            match tokens {
+                Ok(ref tokens) => {
+                    render_mathml(ctx, tokens, latex_source, display, error_type)
                 }
+                Err(ref error) => render_error(ctx, latex_source, error, error_type),
             }
         });
 }
 
+fn render_mathml(
+    ctx: &mut HtmlContext,
+    tokens: &[Token],
+    latex_source: &str,
+    display: DisplayStyle,
+    error_type: &str,
+) {
+    let block = matches!(display, DisplayStyle::Block);
+
+    // Parsed first, so invalid formulas never reach the other renderer
+    let node = match latex::parse(tokens) {
+        Ok(node) => node,
+        Err(error) => {
+            warn!("Error processing LaTeX: {error}");
+            render_error(ctx, latex_source, &error, error_type);
+            return;
+        }
+    };
+
+    let alt_text = latex::linearize(&node);
+
+    cfg_if! {
+        if #[cfg(feature = "mathml")] {
+            match latex_to_mathml(&latex::to_source(tokens), display) {
+                // Errors may also be reported inline, rather than returned
+                Ok(mathml) if !mathml.contains("[PARSE ERROR") => {
+                    info!("Processed LaTeX -> MathML");
+
+                    let mathml = add_alt_text(&mathml, &alt_text);
+
+                    // Inject MathML elements
+                    ctx.html()
+                        .element("wj-math-ml")
+                        .attr(attr!("class" => "wj-math-ml"))
+                        .contents(|ctx| ctx.push_raw_str(&mathml));
+
+                    return;
+                }
+                Ok(_) => {
+                    warn!("Error processing LaTeX -> MathML, using fallback");
+                }
+                Err(error) => {
+                    warn!("Error processing LaTeX -> MathML, using fallback: {error}");
+                }
+            }
+        }
+    }
+
+    // Fallback, using the built-in LaTeX support
+    info!("Processed LaTeX -> MathML (fallback)");
+
+    let mut mathml = String::new();
+    latex::render_mathml(&mut mathml, &node, block, &alt_text);
+
+    ctx.html()
+        .element("wj-math-ml")
+        .attr(attr!("class" => "wj-math-ml"))
+        .contents(|ctx| ctx.push_raw_str(&mathml));
+}
+
+/// Adds the spoken description to the `<math>` element from `latex2mathml`.
+#[cfg(feature = "mathml")]
+fn add_alt_text(mathml: &str, alt_text: &str) -> String {
+    let mut output = String::with_capacity(mathml.len() + alt_text.len() + 11);
+
+    match mathml.strip_prefix("<math") {
+        Some(rest) => {
+            output.push_str("<math alttext=\"");
+            escape(&mut output, alt_text);
+            output.push('"');
+            output.push_str(rest);
+        }
+        None => output.push_str(mathml),
+    }
+
+    output
+}
+
+/// Shows the error message, and the source with the failing token highlighted.
+fn render_error(
+    ctx: &mut HtmlContext,
+    latex_source: &str,
+    error: &LatexError,
+    error_type: &str,
+) {
+    let start = error.span.start.min(latex_source.len());
+    let end = error.span.end.clamp(start, latex_source.len());
+
+    ctx.html()
+        .span()
+        .attr(attr!("class" => error_type " wj-math-error"))
+        .contents(|ctx| {
+            ctx.html()
+                .span()
+                .attr(attr!("class" => "wj-math-error-message"))
+                .inner(error.to_string());
+
+            ctx.html()
+                .code()
+                .attr(attr!("class" => "wj-math-error-source"))
+                .contents(|ctx| {
+                    ctx.push_escaped(&latex_source[..start]);
+                    ctx.html()
+                        .tag("mark")
+                        .attr(attr!("class" => "wj-math-error-token"))
+                        .inner(&latex_source[start..end]);
+                    ctx.push_escaped(&latex_source[end..]);
+                });
+        });
+}
+
 pub fn render_equation_reference(ctx: &mut HtmlContext, name: &str) {
     info!("Rendering equation reference (name '{name}')");
 
+    let equation = ctx.equation(name);
+
     ctx.html()
         .span()
         .attr(attr!("class" => "wj-equation-ref"))
-        .contents(|ctx| {
-            // Equation marker that is hoverable
-            ctx.html()
-                .element("wj-equation-ref-marker")
-                .attr(attr!(
-                    "class" => "wj-equation-ref-marker",
-                    "type" => "button",
-                    "data-name" => name,
-                ))
-                .inner(name);
-
-            // Tooltip shown on hover.
-            ctx.html().span().attr(attr!(
-                "class" => "wj-equation-ref-tooltip",
-                "aria-hidden" => "true",
-            ));
-            // TODO tooltip contents
+        .contents(|ctx| match equation {
+            Some((index, latex_source)) => {
+                let href =
+                    format!("#{}", ctx.namespaced_id(&format!("equation-{index}")),);
+
+                // Equation marker that is hoverable
+                ctx.html()
+                    .a()
+                    .attr(attr!(
+                        "class" => "wj-equation-ref-marker",
+                        "href" => &href,
+                        "data-name" => name,
+                    ))
+                    .contents(|ctx| str_write!(ctx, "({index})"));
+
+                // Tooltip shown on hover, with the equation itself.
+                ctx.html()
+                    .span()
+                    .attr(attr!(
+                        "class" => "wj-equation-ref-tooltip",
+                        "aria-hidden" => "true",
+                    ))
+                    .contents(|ctx| render_tooltip(ctx, latex_source));
+            }
+            None => {
+                // No such equation, a warning was emitted during parsing
+                ctx.html()
+                    .span()
+                    .attr(attr!(
+                        "class" => "wj-equation-ref-marker wj-equation-ref-missing",
+                        "data-name" => name,
+                    ))
+                    .inner("(?)");
+            }
         });
 }
+
+/// Renders the referenced equation, without affecting macros for the rest of the page.
+fn render_tooltip(ctx: &mut HtmlContext, latex_source: &str) {
+    let tokens = ctx.latex_macros().clone().expand(latex_source);
+
+    match tokens {
+        Ok(ref tokens) => render_mathml(
+            ctx,
+            tokens,
+            latex_source,
+            DisplayStyle::Inline,
+            "wj-error-inline",
+        ),
+        Err(ref error) => render_error(ctx, latex_source, error, "wj-error-inline"),
+    }
+}
@@ -20,12 +20,15 @@
 
 //! Module that implements HTML rendering for `Element` and its children.
 
+mod bibliography;
 mod collapsible;
 mod container;
 mod date;
 mod definition_list;
+mod embed;
 mod footnotes;
 mod form;
+mod gallery;
 mod iframe;
 mod image;
 mod include;
@@ -33,6 +36,7 @@ mod link;
 mod list;
 mod math;
 mod module;
+mod note;
 mod table;
 mod tabs;
 mod text;
@@ -47,19 +51,23 @@ mod prelude {
     pub use crate::tree::Element;
 }
 
+use self::bibliography::{render_bibcite, render_bibliography_block};
 use self::collapsible::{render_collapsible, Collapsible};
 use self::container::{render_color, render_container};
 use self::date::render_date;
 use self::definition_list::render_definition_list;
+use self::embed::render_embed;
 use self::footnotes::{render_footnote, render_footnote_block};
 use self::form::render_form_input;
+use self::gallery::render_gallery;
 use self::iframe::{render_html, render_iframe};
 use self::image::render_image;
 use self::include::{render_include, render_variable};
-use self::link::{render_anchor, render_link};
+use self::link::{render_anchor, render_file, render_link};
 use self::list::render_list;
 use self::math::{render_equation_reference, render_math_block, render_math_inline};
 use self::module::render_module;
+use self::note::render_note;
 use self::table::render_table;
 use self::tabs::render_tabview;
 use self::text::{render_code, render_email, render_html_entity, render_wikitext_raw};
@@ -114,6 +122,7 @@ pub fn render_element(ctx: &mut HtmlContext, element: &Element) {
             label,
             target,
         } => render_link(ctx, link, label, *target, *ltype),
+        Element::File { file, label } => render_file(ctx, file, ref_cow!(label)),
         Element::Image {
             source,
             link,
@@ -121,6 +130,18 @@ pub fn render_element(ctx: &mut HtmlContext, element: &Element) {
             alignment,
             attributes,
         } => render_image(ctx, source, link, *link_target, *alignment, attributes),
+        Element::Embed {
+            provider,
+            media_id,
+            width,
+            height,
+        } => render_embed(ctx, provider, media_id, *width, *height),
+        Element::Gallery {
+            items,
+            size,
+            viewer,
+            attributes,
+        } => render_gallery(ctx, items, *size, *viewer, attributes),
         Element::List {
             ltype,
             items,
@@ -149,6 +170,12 @@ pub fn render_element(ctx: &mut HtmlContext, element: &Element) {
                 *text_align,
             ),
         ),
+        Element::Note {
+            note_type,
+            title,
+            elements,
+            attributes,
+        } => render_note(ctx, *note_type, ref_cow!(title), elements, attributes),
         Element::TableOfContents { align, attributes } => {
             render_table_of_contents(ctx, *align, attributes)
         }
@@ -158,6 +185,12 @@ pub fn render_element(ctx: &mut HtmlContext, element: &Element) {
                 render_footnote_block(ctx, ref_cow!(title));
             }
         }
+        Element::BibliographyCite(label) => render_bibcite(ctx, label),
+        Element::BibliographyBlock { title } => {
+            if !ctx.bibliography().is_empty() {
+                render_bibliography_block(ctx, ref_cow!(title));
+            }
+        }
         Element::User { name, show_avatar } => render_user(ctx, name, *show_avatar),
         Element::Date {
             value,
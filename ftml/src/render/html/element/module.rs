@@ -19,6 +19,8 @@
  */
 
 use super::prelude::*;
+use crate::render::html::placeholder::write_placeholder;
+use crate::render::ModuleRenderMode;
 use crate::tree::Module;
 use std::borrow::Cow;
 
@@ -29,11 +31,18 @@ pub fn render_module(ctx: &mut HtmlContext, module: &Module) {
         return;
     }
 
+    if ctx.settings().defer_modules {
+        let id = ctx.add_deferred_module(module);
+        write_placeholder(ctx.buffer(), &id);
+        return;
+    }
+
     let rendered: Cow<str> = {
         let v = ctx.callbacks().render_module(
             module.name().to_owned(),
             module.params().to_owned(),
             module.text().to_owned(),
+            ModuleRenderMode::Html,
         );
         v
     };
@@ -0,0 +1,61 @@
+/*
+ * render/html/element/note.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::{AttributeMap, NoteType};
+
+pub fn render_note(
+    ctx: &mut HtmlContext,
+    note_type: NoteType,
+    title: Option<&str>,
+    elements: &[Element],
+    attributes: &AttributeMap,
+) {
+    info!("Rendering note (type {})", note_type.name());
+
+    let title = match title {
+        Some(title) => String::from(title),
+        None => ctx.handle().get_note_title(note_type),
+    };
+
+    let class = format!("wj-note wj-note-{}", note_type.name());
+
+    ctx.html()
+        .tag("aside")
+        .attr(attr!(
+            "class" => &class,
+            "role" => "note",
+            "data-note-type" => note_type.name();;
+            attributes
+        ))
+        .contents(|ctx| {
+            if !title.is_empty() {
+                ctx.html()
+                    .div()
+                    .attr(attr!("class" => "wj-note-title"))
+                    .inner(&title);
+            }
+
+            ctx.html()
+                .div()
+                .attr(attr!("class" => "wj-note-content"))
+                .inner(elements);
+        });
+}
@@ -19,60 +19,129 @@
  */
 
 use super::prelude::*;
-use crate::tree::{Alignment, Table};
+use crate::tree::{Alignment, Table, TableRow, TableSection};
 use std::num::NonZeroU32;
 
 pub fn render_table(ctx: &mut HtmlContext, table: &Table) {
     info!("Rendering table");
 
-    let mut column_span_buf = String::new();
-    let value_one = NonZeroU32::new(1).unwrap();
+    // Rows in each section, HTML requires them in this order
+    let rows_in = |section| {
+        table
+            .rows
+            .iter()
+            .enumerate()
+            .filter(move |(_, row)| row.section == section)
+    };
+    let has_rows = |section| rows_in(section).next().is_some();
 
     // Full table
     ctx.html()
         .table()
-        .attr(attr!(;; &table.attributes))
+        .attr(attr!(
+            "data-sortable" => "true"; if table.sortable;;
+            &table.attributes,
+        ))
         .contents(|ctx| {
-            ctx.html().tbody().contents(|ctx| {
-                // Each row
-                for row in &table.rows {
-                    ctx.html() //
-                        .tr()
-                        .attr(attr!(;; &row.attributes))
-                        .contents(|ctx| {
-                            // Each cell in a row
-                            for cell in &row.cells {
-                                let elements: &[Element] = &cell.elements;
-                                let align_style = match cell.align {
-                                    Some(Alignment::Left) => "text-align: left",
-                                    Some(Alignment::Right) => "text-align: right",
-                                    Some(Alignment::Center) => "text-align: center",
-                                    Some(Alignment::Justify) => "text-align: justify",
-                                    None => "",
-                                };
-
-                                if cell.column_span > value_one {
-                                    column_span_buf.clear();
-                                    str_write!(column_span_buf, "{}", cell.column_span);
-                                }
-
-                                ctx.html()
-                                    .table_cell(cell.header)
-                                    .attr(attr!(
-                                        // Add column span if not default (1)
-                                        "colspan" => &column_span_buf;
-                                            if cell.column_span > value_one,
-
-                                        // Add alignment if specified
-                                        "style" => align_style;
-                                            if cell.align.is_some();;
-
-                                        &cell.attributes,
-                                    ))
-                                    .inner(elements);
-                            }
-                        });
+            if let Some(caption) = &table.caption {
+                ctx.html().tag("caption").inner(caption);
+            }
+
+            for section in [TableSection::Head, TableSection::Body, TableSection::Foot] {
+                // Always have a body, even if the table is empty
+                let empty_body = section == TableSection::Body
+                    && !has_rows(TableSection::Head)
+                    && !has_rows(TableSection::Foot);
+
+                if has_rows(section) || empty_body {
+                    ctx.html().tag(section.html_tag()).contents(|ctx| {
+                        render_rows(ctx, table, section, rows_in(section))
+                    });
                 }
-            });
+            }
         });
 }
+
+fn render_rows<'a, 't: 'a, I>(
+    ctx: &mut HtmlContext,
+    table: &Table,
+    section: TableSection,
+    rows: I,
+) where
+    I: Iterator<Item = (usize, &'a TableRow<'t>)>,
+{
+    let mut column_span_buf = String::new();
+    let mut row_span_buf = String::new();
+    let value_one = NonZeroU32::new(1).unwrap();
+
+    // Column headers declare how their column is sorted
+    let column_starts = if table.sortable && section == TableSection::Head {
+        table.column_starts()
+    } else {
+        Vec::new()
+    };
+
+    // Each row
+    for (row_index, row) in rows {
+        ctx.html() //
+            .tr()
+            .attr(attr!(;; &row.attributes))
+            .contents(|ctx| {
+                // Each cell in a row
+                for (cell_index, cell) in row.cells.iter().enumerate() {
+                    let elements: &[Element] = &cell.elements;
+                    let align_style = match cell.align {
+                        Some(Alignment::Left) => "text-align: left",
+                        Some(Alignment::Right) => "text-align: right",
+                        Some(Alignment::Center) => "text-align: center",
+                        Some(Alignment::Justify) => "text-align: justify",
+                        None => "",
+                    };
+
+                    if cell.column_span > value_one {
+                        column_span_buf.clear();
+                        str_write!(column_span_buf, "{}", cell.column_span);
+                    }
+
+                    if cell.row_span > value_one {
+                        row_span_buf.clear();
+                        str_write!(row_span_buf, "{}", cell.row_span);
+                    }
+
+                    let sort_type = column_starts
+                        .get(row_index)
+                        .and_then(|starts| starts.get(cell_index))
+                        .map(|&column| table.column_type(column).name());
+
+                    ctx.html()
+                        .table_cell(cell.header)
+                        .attr(attr!(
+                            // Add column span if not default (1)
+                            "colspan" => &column_span_buf;
+                                if cell.column_span > value_one,
+
+                            // Add row span if not default (1)
+                            "rowspan" => &row_span_buf;
+                                if cell.row_span > value_one,
+
+                            // Mark column headers for assistive technologies
+                            "scope" => "col";
+                                if cell.header && section == TableSection::Head,
+
+                            // Add sorting information for sortable tables
+                            "data-sort-type" => sort_type.unwrap_or("");
+                                if cell.header && sort_type.is_some(),
+                            "data-sort-value" => cell.sort_value.as_deref().unwrap_or("");
+                                if cell.sort_value.is_some(),
+
+                            // Add alignment if specified
+                            "style" => align_style;
+                                if cell.align.is_some();;
+
+                            &cell.attributes,
+                        ))
+                        .inner(elements);
+                }
+            });
+    }
+}
@@ -41,14 +41,18 @@ pub fn render_table_of_contents(
         _ => "",
     };
 
+    let toc_id = ctx.namespaced_id("toc").into_owned();
+    let action_bar_id = ctx.namespaced_id("toc-action-bar").into_owned();
+    let list_id = ctx.namespaced_id("toc-list").into_owned();
+
     let build_toc = |ctx: &mut HtmlContext| {
         ctx.html()
             .div()
-            .attr(attr!("class" => &format!("w-toc{float_class_append}"), "id" => "toc"))
+            .attr(attr!("class" => &format!("w-toc{float_class_append}"), "id" => &toc_id))
             .contents(|ctx| {
                 ctx.html()
                     .div()
-                    .attr(attr!("id" => "toc-action-bar", "class" => "toc-action-bar"))
+                    .attr(attr!("id" => &action_bar_id, "class" => "toc-action-bar"))
                     .contents(|ctx| {
                         ctx.html()
                             .a()
@@ -68,7 +72,7 @@ pub fn render_table_of_contents(
 
                 ctx.html()
                     .div()
-                    .attr(attr!("class" => "w-toc-content", "id" => "toc-list"))
+                    .attr(attr!("class" => "w-toc-content", "id" => &list_id))
                     .inner(table_of_contents);
             });
     };
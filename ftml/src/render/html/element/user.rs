@@ -18,16 +18,83 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::borrow::Cow;
-
 use super::prelude::*;
+use crate::data::UserInfo;
 
 pub fn render_user(ctx: &mut HtmlContext, name: &str, show_avatar: bool) {
     info!("Rendering user block (name '{name}', show-avatar {show_avatar})");
 
-    let rendered: Cow<str> = {
-        let v = ctx.callbacks().render_user(Cow::from(name), show_avatar);
-        v
+    let user = ctx.handle().get_user_info(name).map(UserInfo::to_owned);
+
+    match user {
+        Some(user) if user.exists => render_user_existing(ctx, &user, show_avatar),
+        _ => render_user_deleted(ctx, name),
+    }
+}
+
+fn render_user_existing(ctx: &mut HtmlContext, user: &UserInfo, show_avatar: bool) {
+    debug!("Found user, rendering profile link");
+
+    let name = user.name.as_ref();
+    let id = user.id.map(|id| id.to_string());
+    let display_name = match user.display_name {
+        Some(ref display_name) => display_name.as_ref(),
+        None => name,
+    };
+    let profile_url = user.profile_url.as_deref().unwrap_or("javascript:;");
+    let avatar_url = match user.avatar_url {
+        Some(ref avatar_url) if show_avatar => Some(avatar_url.as_ref()),
+        _ => None,
+    };
+    let class = match avatar_url {
+        Some(_) => "printuser w-user avatarhover",
+        None => "printuser w-user",
     };
-    str_write!(ctx.buffer(), "{}", rendered);
+
+    ctx.html()
+        .span()
+        .attr(attr!(
+            "class" => class,
+            "data-user-id" => id.as_deref().unwrap_or(""); if id.is_some(),
+            "data-user-name" => name,
+        ))
+        .contents(|ctx| {
+            if let Some(avatar_url) = avatar_url {
+                ctx.html()
+                    .a()
+                    .attr(attr!("href" => profile_url))
+                    .contents(|ctx| {
+                        ctx.html().img().attr(attr!(
+                            "class" => "small",
+                            "src" => avatar_url,
+                            "alt" => display_name,
+                        ));
+                    });
+            }
+
+            ctx.html()
+                .a()
+                .attr(attr!(
+                    "class" => "w-user-preview-trigger",
+                    "href" => profile_url,
+                    "data-user-id" => id.as_deref().unwrap_or(""); if id.is_some(),
+                    "data-user-name" => name,
+                    "aria-haspopup" => "dialog"; if id.is_some(),
+                ))
+                .inner(display_name);
+        });
+}
+
+fn render_user_deleted(ctx: &mut HtmlContext, name: &str) {
+    debug!("User not found, rendering as deleted");
+
+    let message = ctx.handle().get_message("user-deleted");
+
+    ctx.html()
+        .span()
+        .attr(attr!(
+            "class" => "printuser deleted w-user w-user-deleted",
+            "data-user-name" => name,
+        ))
+        .inner(message);
 }
@@ -30,11 +30,20 @@ pub fn escape_char(c: char) -> Option<&'static str> {
     }
 }
 
+/// Escapes `s` into `buffer`.
+///
+/// Runs of characters which need no escaping are copied
+/// as a single slice, rather than one character at a time.
 pub fn escape(buffer: &mut String, s: &str) {
-    for ch in s.chars() {
-        match escape_char(ch) {
-            Some(s) => buffer.push_str(s),
-            None => buffer.push(ch),
+    let mut last = 0;
+
+    for (idx, ch) in s.char_indices() {
+        if let Some(escaped) = escape_char(ch) {
+            buffer.push_str(&s[last..idx]);
+            buffer.push_str(escaped);
+            last = idx + ch.len_utf8();
         }
     }
+
+    buffer.push_str(&s[last..]);
 }
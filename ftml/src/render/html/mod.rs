@@ -26,33 +26,49 @@ mod element;
 mod escape;
 mod meta;
 mod output;
+mod placeholder;
+mod preview;
 mod random;
 mod render;
+mod writer;
 
 pub use self::meta::{HtmlMeta, HtmlMetaType};
-pub use self::output::HtmlOutput;
-use std::rc::Rc;
+pub use self::output::{HtmlOutput, HtmlStreamOutput};
+pub use self::placeholder::{fill_placeholders, ModuleInvocation};
+
+pub(crate) use self::escape::{escape, escape_char};
 
 use self::context::HtmlContext;
-use crate::data::{PageCallbacks, PageInfo};
+use self::preview::add_preview_metadata;
+use self::writer::{HtmlWriter, IoWriter};
+use crate::data::{FileRef, PageCallbacks, PageInfo};
 use crate::render::html::element::render_elements;
 use crate::render::{Handle, Render};
 use crate::settings::WikitextSettings;
 use crate::tree::SyntaxTree;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct HtmlRender;
 
-impl Render for HtmlRender {
-    type Output = HtmlOutput;
-
-    fn render(
+impl HtmlRender {
+    /// Renders the syntax tree, writing the body directly into `writer`.
+    ///
+    /// Output is buffered internally and written out in chunks, so the
+    /// full body is never held in memory. Everything besides the body
+    /// is collected and returned once rendering is finished.
+    pub fn render_to_writer<W: fmt::Write>(
         &self,
         tree: &SyntaxTree,
         page_info: &PageInfo,
-        page_callbacks: Rc<dyn PageCallbacks>,
+        page_callbacks: Arc<dyn PageCallbacks>,
         settings: &WikitextSettings,
-    ) -> HtmlOutput {
+        writer: &mut W,
+    ) -> Result<HtmlStreamOutput, fmt::Error> {
         info!(
             "Rendering HTML (site {}, page {}, category {})",
             page_info.site.as_ref(),
@@ -63,9 +79,17 @@ impl Render for HtmlRender {
             },
         );
 
-        // fetch page details
+        // fetch page, user, and file details
         let internal_links = page_callbacks.get_page_info(&tree.internal_links);
-        let handle = Handle::new(page_callbacks.clone(), &internal_links);
+        let users = page_callbacks.get_user_info(&unique_users(&tree.users));
+        let files = page_callbacks.get_file_info(&unique_files(&tree.files));
+        let handle = Handle::new(
+            page_callbacks.clone(),
+            &page_info.language,
+            &internal_links,
+            &users,
+            &files,
+        );
 
         let mut ctx = HtmlContext::new(
             page_info,
@@ -74,13 +98,84 @@ impl Render for HtmlRender {
             settings,
             &tree.table_of_contents,
             tree.has_toc_block,
+            &tree.heading_anchors,
             &tree.footnotes,
+            &tree.equations,
+            &tree.bibliography,
+            HtmlWriter::new(writer),
         );
 
+        // Add metadata for link previews
+        if settings.preview_metadata {
+            add_preview_metadata(&mut ctx, tree);
+        }
+
         // Crawl through elements and generate HTML
         render_elements(&mut ctx, &tree.elements);
 
-        // Build and return HtmlOutput
-        ctx.into()
+        // Flush remaining output and return the rest
+        ctx.finish()
+    }
+
+    /// Like [`HtmlRender::render_to_writer`], but for byte-oriented sinks.
+    pub fn render_to_io_writer<W: io::Write>(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        page_callbacks: Arc<dyn PageCallbacks>,
+        settings: &WikitextSettings,
+        writer: &mut W,
+    ) -> io::Result<HtmlStreamOutput> {
+        let mut writer = IoWriter::new(writer);
+
+        self.render_to_writer(tree, page_info, page_callbacks, settings, &mut writer)
+            .map_err(|_| writer.into_error())
+    }
+}
+
+/// Removes repeated names, so each user is only looked up once.
+fn unique_users<'t>(users: &[Cow<'t, str>]) -> Vec<Cow<'t, str>> {
+    let mut seen = HashSet::new();
+    let mut unique = Vec::new();
+
+    for name in users {
+        if seen.insert(name.as_ref()) {
+            unique.push(name.clone());
+        }
+    }
+
+    unique
+}
+
+/// Removes repeated files, so each file is only looked up once.
+fn unique_files<'t>(files: &[FileRef<'t>]) -> Vec<FileRef<'t>> {
+    let mut seen = HashSet::new();
+    let mut unique = Vec::new();
+
+    for file in files {
+        if seen.insert(file) {
+            unique.push(file.clone());
+        }
+    }
+
+    unique
+}
+
+impl Render for HtmlRender {
+    type Output = HtmlOutput;
+
+    fn render(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        page_callbacks: Arc<dyn PageCallbacks>,
+        settings: &WikitextSettings,
+    ) -> HtmlOutput {
+        let mut body = String::new();
+        let output = self
+            .render_to_writer(tree, page_info, page_callbacks, settings, &mut body)
+            .expect("Writing to a String cannot fail");
+
+        output.with_body(body)
     }
 }
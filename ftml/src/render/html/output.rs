@@ -19,6 +19,7 @@
  */
 
 use super::meta::HtmlMeta;
+use super::placeholder::ModuleInvocation;
 use crate::data::Backlinks;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -27,4 +28,51 @@ pub struct HtmlOutput {
     pub styles: Vec<String>,
     pub meta: Vec<HtmlMeta>,
     pub backlinks: Backlinks<'static>,
+    pub modules: Vec<ModuleInvocation>,
+
+    /// A JSON-LD `Article` description of the page, if it is a full page.
+    ///
+    /// It is escaped so it can be placed as-is within a
+    /// `<script type="application/ld+json">` tag.
+    pub json_ld: Option<String>,
+}
+
+/// Everything produced by HTML rendering except the body.
+///
+/// This is returned when the body is written directly into a sink,
+/// see [`HtmlRender::render_to_writer`](super::HtmlRender::render_to_writer).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HtmlStreamOutput {
+    pub styles: Vec<String>,
+    pub meta: Vec<HtmlMeta>,
+    pub backlinks: Backlinks<'static>,
+    pub modules: Vec<ModuleInvocation>,
+
+    /// A JSON-LD `Article` description of the page, if it is a full page.
+    ///
+    /// It is escaped so it can be placed as-is within a
+    /// `<script type="application/ld+json">` tag.
+    pub json_ld: Option<String>,
+}
+
+impl HtmlStreamOutput {
+    #[inline]
+    pub fn with_body(self, body: String) -> HtmlOutput {
+        let HtmlStreamOutput {
+            styles,
+            meta,
+            backlinks,
+            modules,
+            json_ld,
+        } = self;
+
+        HtmlOutput {
+            body,
+            styles,
+            meta,
+            backlinks,
+            modules,
+            json_ld,
+        }
+    }
 }
@@ -0,0 +1,95 @@
+/*
+ * render/html/placeholder.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Placeholders for modules whose rendering is deferred to the host.
+//!
+//! Each placeholder is an HTML comment containing an ID made of a random
+//! per-render nonce and the module's index. User content cannot produce
+//! a placeholder, since any `<` it contains is escaped, and content from
+//! elsewhere (such as module output) cannot guess the nonce.
+
+use std::collections::HashMap;
+
+const PLACEHOLDER_START: &str = "<!--wj-module:";
+const PLACEHOLDER_END: &str = "-->";
+
+/// A module invocation which was not rendered, see `WikitextSettings::defer_modules`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ModuleInvocation {
+    /// The placeholder ID, to be used as a key for [`fill_placeholders`].
+    pub id: String,
+    pub name: String,
+    pub params: HashMap<String, String>,
+    pub body: String,
+}
+
+/// Writes the placeholder for the given ID.
+pub fn write_placeholder(buffer: &mut String, id: &str) {
+    buffer.push_str(PLACEHOLDER_START);
+    buffer.push_str(id);
+    buffer.push_str(PLACEHOLDER_END);
+}
+
+/// Replaces module placeholders in `body` with their rendered HTML.
+///
+/// `rendered` maps placeholder IDs (from [`ModuleInvocation::id`]) to
+/// the module output. Placeholders for which no output is given are
+/// left as-is, which is harmless since they are HTML comments.
+///
+/// The body is scanned only once, so any placeholder-like text within the
+/// substituted module output is never itself replaced.
+pub fn fill_placeholders<S>(body: &str, rendered: &HashMap<String, S>) -> String
+where
+    S: AsRef<str>,
+{
+    let mut output = String::with_capacity(body.len());
+    let mut remaining = body;
+
+    while let Some(start) = remaining.find(PLACEHOLDER_START) {
+        let (before, placeholder) = remaining.split_at(start);
+        output.push_str(before);
+
+        let id_start = PLACEHOLDER_START.len();
+        let id_end = match placeholder[id_start..].find(PLACEHOLDER_END) {
+            Some(end) => id_start + end,
+            None => {
+                remaining = placeholder;
+                break;
+            }
+        };
+
+        let id = &placeholder[id_start..id_end];
+        let placeholder_end = id_end + PLACEHOLDER_END.len();
+
+        match rendered.get(id) {
+            Some(html) => output.push_str(html.as_ref()),
+            None => {
+                warn!("No rendered output for module placeholder '{id}'");
+                output.push_str(&placeholder[..placeholder_end]);
+            }
+        }
+
+        remaining = &placeholder[placeholder_end..];
+    }
+
+    output.push_str(remaining);
+    output
+}
@@ -0,0 +1,182 @@
+/*
+ * render/html/preview.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Metadata used by other sites to show previews of links to the page.
+//!
+//! This produces the OpenGraph and Twitter card `<meta>` tags,
+//! and, for full pages, a JSON-LD `Article` description.
+
+use super::context::HtmlContext;
+use super::meta::{HtmlMeta, HtmlMetaType};
+use crate::data::PageInfo;
+use crate::render::excerpt::{ExcerptLength, ExcerptRender};
+use crate::render::Render;
+use crate::settings::WikitextMode;
+use crate::tree::{DefinitionListItem, Element, ImageSource, ListItem, SyntaxTree};
+use serde_json::json;
+
+/// How long the description shown in link previews may be.
+const DESCRIPTION_LENGTH: usize = 200;
+
+pub fn add_preview_metadata(ctx: &mut HtmlContext, tree: &SyntaxTree) {
+    info!("Generating link preview metadata");
+
+    let info = ctx.info();
+    let title = match info.alt_title {
+        Some(ref alt_title) => format!("{} - {alt_title}", info.title),
+        None => str!(info.title),
+    };
+    let url = page_url(info);
+
+    let description = ExcerptRender::new(ExcerptLength::Characters(DESCRIPTION_LENGTH))
+        .render(tree, info, ctx.callbacks(), ctx.settings())
+        .text;
+
+    let image = find_image(&tree.elements)
+        .and_then(|source| ctx.handle().get_image_link(source, info, ctx.settings()))
+        .map(|link| absolute_url(info, &link));
+
+    // OpenGraph
+    ctx.add_meta(property("og:type", str!("article")));
+    ctx.add_meta(property("og:title", title.clone()));
+    ctx.add_meta(property("og:description", description.clone()));
+    ctx.add_meta(property("og:url", url.clone()));
+
+    if let Some(ref image) = image {
+        ctx.add_meta(property("og:image", image.clone()));
+    }
+
+    // Twitter cards
+    let card = match image {
+        Some(_) => "summary_large_image",
+        None => "summary",
+    };
+
+    ctx.add_meta(name("twitter:card", str!(card)));
+    ctx.add_meta(name("twitter:title", title.clone()));
+    ctx.add_meta(name("twitter:description", description.clone()));
+
+    if let Some(ref image) = image {
+        ctx.add_meta(name("twitter:image", image.clone()));
+    }
+
+    // JSON-LD, only for full pages
+    if ctx.settings().mode == WikitextMode::Page {
+        let mut article = json!({
+            "@context": "https://schema.org",
+            "@type": "Article",
+            "headline": title,
+            "description": description,
+            "keywords": info.tags.join(","),
+            "url": url,
+        });
+
+        // "default" is the site's language, which isn't known here
+        if info.language != "default" {
+            article["inLanguage"] = json!(info.language);
+        }
+
+        if let Some(image) = image {
+            article["image"] = json!(image);
+        }
+
+        ctx.set_json_ld(escape_json(&article.to_string()));
+    }
+}
+
+fn property(name: &str, value: String) -> HtmlMeta {
+    HtmlMeta {
+        tag_type: HtmlMetaType::Property,
+        name: str!(name),
+        value,
+    }
+}
+
+fn name(name: &str, value: String) -> HtmlMeta {
+    HtmlMeta {
+        tag_type: HtmlMetaType::Name,
+        name: str!(name),
+        value,
+    }
+}
+
+fn page_url(info: &PageInfo) -> String {
+    format!("https://{}/{}", info.domain, info.full_name())
+}
+
+/// Makes a link absolute, since link previews have no base URL.
+fn absolute_url(info: &PageInfo, link: &str) -> String {
+    if link.starts_with("//") {
+        format!("https:{link}")
+    } else if link.starts_with('/') {
+        format!("https://{}{link}", info.domain)
+    } else {
+        str!(link)
+    }
+}
+
+/// Escapes JSON so it can be placed within a `<script>` tag.
+fn escape_json(json: &str) -> String {
+    json.replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+}
+
+/// Finds the first image in the page, in document order.
+fn find_image<'a, 't>(elements: &'a [Element<'t>]) -> Option<&'a ImageSource<'t>> {
+    for element in elements {
+        let image = match element {
+            Element::Image { source, .. } => Some(source),
+            Element::Gallery { items, .. } => items.first().map(|item| &item.source),
+            Element::Fragment(elements)
+            | Element::Anchor { elements, .. }
+            | Element::Color { elements, .. }
+            | Element::Collapsible { elements, .. }
+            | Element::Include { elements, .. } => find_image(elements),
+            Element::Container(container) => find_image(container.elements()),
+            Element::TabView(tabs) => {
+                tabs.iter().find_map(|tab| find_image(&tab.elements))
+            }
+            Element::Table(table) => table
+                .rows
+                .iter()
+                .flat_map(|row| &row.cells)
+                .find_map(|cell| find_image(&cell.elements)),
+            Element::List { items, .. } => items.iter().find_map(|item| match item {
+                ListItem::Elements { elements, .. } => find_image(elements),
+                ListItem::SubList { element } => {
+                    find_image(std::slice::from_ref(element.as_ref()))
+                }
+            }),
+            Element::DefinitionList(items) => {
+                items.iter().find_map(|DefinitionListItem { key, value }| {
+                    find_image(key).or_else(|| find_image(value))
+                })
+            }
+            _ => None,
+        };
+
+        if image.is_some() {
+            return image;
+        }
+    }
+
+    None
+}
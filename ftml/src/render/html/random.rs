@@ -39,7 +39,10 @@ impl Default for Random {
 impl Random {
     pub fn generate_html_id_into(&mut self, buffer: &mut String) {
         buffer.push_str("wj-id-");
+        self.generate_token_into(buffer);
+    }
 
+    pub fn generate_token_into(&mut self, buffer: &mut String) {
         let char_stream = iter::repeat(())
             .map(|_| self.rng.sample(Alphanumeric))
             .map(char::from)
@@ -0,0 +1,121 @@
+/*
+ * render/html/writer.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::{self, Debug, Write};
+use std::io;
+
+/// How large the pending buffer may grow before it is written to the sink.
+const FLUSH_THRESHOLD: usize = 8 * 1024;
+
+/// Buffered HTML output, which is periodically flushed into a sink.
+///
+/// Rendering writes into a small in-memory buffer, which is handed
+/// off to the underlying [`fmt::Write`] once it grows large enough.
+/// This way the full body never needs to be held in memory at once.
+///
+/// Rendering itself is infallible, so if the sink returns an error,
+/// it is recorded, further output is discarded, and the error is
+/// reported by [`HtmlWriter::finish`].
+pub struct HtmlWriter<'w> {
+    buffer: String,
+    sink: &'w mut dyn Write,
+    failed: bool,
+}
+
+impl<'w> HtmlWriter<'w> {
+    pub fn new(sink: &'w mut dyn Write) -> Self {
+        HtmlWriter {
+            buffer: String::with_capacity(FLUSH_THRESHOLD),
+            sink,
+            failed: false,
+        }
+    }
+
+    /// Gets the pending buffer to append output to.
+    ///
+    /// Its contents are first flushed if it has grown past the threshold.
+    pub fn buffer(&mut self) -> &mut String {
+        if self.buffer.len() >= FLUSH_THRESHOLD {
+            self.flush();
+        }
+
+        &mut self.buffer
+    }
+
+    fn flush(&mut self) {
+        if !self.failed && self.sink.write_str(&self.buffer).is_err() {
+            warn!("HTML output sink returned an error, discarding further output");
+            self.failed = true;
+        }
+
+        self.buffer.clear();
+    }
+
+    /// Writes any remaining output, returning whether the sink failed.
+    pub fn finish(mut self) -> fmt::Result {
+        self.flush();
+
+        if self.failed {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Debug for HtmlWriter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HtmlWriter")
+            .field("buffer", &self.buffer)
+            .field("failed", &self.failed)
+            .finish()
+    }
+}
+
+/// Adapts an [`io::Write`] into a [`fmt::Write`], preserving the I/O error.
+#[derive(Debug)]
+pub struct IoWriter<'w, W> {
+    inner: &'w mut W,
+    error: Option<io::Error>,
+}
+
+impl<'w, W: io::Write> IoWriter<'w, W> {
+    #[inline]
+    pub fn new(inner: &'w mut W) -> Self {
+        IoWriter { inner, error: None }
+    }
+
+    /// Converts a formatting error into the underlying I/O error.
+    pub fn into_error(self) -> io::Error {
+        match self.error {
+            Some(error) => error,
+            None => io::Error::other("formatter error"),
+        }
+    }
+}
+
+impl<W: io::Write> Write for IoWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
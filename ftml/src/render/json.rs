@@ -25,7 +25,7 @@
 
 use super::prelude::*;
 use crate::data::PageCallbacks;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct JsonRender {
@@ -52,7 +52,7 @@ impl Render for JsonRender {
         &self,
         syntax_tree: &SyntaxTree,
         page_info: &PageInfo,
-        _page_callbacks: Rc<dyn PageCallbacks>,
+        _page_callbacks: Arc<dyn PageCallbacks>,
         settings: &WikitextSettings,
     ) -> String {
         info!(
@@ -30,16 +30,21 @@ pub mod json;
 pub mod null;
 pub mod text;
 
+#[cfg(feature = "html")]
+pub mod excerpt;
+
 #[cfg(feature = "html")]
 pub mod html;
 
 mod handle;
 
+pub use self::handle::ModuleRenderMode;
+
 use self::handle::Handle;
 use crate::data::{PageCallbacks, PageInfo};
 use crate::settings::WikitextSettings;
 use crate::tree::SyntaxTree;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Abstract trait for any ftml renderer.
 ///
@@ -64,7 +69,7 @@ pub trait Render {
         &self,
         tree: &SyntaxTree,
         page_info: &PageInfo,
-        page_callbacks: Rc<dyn PageCallbacks>,
+        page_callbacks: Arc<dyn PageCallbacks>,
         settings: &WikitextSettings,
     ) -> Self::Output;
 }
@@ -25,7 +25,7 @@
 
 use super::prelude::*;
 use crate::data::PageCallbacks;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct NullRender;
@@ -38,7 +38,7 @@ impl Render for NullRender {
         &self,
         _tree: &SyntaxTree,
         _page_info: &PageInfo,
-        _page_callbacks: Rc<dyn PageCallbacks>,
+        _page_callbacks: Arc<dyn PageCallbacks>,
         _settings: &WikitextSettings,
     ) {
     }
@@ -19,6 +19,7 @@
  */
 
 use crate::data::PageInfo;
+use crate::latex::LatexMacros;
 use crate::non_empty_vec::NonEmptyVec;
 use crate::prelude::PageCallbacks;
 use crate::render::Handle;
@@ -26,7 +27,7 @@ use crate::settings::WikitextSettings;
 use crate::tree::{Element, VariableScopes};
 use std::fmt::{self, Write};
 use std::num::NonZeroUsize;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct TextContext<'i, 'h, 'e, 't>
@@ -35,7 +36,7 @@ where
 {
     output: String,
     info: &'i PageInfo<'i>,
-    callbacks: Rc<dyn PageCallbacks>,
+    callbacks: Arc<dyn PageCallbacks>,
     handle: &'h Handle<'t>,
     settings: &'e WikitextSettings,
 
@@ -49,6 +50,8 @@ where
     //
     table_of_contents: &'e [Element<'t>],
     footnotes: &'e [Vec<Element<'t>>],
+    equations: &'e [(Option<String>, String)],
+    bibliography: &'e [(String, Vec<Element<'t>>)],
 
     //
     // Other fields to track
@@ -57,7 +60,10 @@ where
     prefixes: Vec<&'static str>,
 
     /// How deep we currently are in the list.
-    list_depths: NonEmptyVec<usize>,
+    ///
+    /// Each depth has the number of its next item, and how it changes
+    /// with each item, which is negative for reversed lists.
+    list_depths: NonEmptyVec<(i64, i64)>,
 
     /// Whether we're in "invisible mode".
     /// When this is non-zero, all non-newline characters
@@ -69,6 +75,15 @@ where
 
     /// The current footnote index, for rendering.
     footnote_index: NonZeroUsize,
+
+    /// Whether we're rendering the "readable plain text" profile.
+    readable: bool,
+
+    /// Link destinations which have been numbered, in order.
+    links: Vec<String>,
+
+    /// LaTeX macros defined so far on the page.
+    latex_macros: LatexMacros,
 }
 
 impl<'i, 'h, 'e, 't> TextContext<'i, 'h, 'e, 't>
@@ -76,13 +91,17 @@ where
     'e: 't,
 {
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         info: &'i PageInfo<'i>,
-        callbacks: Rc<dyn PageCallbacks>,
+        callbacks: Arc<dyn PageCallbacks>,
         handle: &'h Handle<'t>,
         settings: &'e WikitextSettings,
         table_of_contents: &'e [Element<'t>],
         footnotes: &'e [Vec<Element<'t>>],
+        equations: &'e [(Option<String>, String)],
+        bibliography: &'e [(String, Vec<Element<'t>>)],
+        readable: bool,
     ) -> Self {
         TextContext {
             output: String::new(),
@@ -93,11 +112,16 @@ where
             variables: VariableScopes::new(),
             table_of_contents,
             footnotes,
+            equations,
+            bibliography,
             prefixes: Vec::new(),
-            list_depths: NonEmptyVec::new(1),
+            list_depths: NonEmptyVec::new((1, 1)),
             invisible: 0,
             equation_index: NonZeroUsize::new(1).unwrap(),
             footnote_index: NonZeroUsize::new(1).unwrap(),
+            readable,
+            links: Vec::new(),
+            latex_macros: LatexMacros::new(),
         }
     }
 
@@ -113,7 +137,7 @@ where
     }
 
     #[inline]
-    pub fn callbacks(&self) -> Rc<dyn PageCallbacks> {
+    pub fn callbacks(&self) -> Arc<dyn PageCallbacks> {
         self.callbacks.clone()
     }
 
@@ -152,6 +176,55 @@ where
         self.footnotes
     }
 
+    /// Gets the number of the equation with the given name, if it exists.
+    pub fn equation_number(&self, name: &str) -> Option<NonZeroUsize> {
+        self.equations
+            .iter()
+            .position(|(equation_name, _)| equation_name.as_deref() == Some(name))
+            .and_then(|index| NonZeroUsize::new(index + 1))
+    }
+
+    #[inline]
+    pub fn bibliography(&self) -> &'e [(String, Vec<Element<'t>>)] {
+        self.bibliography
+    }
+
+    /// Gets the number of the bibliography entry with the given label, if it exists.
+    pub fn bibliography_number(&self, label: &str) -> Option<NonZeroUsize> {
+        self.bibliography
+            .iter()
+            .position(|(entry_label, _)| entry_label == label)
+            .and_then(|index| NonZeroUsize::new(index + 1))
+    }
+
+    #[inline]
+    pub fn readable(&self) -> bool {
+        self.readable
+    }
+
+    #[inline]
+    pub fn links(&self) -> &[String] {
+        &self.links
+    }
+
+    /// Gets the number of the given link destination, adding it if it's new.
+    pub fn link_number(&mut self, url: &str) -> usize {
+        let index = match self.links.iter().position(|link| link == url) {
+            Some(index) => index,
+            None => {
+                self.links.push(str!(url));
+                self.links.len() - 1
+            }
+        };
+
+        index + 1
+    }
+
+    #[inline]
+    pub fn latex_macros_mut(&mut self) -> &mut LatexMacros {
+        &mut self.latex_macros
+    }
+
     pub fn next_equation_index(&mut self) -> NonZeroUsize {
         let index = self.equation_index;
         self.equation_index = NonZeroUsize::new(index.get() + 1).unwrap();
@@ -183,7 +256,7 @@ where
 
     #[inline]
     pub fn incr_list_depth(&mut self) {
-        self.list_depths.push(1);
+        self.list_depths.push((1, 1));
     }
 
     #[inline]
@@ -191,9 +264,14 @@ where
         self.list_depths.pop();
     }
 
-    pub fn next_list_index(&mut self) -> usize {
-        let index = *self.list_depths.last();
-        *self.list_depths.last_mut() += 1;
+    pub fn start_list_numbering(&mut self, first: i64, reversed: bool) {
+        let step = if reversed { -1 } else { 1 };
+        *self.list_depths.last_mut() = (first, step);
+    }
+
+    pub fn next_list_index(&mut self) -> i64 {
+        let (index, step) = *self.list_depths.last();
+        self.list_depths.last_mut().0 += step;
         index
     }
 
@@ -241,6 +319,27 @@ where
         }
     }
 
+    /// Appends text which may span several lines, keeping the current line prefixes.
+    pub fn push_lines(&mut self, s: &str) {
+        for (i, line) in s.lines().enumerate() {
+            if i > 0 {
+                self.add_newline();
+            }
+
+            self.push_str(line);
+        }
+    }
+
+    /// Runs the given closure, returning its output rather than appending it.
+    pub fn capture<F>(&mut self, f: F) -> String
+    where
+        F: FnOnce(&mut Self),
+    {
+        let start = self.output.len();
+        f(self);
+        self.output.split_off(start)
+    }
+
     #[inline]
     pub fn ends_with_newline(&self) -> bool {
         self.output.ends_with('\n')
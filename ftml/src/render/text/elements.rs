@@ -20,10 +20,14 @@
 
 //! Module that implements text rendering for `Element` and its children.
 
+use super::layout::{layout_table, LayoutCell};
 use super::TextContext;
+use crate::latex;
+use crate::render::ModuleRenderMode;
+use crate::settings::is_valid_media_id;
 use crate::tree::{
-    ContainerType, DefinitionListItem, Element, FormInput, LinkLocation, ListItem,
-    ListType, Tab,
+    ContainerType, DefinitionListItem, Element, FormInput, GalleryItem, ImageSource,
+    LinkLocation, LinkType, ListItem, ListType, Tab,
 };
 use crate::url::normalize_link;
 use std::borrow::Cow;
@@ -46,7 +50,7 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
             let mut invisible = false;
             let (add_newlines, prefix) = match container.ctype() {
                 // Don't render this at all.
-                ContainerType::Hidden => return,
+                ContainerType::Hidden | ContainerType::HiddenBlock => return,
 
                 // Render it, but invisibly.
                 // Requires setting a special mode in the context.
@@ -56,10 +60,17 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
 
                     (false, None)
                 }
+                ContainerType::InvisibleBlock => {
+                    ctx.enable_invisible();
+                    invisible = true;
+
+                    (true, None)
+                }
 
                 // If container is "terminating" (e.g. blockquote, p), then add newlines.
                 // Also, determine if we add a prefix.
                 ContainerType::Div | ContainerType::Paragraph => (true, None),
+                ContainerType::Blockquote if ctx.readable() => (true, Some("> ")),
                 ContainerType::Blockquote => (true, Some("    ")),
                 ContainerType::Header(_heading) => (true, None),
 
@@ -108,8 +119,24 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
                 ctx.disable_invisible();
             }
         }
-        Element::Module(_module) => {
-            // for now: do nothing. text rendering is not supported on Python side yet
+        Element::Module(module) => {
+            if ctx.settings().no_modules {
+                return;
+            }
+
+            let rendered = ctx.callbacks().render_module(
+                module.name().to_owned(),
+                module.params().to_owned(),
+                module.text().to_owned(),
+                ModuleRenderMode::Text,
+            );
+            let rendered = rendered.trim_matches(|c| c == '\r' || c == '\n');
+
+            if !rendered.is_empty() {
+                ctx.add_newline();
+                ctx.push_lines(rendered);
+                ctx.add_newline();
+            }
         }
         Element::Text(text) | Element::Raw(text) | Element::Email(text) => {
             ctx.push_str(text)
@@ -135,13 +162,76 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
                 ctx.add_newline();
             }
 
-            for row in &table.rows {
-                for (i, cell) in row.cells.iter().enumerate() {
-                    render_elements(ctx, &cell.elements);
+            if let Some(caption) = &table.caption {
+                ctx.push_str(caption);
+                ctx.add_newline();
+            }
+
+            if ctx.readable() {
+                let mut rows = Vec::with_capacity(table.rows.len());
+
+                for row in &table.rows {
+                    let mut cells = Vec::with_capacity(row.cells.len());
+
+                    for cell in &row.cells {
+                        let text =
+                            ctx.capture(|ctx| render_elements(ctx, &cell.elements));
+
+                        cells.push(LayoutCell {
+                            text: collapse_whitespace(&text),
+                            span: cell.column_span.get() as usize,
+                            row_span: cell.row_span.get() as usize,
+                            header: cell.header,
+                        });
+                    }
+
+                    rows.push(cells);
+                }
+
+                for line in layout_table(&rows) {
+                    ctx.push_str(&line);
+                    ctx.add_newline();
+                }
+
+                ctx.add_newline();
+                return;
+            }
+
+            // Cells spanning down from earlier rows are output as an empty
+            // column, so later cells stay in their own column. Like other
+            // cells, this is a single column regardless of column span.
+            let starts = table.column_starts();
+            let mut spanned = vec![Vec::new(); table.rows.len()];
+
+            for (row_index, (row, starts)) in table.rows.iter().zip(&starts).enumerate() {
+                for (cell, &start) in row.cells.iter().zip(starts) {
+                    let last_row =
+                        (row_index + cell.row_span.get() as usize).min(spanned.len());
+
+                    for columns in &mut spanned[row_index + 1..last_row] {
+                        columns.push(start);
+                    }
+                }
+            }
+
+            for ((row, starts), spanned) in table.rows.iter().zip(&starts).zip(spanned) {
+                let mut columns: Vec<_> = starts
+                    .iter()
+                    .copied()
+                    .zip(row.cells.iter().map(Some))
+                    .chain(spanned.into_iter().map(|start| (start, None)))
+                    .collect();
+
+                columns.sort_by_key(|&(start, _)| start);
 
-                    if i < row.cells.len() - 1 {
+                for (i, (_, cell)) in columns.into_iter().enumerate() {
+                    if i > 0 {
                         ctx.push_str("\t");
                     }
+
+                    if let Some(cell) = cell {
+                        render_elements(ctx, &cell.elements);
+                    }
                 }
 
                 ctx.add_newline();
@@ -165,7 +255,9 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
             // Anchor names are an invisible addition to the HTML
             // to aid navigation. So in text mode, they are ignored.
         }
-        Element::Link { link, label, .. } => {
+        Element::Link {
+            ltype, link, label, ..
+        } => {
             let label = {
                 let mut o_label: String = String::new();
                 ctx.handle().get_link_label(link, label, |label| {
@@ -174,16 +266,104 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
                 o_label
             };
 
-            ctx.push_str(&label)
+            ctx.push_str(&label);
+
+            // Number the link, so its destination can be listed at the end
+            if ctx.readable() && *ltype != LinkType::TableOfContents {
+                if let Some(url) = absolute_link(ctx, link) {
+                    if url != label {
+                        let number = ctx.link_number(&url);
+                        str_write!(ctx, " <{number}>");
+                    }
+                }
+            }
+        }
+        Element::File { file, label } => {
+            let label = label.as_deref().unwrap_or(&file.file);
+            ctx.push_str(label);
+
+            // Number the file, so its link can be listed at the end
+            if ctx.readable() {
+                if let Some(url) =
+                    ctx.handle().get_file_link(file, ctx.info(), ctx.settings())
+                {
+                    let number = ctx.link_number(&format!("https:{url}"));
+                    str_write!(ctx, " <{number}>");
+                }
+            }
         }
-        Element::Image { .. } => {
-            // do not render images, they are not text.
+        Element::Image {
+            source, attributes, ..
+        } => {
+            // Images are shown by their description in readable text only.
+            if ctx.readable() {
+                let alt = match attributes.get().get("alt") {
+                    Some(alt) if !alt.trim().is_empty() => alt.trim(),
+                    _ => match source {
+                        ImageSource::Url(url) => url.rsplit('/').next().unwrap_or(url),
+                        ImageSource::File1 { file } | ImageSource::File2 { file, .. } => {
+                            file
+                        }
+                    },
+                };
+
+                str_write!(ctx, "[image: {alt}]");
+            }
+        }
+        Element::Embed {
+            provider, media_id, ..
+        } => {
+            // Embeds can't be shown in text, so link to the media instead.
+            let link = ctx
+                .settings()
+                .embed
+                .get(provider)
+                .filter(|_| is_valid_media_id(media_id))
+                .map(|provider| (provider.build_link_url(media_id), provider.media));
+
+            if let Some((url, media)) = link {
+                if !ctx.ends_with_newline() {
+                    ctx.add_newline();
+                }
+
+                if ctx.readable() {
+                    str_write!(ctx, "[{}: {url}]", media.name());
+                } else {
+                    ctx.push_str(&url);
+                }
+
+                ctx.add_newline();
+            }
+        }
+        Element::Gallery { items, .. } => {
+            // Images are shown by their caption, or described in readable text.
+            for GalleryItem { source, caption } in items {
+                let caption = caption.as_deref().filter(|caption| !caption.is_empty());
+
+                if !ctx.ends_with_newline() {
+                    ctx.add_newline();
+                }
+
+                match (caption, ctx.readable()) {
+                    (Some(caption), false) => ctx.push_str(caption),
+                    (None, false) => continue,
+                    (Some(caption), true) => str_write!(ctx, "[image: {caption}]"),
+                    (None, true) => str_write!(ctx, "[image: {}]", source.file_name()),
+                }
+
+                ctx.add_newline();
+            }
         }
         Element::List { ltype, items, .. } => {
             if !ctx.ends_with_newline() {
                 ctx.add_newline();
             }
 
+            if let ListType::Numbered(numbered) = ltype {
+                let first = numbered.first_number(items);
+                ctx.start_list_numbering(first, numbered.reversed);
+            }
+
             for item in items {
                 match item {
                     ListItem::Elements {
@@ -203,9 +383,10 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
                         if !*hidden {
                             match *ltype {
                                 ListType::Bullet => ctx.push_str("* "),
-                                ListType::Numbered => {
+                                ListType::Numbered(numbered) => {
                                     let index = ctx.next_list_index();
-                                    str_write!(ctx, "{index}. ");
+                                    let number = numbered.style.format(index);
+                                    str_write!(ctx, "{number}. ");
                                 }
                                 ListType::Generic => (),
                             }
@@ -250,6 +431,16 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
                 _ => ctx.handle().get_message("collapsible-open"),
             };
 
+            // In readable text, the show text is a header for the contents
+            if ctx.readable() {
+                ctx.add_newline();
+                ctx.push_str(show_text.trim());
+                ctx.add_newline();
+                render_elements(ctx, elements);
+                ctx.add_newline();
+                return;
+            }
+
             let hide_text = match hide_text {
                 Some(s) => String::from(s.as_ref()),
                 _ => ctx.handle().get_message("collapsible-hide"),
@@ -275,9 +466,51 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
                 ctx.add_newline();
             }
         }
-        Element::FormInput(FormInput { .. }) => {
-            // forms are not rendered
+        Element::Note {
+            note_type,
+            title,
+            elements,
+            ..
+        } => {
+            info!("Rendering note (type {})", note_type.name());
+
+            let title = match title {
+                Some(title) => title.to_string(),
+                None => ctx.handle().get_note_title(*note_type),
+            };
+
+            ctx.add_newline();
+
+            // Prefix with the title, e.g. "Warning:"
+            if !title.is_empty() {
+                ctx.push_str(&title);
+
+                if !title.ends_with(|c: char| c.is_ascii_punctuation()) {
+                    ctx.push(':');
+                }
+            }
+
+            render_elements(ctx, elements);
+            ctx.add_newline();
         }
+        Element::FormInput(input) => match input {
+            // Show the state of choices, other controls are not rendered
+            FormInput::Checkbox { label, checked, .. } => {
+                ctx.push_str(if *checked { "[x]" } else { "[ ]" });
+
+                if let Some(label) = label {
+                    str_write!(ctx, " {label}");
+                }
+            }
+            FormInput::Radio { label, checked, .. } => {
+                ctx.push_str(if *checked { "(*)" } else { "( )" });
+
+                if let Some(label) = label {
+                    str_write!(ctx, " {label}");
+                }
+            }
+            _ => {}
+        },
         Element::TableOfContents { .. } => {
             info!("Rendering table of contents");
 
@@ -324,6 +557,34 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
                 ctx.add_newline();
             }
         }
+        Element::BibliographyCite(label) => match ctx.bibliography_number(label) {
+            Some(index) => str_write!(ctx, "[{index}]"),
+            None => ctx.push_str("[?]"),
+        },
+        Element::BibliographyBlock { title } => {
+            info!("Rendering bibliography block");
+
+            if ctx.bibliography().is_empty() {
+                return;
+            }
+
+            let title = match title {
+                Some(title) => String::from(title.as_ref()),
+                None => ctx.handle().get_message("bibliography-block-title"),
+            };
+
+            ctx.add_newline();
+            ctx.push_str(&title);
+            ctx.add_newline();
+
+            // Render entries in order.
+            for (index, (_, contents)) in ctx.bibliography().iter().enumerate() {
+                str_write!(ctx, "{}. ", index + 1);
+
+                render_elements(ctx, contents);
+                ctx.add_newline();
+            }
+        }
         Element::User { name, .. } => ctx.push_str(name),
         Element::Date { value, .. } => {
             str_write!(
@@ -338,26 +599,57 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
             ctx.push_str(contents);
             ctx.add_newline();
         }
-        Element::Math { .. } => {
-            // math is not supported in text
-        }
-        Element::MathInline { .. } => {
-            // math is not supported in text
+        Element::Math { latex_source, .. } => {
+            // math is shown as its source in readable text, and read aloud otherwise
+            if ctx.readable() {
+                ctx.add_newline();
+                ctx.push_lines(latex_source.trim());
+                ctx.add_newline();
+            } else if let Some(speech) = linearize_math(ctx, latex_source) {
+                ctx.add_newline();
+                ctx.push_str(&speech);
+                ctx.add_newline();
+            }
         }
-        Element::EquationReference(_name) => {
-            // math is not supported in text
+        Element::MathInline { latex_source } => {
+            if ctx.readable() {
+                ctx.push_str(latex_source.trim());
+            } else if let Some(speech) = linearize_math(ctx, latex_source) {
+                ctx.push_str(&speech);
+            }
         }
+        Element::EquationReference(name) => match ctx.equation_number(name) {
+            Some(index) => str_write!(ctx, "({index})"),
+            None => ctx.push_str("(?)"),
+        },
         Element::Html {
             contents,
             external: _,
         } => {
             str_write!(ctx, "\n{contents}\n");
         }
-        Element::Iframe { .. } => {
-            // iframes are not supported in text
+        Element::Iframe { url, .. } => {
+            // iframes can't be shown in text, so readable text gives their address.
+            if ctx.readable() {
+                if !ctx.ends_with_newline() {
+                    ctx.add_newline();
+                }
+
+                str_write!(ctx, "[iframe: {url}]");
+                ctx.add_newline();
+            }
         }
-        Element::Include { .. } => {
-            // include is not supported in text
+        Element::Include {
+            variables,
+            elements,
+            ..
+        } => {
+            // Included pages are only shown in readable text.
+            if ctx.readable() {
+                ctx.variables_mut().push_scope(variables);
+                render_elements(ctx, elements);
+                ctx.variables_mut().pop_scope();
+            }
         }
         Element::LineBreak => ctx.add_newline(),
         Element::LineBreaks(amount) => {
@@ -375,3 +667,46 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
         Element::Void => {}
     }
 }
+
+/// Reads the formula aloud, or gives its source if it can't be parsed.
+///
+/// Returns nothing if the formula only defines macros.
+fn linearize_math(ctx: &mut TextContext, latex_source: &str) -> Option<String> {
+    if latex::is_definitions_only(latex_source) {
+        let _ = ctx.latex_macros_mut().expand(latex_source);
+        return None;
+    }
+
+    let speech = ctx
+        .latex_macros_mut()
+        .expand(latex_source)
+        .and_then(|tokens| latex::parse(&tokens))
+        .map(|node| latex::linearize(&node));
+
+    match speech {
+        Ok(speech) => Some(speech),
+        Err(error) => {
+            warn!("Unable to read LaTeX aloud, using source: {error}");
+            Some(str!(latex_source.trim()))
+        }
+    }
+}
+
+/// Gets the full URL for a link, or `None` if it doesn't lead anywhere useful.
+fn absolute_link(ctx: &TextContext, link: &LinkLocation) -> Option<String> {
+    let url = normalize_link(link);
+
+    if url.starts_with('#') || url.starts_with("javascript:") {
+        None
+    } else if url.starts_with("//") {
+        Some(format!("https:{url}"))
+    } else if url.starts_with('/') {
+        Some(format!("https://{}{url}", ctx.info().domain))
+    } else {
+        Some(url.into_owned())
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,203 @@
+/*
+ * render/text/layout.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Helpers for laying out plain text in fixed-width columns.
+//!
+//! All widths are measured in terminal columns rather than bytes or
+//! characters, so that Cyrillic and CJK text lines up correctly.
+
+use crate::tree::place_cells;
+use unicode_width::UnicodeWidthStr;
+
+/// A rendered table cell, ready to be laid out.
+#[derive(Debug, Clone)]
+pub struct LayoutCell {
+    pub text: String,
+    pub span: usize,
+    pub row_span: usize,
+    pub header: bool,
+}
+
+/// Lays out table rows as padded columns separated by `" | "`.
+///
+/// Cells spanning several columns are given the combined width of those
+/// columns. Cells spanning several rows leave their columns blank in the
+/// rows below. A rule is drawn below any row made up entirely of header cells.
+pub fn layout_table(rows: &[Vec<LayoutCell>]) -> Vec<String> {
+    const SEPARATOR: &str = " | ";
+
+    let starts = place_cells(
+        rows.iter()
+            .map(|row| row.iter().map(|cell| (cell.span, cell.row_span))),
+    );
+    let column_count = rows
+        .iter()
+        .zip(&starts)
+        .filter_map(|(row, starts)| Some(starts.last()? + row.last()?.span))
+        .max()
+        .unwrap_or(0);
+
+    // Single-column cells determine the width of each column
+    let mut widths = vec![0; column_count];
+    for (row, starts) in rows.iter().zip(&starts) {
+        for (cell, &column) in row.iter().zip(starts) {
+            if cell.span == 1 {
+                widths[column] = widths[column].max(cell.text.width());
+            }
+        }
+    }
+
+    // Widen the last spanned column if a spanning cell still doesn't fit
+    for (row, starts) in rows.iter().zip(&starts) {
+        for (cell, &column) in row.iter().zip(starts) {
+            if cell.span > 1 {
+                let columns = column..column + cell.span;
+                let available = spanned_width(&widths[columns.clone()], SEPARATOR);
+                let needed = cell.text.width();
+
+                if needed > available {
+                    widths[columns.end - 1] += needed - available;
+                }
+            }
+        }
+    }
+
+    let mut lines = Vec::with_capacity(rows.len());
+    for (row, starts) in rows.iter().zip(&starts) {
+        let mut line = String::new();
+        let mut column = 0;
+
+        for (cell, &start) in row.iter().zip(starts) {
+            // Leave columns covered by a cell from above blank
+            if start > column {
+                if column > 0 {
+                    line.push_str(SEPARATOR);
+                }
+
+                let width = spanned_width(&widths[column..start], SEPARATOR);
+                pad_to(&mut line, "", width);
+            }
+
+            if start > 0 {
+                line.push_str(SEPARATOR);
+            }
+
+            let width = spanned_width(&widths[start..start + cell.span], SEPARATOR);
+            pad_to(&mut line, &cell.text, width);
+            column = start + cell.span;
+        }
+
+        lines.push(str!(line.trim_end()));
+
+        if !row.is_empty() && row.iter().all(|cell| cell.header) {
+            let rule = widths
+                .iter()
+                .map(|&width| "-".repeat(width))
+                .collect::<Vec<_>>()
+                .join("-+-");
+
+            lines.push(rule);
+        }
+    }
+
+    lines
+}
+
+fn spanned_width(widths: &[usize], separator: &str) -> usize {
+    let separators = widths.len().saturating_sub(1) * separator.len();
+    widths.iter().sum::<usize>() + separators
+}
+
+fn pad_to(buffer: &mut String, text: &str, width: usize) {
+    buffer.push_str(text);
+
+    for _ in text.width()..width {
+        buffer.push(' ');
+    }
+}
+
+/// Hard-wraps each line of `text` so that none exceeds `width` columns.
+///
+/// Lines are only broken at spaces, so words longer than the width
+/// (such as URLs) are kept whole on a line of their own. Continuation
+/// lines keep the original line's indentation and `>` quote markers,
+/// so quotes and lists stay readable.
+pub fn wrap_lines(text: &str, width: usize) -> String {
+    let mut output = String::with_capacity(text.len());
+
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+
+        if line.width() <= width {
+            output.push_str(line);
+            continue;
+        }
+
+        let content = line.trim_start_matches([' ', '>']);
+        let indent = &line[..line.len() - content.len()];
+        let indent = if indent.len() * 2 < width { indent } else { "" };
+
+        wrap_line(&mut output, indent, content, width);
+    }
+
+    output
+}
+
+fn wrap_line(output: &mut String, indent: &str, content: &str, width: usize) {
+    let available = width - indent.len();
+    let mut current_width = 0;
+    let mut spaces = 0;
+
+    output.push_str(indent);
+
+    for (index, word) in content.split(' ').enumerate() {
+        // Keep runs of spaces within a line, such as table padding
+        if index > 0 {
+            spaces += 1;
+        }
+
+        if word.is_empty() {
+            continue;
+        }
+
+        let word_width = word.width();
+
+        // Move to a new line if this word doesn't fit on the current one
+        if current_width > 0 && current_width + spaces + word_width > available {
+            output.push('\n');
+            output.push_str(indent);
+            current_width = 0;
+        }
+
+        if current_width > 0 {
+            for _ in 0..spaces {
+                output.push(' ');
+            }
+
+            current_width += spaces;
+        }
+
+        spaces = 0;
+        output.push_str(word);
+        current_width += word_width;
+    }
+}
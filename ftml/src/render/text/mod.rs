@@ -20,14 +20,17 @@
 
 mod context;
 mod elements;
+mod layout;
 
 use self::context::TextContext;
 use self::elements::render_elements;
+use self::layout::wrap_lines;
 use crate::data::{PageCallbacks, PageInfo};
 use crate::render::{Handle, Render};
 use crate::settings::WikitextSettings;
 use crate::tree::{Element, SyntaxTree};
-use std::rc::Rc;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct TextRender;
@@ -38,81 +41,157 @@ impl TextRender {
         &self,
         elements: &[Element],
         page_info: &PageInfo,
-        page_callbacks: Rc<dyn PageCallbacks>,
+        page_callbacks: Arc<dyn PageCallbacks>,
         settings: &WikitextSettings,
     ) -> String {
-        self.render_partial_direct(
+        render_text(
             elements,
             page_info,
-            page_callbacks.clone(),
+            page_callbacks,
             settings,
             &[],
             &[],
+            &[],
+            &[],
+            None,
         )
     }
+}
+
+impl Render for TextRender {
+    type Output = String;
 
-    fn render_partial_direct(
+    #[inline]
+    fn render(
         &self,
-        elements: &[Element],
+        tree: &SyntaxTree,
         page_info: &PageInfo,
-        page_callbacks: Rc<dyn PageCallbacks>,
+        page_callbacks: Arc<dyn PageCallbacks>,
         settings: &WikitextSettings,
-        table_of_contents: &[Element],
-        footnotes: &[Vec<Element>],
     ) -> String {
-        info!(
-            "Rendering text (site {}, page {}, category {})",
-            page_info.site.as_ref(),
-            page_info.page.as_ref(),
-            match &page_info.category {
-                Some(category) => category.as_ref(),
-                None => "_default",
-            },
-        );
-
-        let handle = Handle::new(page_callbacks.clone(), &vec![]);
-
-        let mut ctx = TextContext::new(
+        render_text(
+            &tree.elements,
             page_info,
-            page_callbacks.clone(),
-            &handle,
+            page_callbacks,
             settings,
-            table_of_contents,
-            footnotes,
-        );
-        render_elements(&mut ctx, elements);
-
-        // Remove leading and trailing newlines
-        while ctx.buffer().starts_with('\n') {
-            ctx.buffer().remove(0);
-        }
+            &tree.table_of_contents,
+            &tree.footnotes,
+            &tree.equations,
+            &tree.bibliography,
+            None,
+        )
+    }
+}
 
-        while ctx.buffer().ends_with('\n') {
-            ctx.buffer().pop();
-        }
+/// Renders the "readable plain text" profile, intended for e-mails and notifications.
+///
+/// Unlike [`TextRender`], this lays out tables as aligned columns,
+/// numbers links and lists their destinations at the end, shows
+/// images, math, iframes and included pages, marks quotes with `>`,
+/// and can hard-wrap lines.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ReadableTextRender {
+    /// If set, lines are wrapped so they don't exceed this many columns.
+    pub wrap_width: Option<NonZeroUsize>,
+}
 
-        ctx.into()
+impl ReadableTextRender {
+    #[inline]
+    pub fn new(wrap_width: Option<NonZeroUsize>) -> Self {
+        ReadableTextRender { wrap_width }
     }
 }
 
-impl Render for TextRender {
+impl Render for ReadableTextRender {
     type Output = String;
 
-    #[inline]
     fn render(
         &self,
         tree: &SyntaxTree,
         page_info: &PageInfo,
-        page_callbacks: Rc<dyn PageCallbacks>,
+        page_callbacks: Arc<dyn PageCallbacks>,
         settings: &WikitextSettings,
     ) -> String {
-        self.render_partial_direct(
+        render_text(
             &tree.elements,
             page_info,
-            page_callbacks.clone(),
+            page_callbacks,
             settings,
             &tree.table_of_contents,
             &tree.footnotes,
+            &tree.equations,
+            &tree.bibliography,
+            Some(self),
         )
     }
 }
+
+#[allow(clippy::too_many_arguments)]
+fn render_text(
+    elements: &[Element],
+    page_info: &PageInfo,
+    page_callbacks: Arc<dyn PageCallbacks>,
+    settings: &WikitextSettings,
+    table_of_contents: &[Element],
+    footnotes: &[Vec<Element>],
+    equations: &[(Option<String>, String)],
+    bibliography: &[(String, Vec<Element>)],
+    readable: Option<&ReadableTextRender>,
+) -> String {
+    info!(
+        "Rendering text (site {}, page {}, category {})",
+        page_info.site.as_ref(),
+        page_info.page.as_ref(),
+        match &page_info.category {
+            Some(category) => category.as_ref(),
+            None => "_default",
+        },
+    );
+
+    let handle = Handle::new(
+        page_callbacks.clone(),
+        &page_info.language,
+        &vec![],
+        &[],
+        &[],
+    );
+
+    let mut ctx = TextContext::new(
+        page_info,
+        page_callbacks,
+        &handle,
+        settings,
+        table_of_contents,
+        footnotes,
+        equations,
+        bibliography,
+        readable.is_some(),
+    );
+    render_elements(&mut ctx, elements);
+
+    // Remove leading and trailing newlines
+    while ctx.buffer().starts_with('\n') {
+        ctx.buffer().remove(0);
+    }
+
+    while ctx.buffer().ends_with('\n') {
+        ctx.buffer().pop();
+    }
+
+    if let Some(width) = readable.and_then(|render| render.wrap_width) {
+        let wrapped = wrap_lines(ctx.buffer(), width.get());
+        *ctx.buffer() = wrapped;
+    }
+
+    // List numbered link destinations, which are never wrapped
+    if !ctx.links().is_empty() {
+        let links = ctx.links().to_vec();
+
+        ctx.buffer().push('\n');
+        for (index, url) in links.iter().enumerate() {
+            str_write!(ctx.buffer(), "\n<{}> {url}", index + 1);
+        }
+    }
+
+    ctx.into()
+}
@@ -0,0 +1,184 @@
+/*
+ * settings/embed.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+
+lazy_static! {
+    pub static ref EMPTY_EMBED: EmbedSettings = EmbedSettings { providers: vec![] };
+    pub static ref DEFAULT_EMBED: EmbedSettings = {
+        EmbedSettings {
+            providers: vec![
+                EmbedProvider {
+                    name: cow!("youtube"),
+                    media: EmbedMedia::Video,
+                    iframe: true,
+                    patterns: vec![
+                        cow!("https://www.youtube.com/watch?v=$$"),
+                        cow!("https://youtube.com/watch?v=$$"),
+                        cow!("https://youtu.be/$$"),
+                    ],
+                    embed_url: cow!("https://www.youtube-nocookie.com/embed/$$"),
+                    link_url: cow!("https://www.youtube.com/watch?v=$$"),
+                },
+                EmbedProvider {
+                    name: cow!("vimeo"),
+                    media: EmbedMedia::Video,
+                    iframe: true,
+                    patterns: vec![cow!("https://vimeo.com/$$")],
+                    embed_url: cow!("https://player.vimeo.com/video/$$"),
+                    link_url: cow!("https://vimeo.com/$$"),
+                },
+                EmbedProvider {
+                    name: cow!("soundcloud"),
+                    media: EmbedMedia::Audio,
+                    iframe: true,
+                    patterns: vec![cow!("https://soundcloud.com/$$")],
+                    embed_url: cow!(
+                        "https://w.soundcloud.com/player/?url=https%3A//soundcloud.com/$$"
+                    ),
+                    link_url: cow!("https://soundcloud.com/$$"),
+                },
+            ],
+        }
+    };
+}
+
+/// The kind of media served by an embed provider.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmbedMedia {
+    Video,
+    Audio,
+}
+
+impl EmbedMedia {
+    pub fn name(self) -> &'static str {
+        match self {
+            EmbedMedia::Video => "video",
+            EmbedMedia::Audio => "audio",
+        }
+    }
+}
+
+/// A site or server whose media may be embedded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct EmbedProvider {
+    /// The name of this provider, such as `youtube`.
+    ///
+    /// This is also accepted in place of a URL, as in `[[embed youtube video="..."]]`.
+    pub name: Cow<'static, str>,
+
+    /// What kind of media this provider serves.
+    ///
+    /// `[[embedvideo]]` and `[[embedaudio]]` only accept providers of that kind.
+    pub media: EmbedMedia,
+
+    /// Whether the media is shown in an iframe, or in a `<video>` or `<audio>` element.
+    ///
+    /// The latter is intended for self-hosted media files.
+    pub iframe: bool,
+
+    /// The URLs accepted by this provider.
+    ///
+    /// The media ID is captured in place of `$$`. It ends at the first `?`, `&` or `#`,
+    /// and may only contain letters, numbers, and `-_./%`.
+    pub patterns: Vec<Cow<'static, str>>,
+
+    /// The URL of the embedded media, with `$$` replaced by the media ID.
+    pub embed_url: Cow<'static, str>,
+
+    /// The URL linking to the media, with `$$` replaced by the media ID.
+    ///
+    /// This is used when the embed cannot be shown, such as in text output.
+    pub link_url: Cow<'static, str>,
+}
+
+impl EmbedProvider {
+    /// Gets the media ID from the URL, if this provider accepts it.
+    pub fn match_url<'a>(&self, url: &'a str) -> Option<&'a str> {
+        self.patterns.iter().find_map(|pattern| {
+            let (prefix, suffix) = pattern.split_once("$$")?;
+            let rest = url.strip_prefix(prefix)?;
+            let end = rest.find(['?', '&', '#']).unwrap_or(rest.len());
+            let media_id = rest[..end].strip_suffix(suffix)?;
+
+            if is_valid_media_id(media_id) {
+                Some(media_id)
+            } else {
+                None
+            }
+        })
+    }
+
+    #[inline]
+    pub fn build_embed_url(&self, media_id: &str) -> String {
+        self.embed_url.replace("$$", media_id)
+    }
+
+    #[inline]
+    pub fn build_link_url(&self, media_id: &str) -> String {
+        self.link_url.replace("$$", media_id)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct EmbedSettings {
+    pub providers: Vec<EmbedProvider>,
+}
+
+impl EmbedSettings {
+    #[inline]
+    pub fn new() -> Self {
+        EmbedSettings::default()
+    }
+
+    /// Gets the provider with the given name, case-insensitively.
+    pub fn get(&self, name: &str) -> Option<&EmbedProvider> {
+        self.providers
+            .iter()
+            .find(|provider| provider.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Finds the first provider accepting this URL, with the media ID it gives.
+    ///
+    /// If `media` is given, only providers serving that kind of media are checked.
+    pub fn find<'a>(
+        &self,
+        url: &'a str,
+        media: Option<EmbedMedia>,
+    ) -> Option<(&EmbedProvider, &'a str)> {
+        self.providers
+            .iter()
+            .filter(|provider| media.is_none() || media == Some(provider.media))
+            .find_map(|provider| {
+                provider.match_url(url).map(|media_id| (provider, media_id))
+            })
+    }
+}
+
+/// Checks that a media ID is safe to substitute into an embed URL.
+pub(crate) fn is_valid_media_id(media_id: &str) -> bool {
+    !media_id.is_empty()
+        && !media_id.split('/').any(|part| part == "..")
+        && media_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./%".contains(c))
+}
@@ -18,8 +18,13 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+mod embed;
 mod interwiki;
 
+pub(crate) use self::embed::is_valid_media_id;
+pub use self::embed::{
+    EmbedMedia, EmbedProvider, EmbedSettings, DEFAULT_EMBED, EMPTY_EMBED,
+};
 pub use self::interwiki::{InterwikiSettings, DEFAULT_INTERWIKI, EMPTY_INTERWIKI};
 
 /// Settings to tweak behavior in the ftml parser and renderer.
@@ -91,6 +96,12 @@ pub struct WikitextSettings {
     /// * By convention, prefixes should be all-lowercase.
     pub interwiki: InterwikiSettings,
 
+    /// What sites and servers media may be embedded from.
+    ///
+    /// Used by `[[embed]]`, `[[embedvideo]]`, and `[[embedaudio]]`. URLs not
+    /// accepted by any provider are rejected with a warning.
+    pub embed: EmbedSettings,
+
     /// Used for finding page backlinks and for listing [[code]] and [[html]] blocks.
     /// Iftags conditions are always true.
     /// For ifexpr and if both sides are evaluated (and stored and rendered) no matter the values.
@@ -99,11 +110,41 @@ pub struct WikitextSettings {
     /// Used for disabling modules.
     /// This is also used in "system mode" (when we just want to collect code blocks and html blocks).
     pub no_modules: bool,
+
+    /// Whether modules are rendered later by the host, rather than during rendering.
+    ///
+    /// If set, the HTML renderer emits a placeholder for each module instead of
+    /// calling `PageCallbacks::render_module`, and lists every module invocation
+    /// in its output. The rendered modules can then be substituted into the body
+    /// using `fill_placeholders`.
+    pub defer_modules: bool,
+
+    /// How the IDs of headings with table of contents entries are chosen.
+    ///
+    /// See [`HeadingAnchors`].
+    pub heading_anchors: HeadingAnchors,
+
+    /// A prefix added to every ID generated during rendering.
+    ///
+    /// This applies to footnotes, the table of contents, headings and equations,
+    /// along with the links to them. It allows several renders, for instance each
+    /// post in a forum thread, to be placed in the same document without their
+    /// IDs colliding. Typically this is something unique like the post's ID,
+    /// prefixed so that it begins with a letter, such as `post-123`.
+    pub id_namespace: Option<String>,
+
+    /// Whether the HTML renderer generates metadata for link previews.
+    ///
+    /// This is the OpenGraph and Twitter card `<meta>` tags, and JSON-LD.
+    /// Generating it renders an excerpt of the page, so it is only enabled
+    /// by default for full pages.
+    pub preview_metadata: bool,
 }
 
 impl WikitextSettings {
     pub fn from_mode(mode: WikitextMode) -> Self {
         let interwiki = DEFAULT_INTERWIKI.clone();
+        let embed = DEFAULT_EMBED.clone();
 
         match mode {
             WikitextMode::Page => WikitextSettings {
@@ -115,8 +156,13 @@ impl WikitextSettings {
                 isolate_user_ids: true,
                 allow_local_paths: true,
                 interwiki,
+                embed,
                 no_conditionals: false,
                 no_modules: false,
+                defer_modules: false,
+                heading_anchors: HeadingAnchors::Index,
+                id_namespace: None,
+                preview_metadata: true,
             },
             WikitextMode::System => WikitextSettings {
                 mode,
@@ -127,8 +173,13 @@ impl WikitextSettings {
                 isolate_user_ids: true,
                 allow_local_paths: true,
                 interwiki,
+                embed,
                 no_conditionals: true,
                 no_modules: true,
+                defer_modules: false,
+                heading_anchors: HeadingAnchors::Index,
+                id_namespace: None,
+                preview_metadata: false,
             },
             WikitextMode::SystemWithModules => WikitextSettings {
                 mode,
@@ -139,8 +190,13 @@ impl WikitextSettings {
                 isolate_user_ids: true,
                 allow_local_paths: true,
                 interwiki,
+                embed,
                 no_conditionals: true,
                 no_modules: false,
+                defer_modules: false,
+                heading_anchors: HeadingAnchors::Index,
+                id_namespace: None,
+                preview_metadata: false,
             },
             WikitextMode::Draft => WikitextSettings {
                 mode,
@@ -151,8 +207,13 @@ impl WikitextSettings {
                 isolate_user_ids: true,
                 allow_local_paths: true,
                 interwiki,
+                embed,
                 no_conditionals: false,
                 no_modules: false,
+                defer_modules: false,
+                heading_anchors: HeadingAnchors::Index,
+                id_namespace: None,
+                preview_metadata: false,
             },
             WikitextMode::ForumPost | WikitextMode::DirectMessage => WikitextSettings {
                 mode,
@@ -163,8 +224,13 @@ impl WikitextSettings {
                 isolate_user_ids: true,
                 allow_local_paths: false,
                 interwiki,
+                embed,
                 no_conditionals: false,
                 no_modules: false,
+                defer_modules: false,
+                heading_anchors: HeadingAnchors::Index,
+                id_namespace: None,
+                preview_metadata: false,
             },
             WikitextMode::Inline => WikitextSettings {
                 mode,
@@ -175,8 +241,13 @@ impl WikitextSettings {
                 isolate_user_ids: true,
                 allow_local_paths: true,
                 interwiki,
+                embed,
                 no_conditionals: false,
                 no_modules: false,
+                defer_modules: false,
+                heading_anchors: HeadingAnchors::Index,
+                id_namespace: None,
+                preview_metadata: false,
             },
             WikitextMode::List => WikitextSettings {
                 mode,
@@ -187,8 +258,13 @@ impl WikitextSettings {
                 isolate_user_ids: true,
                 allow_local_paths: true,
                 interwiki,
+                embed,
                 no_conditionals: false,
                 no_modules: false,
+                defer_modules: false,
+                heading_anchors: HeadingAnchors::Index,
+                id_namespace: None,
+                preview_metadata: false,
             },
         }
     }
@@ -225,3 +301,33 @@ pub enum WikitextMode {
     System,
     SystemWithModules,
 }
+
+/// How the IDs of headings are chosen, for use as link anchors.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HeadingAnchors {
+    /// IDs from the heading's position, such as `toc0`, `toc1`, etc.
+    ///
+    /// These are only added if the page has a table of contents.
+    Index,
+
+    /// IDs from the heading's text, such as `first-heading`.
+    ///
+    /// Cyrillic is transliterated, and repeated headings get a numeric suffix.
+    /// Unlike [`HeadingAnchors::Index`], these are always added.
+    Slug,
+
+    /// Like [`HeadingAnchors::Slug`], but the positional ID is also added
+    /// to an empty anchor within the heading, so older links keep working.
+    SlugAndIndex,
+}
+
+impl HeadingAnchors {
+    #[inline]
+    pub fn use_slugs(self) -> bool {
+        match self {
+            HeadingAnchors::Index => false,
+            HeadingAnchors::Slug | HeadingAnchors::SlugAndIndex => true,
+        }
+    }
+}
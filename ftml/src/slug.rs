@@ -0,0 +1,125 @@
+/*
+ * slug.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Utility to produce URL-friendly identifiers from text.
+//!
+//! Slugs are lowercase, with quotes removed and runs of anything else
+//! besides letters and digits replaced by a single `-`. Cyrillic letters are transliterated
+//! into Latin, so Russian headings produce readable anchors.
+
+/// Produces a slug from the given text.
+///
+/// If nothing in the text is usable, then the slug is empty.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    let mut pending_dash = false;
+
+    for ch in text.chars().flat_map(char::to_lowercase) {
+        let part = match transliterate(ch) {
+            Some(part) => part,
+            None if ch.is_alphanumeric() => {
+                if pending_dash && !slug.is_empty() {
+                    slug.push('-');
+                }
+
+                pending_dash = false;
+                slug.push(ch);
+                continue;
+            }
+            None if is_quote(ch) => continue,
+            None => {
+                pending_dash = true;
+                continue;
+            }
+        };
+
+        // Hard and soft signs have no Latin equivalent
+        if part.is_empty() {
+            continue;
+        }
+
+        if pending_dash && !slug.is_empty() {
+            slug.push('-');
+        }
+
+        pending_dash = false;
+        slug.push_str(part);
+    }
+
+    slug
+}
+
+/// Whether this is an apostrophe or quote, which are dropped from slugs.
+///
+/// This way words like "don't" remain together.
+#[inline]
+fn is_quote(ch: char) -> bool {
+    matches!(ch, '\'' | '"' | '‘' | '’' | '“' | '”' | '«' | '»' | '`')
+}
+
+/// Transliterates a lowercase Cyrillic letter.
+///
+/// This follows the common Russian passport scheme, along with
+/// the letters needed for Ukrainian and Belarusian.
+fn transliterate(ch: char) -> Option<&'static str> {
+    let latin = match ch {
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' => "g",
+        'д' => "d",
+        'е' => "e",
+        'ё' => "e",
+        'ж' => "zh",
+        'з' => "z",
+        'и' => "i",
+        'й' => "i",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' => "",
+        'ы' => "y",
+        'ь' => "",
+        'э' => "e",
+        'ю' => "iu",
+        'я' => "ia",
+        'і' => "i",
+        'ї' => "i",
+        'є' => "ie",
+        'ґ' => "g",
+        'ў' => "u",
+        _ => return None,
+    };
+
+    Some(latin)
+}
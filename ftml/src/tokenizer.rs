@@ -60,3 +60,16 @@ pub fn tokenize(text: &str) -> Tokenization {
 
     Tokenization { tokens, full_text }
 }
+
+/// Like [`tokenize`], but using the original pest-based lexer.
+///
+/// This is slower, and only kept as a reference implementation
+/// to test and benchmark the hand-written lexer against.
+pub fn tokenize_reference(text: &str) -> Tokenization<'_> {
+    info!("Running reference lexer on text to produce tokens");
+
+    let tokens = Token::extract_all_pest(text);
+    let full_text = FullText::new(text);
+
+    Tokenization { tokens, full_text }
+}
@@ -94,6 +94,13 @@ impl<'t> AttributeMap<'t> {
         self.inner.remove(attribute)
     }
 
+    /// Removes all attributes which are not in the given list.
+    ///
+    /// Used by elements which only accept a specific set of attributes.
+    pub fn retain_only(&mut self, allowed: &[&str]) {
+        self.inner.retain(|key, _| allowed.contains(&key.as_ref()));
+    }
+
     #[inline]
     pub fn get(&self) -> &BTreeMap<Cow<'t, str>, Cow<'t, str>> {
         &self.inner
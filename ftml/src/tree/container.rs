@@ -58,6 +58,11 @@ impl<'t> Container<'t> {
         &self.elements
     }
 
+    #[inline]
+    pub fn elements_mut(&mut self) -> &mut [Element<'t>] {
+        &mut self.elements
+    }
+
     #[inline]
     pub fn attributes(&self) -> &AttributeMap<'t> {
         &self.attributes
@@ -106,6 +111,8 @@ pub enum ContainerType {
     Deletion,
     Hidden,
     Invisible,
+    HiddenBlock,
+    InvisibleBlock,
     Size,
     Ruby,
     RubyText,
@@ -140,6 +147,8 @@ impl ContainerType {
             ContainerType::Deletion => HtmlTag::new("del"),
             ContainerType::Hidden => HtmlTag::with_class("span", "wj-hidden"),
             ContainerType::Invisible => HtmlTag::with_class("span", "wj-invisible"),
+            ContainerType::HiddenBlock => HtmlTag::with_class("div", "wj-hidden"),
+            ContainerType::InvisibleBlock => HtmlTag::with_class("div", "wj-invisible"),
             ContainerType::Size => HtmlTag::new("span"),
             ContainerType::Ruby => HtmlTag::new("ruby"),
             ContainerType::RubyText => HtmlTag::new("rt"),
@@ -172,6 +181,8 @@ impl ContainerType {
             ContainerType::Deletion => true,
             ContainerType::Hidden => true,
             ContainerType::Invisible => true,
+            ContainerType::HiddenBlock => false,
+            ContainerType::InvisibleBlock => false,
             ContainerType::Size => true,
             ContainerType::Ruby => true,
             ContainerType::RubyText => true,
@@ -23,12 +23,13 @@ use std::num::NonZeroU32;
 
 use ref_map::*;
 
-use crate::data::PageRef;
+use crate::data::{FileRef, PageRef};
 use crate::tree::clone::*;
 use crate::tree::{
     Alignment, AnchorTarget, AttributeMap, ClearFloat, Container, Date,
-    DefinitionListItem, FloatAlignment, FormInput, ImageSource, LinkLabel, LinkLocation,
-    LinkType, ListItem, ListType, Module, PartialElement, Tab, Table, VariableMap,
+    DefinitionListItem, FloatAlignment, FormInput, GalleryItem, GallerySize, ImageSource,
+    LinkLabel, LinkLocation, LinkType, ListItem, ListType, Module, NoteType,
+    PartialElement, Tab, Table, VariableMap,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -121,6 +122,14 @@ pub enum Element<'t> {
         target: Option<AnchorTarget>,
     },
 
+    /// An element linking to a file attached to a page.
+    ///
+    /// If the "label" field is absent, the file name is shown instead.
+    File {
+        file: FileRef<'t>,
+        label: Option<Cow<'t, str>>,
+    },
+
     /// An element representing an image and its associated metadata.
     ///
     /// The "source" field is the link to the image itself.
@@ -134,6 +143,27 @@ pub enum Element<'t> {
         attributes: AttributeMap<'t>,
     },
 
+    /// Media embedded from another site, such as a video.
+    ///
+    /// The "provider" field names an `EmbedProvider` in the settings,
+    /// which builds the embed from the "media_id" field.
+    Embed {
+        provider: Cow<'t, str>,
+        media_id: Cow<'t, str>,
+        width: Option<u32>,
+        height: Option<u32>,
+    },
+
+    /// A gallery of images, shown as a grid of thumbnails.
+    ///
+    /// If `viewer` is set, each thumbnail links to the full image.
+    Gallery {
+        items: Vec<GalleryItem<'t>>,
+        size: GallerySize,
+        viewer: bool,
+        attributes: AttributeMap<'t>,
+    },
+
     /// An ordered or unordered list.
     List {
         #[serde(rename = "type")]
@@ -162,6 +192,18 @@ pub enum Element<'t> {
         text_align: Option<Alignment>,
     },
 
+    /// A note, or admonition, set apart from the surrounding text.
+    ///
+    /// If the title is `None`, then a localized default for the note type is used.
+    /// An empty title means no heading is shown.
+    #[serde(rename_all = "kebab-case")]
+    Note {
+        note_type: NoteType,
+        title: Option<Cow<'t, str>>,
+        elements: Vec<Element<'t>>,
+        attributes: AttributeMap<'t>,
+    },
+
     /// A table of contents block.
     ///
     /// This contains links to sub-headings on the page.
@@ -188,6 +230,19 @@ pub enum Element<'t> {
         hide: bool,
     },
 
+    /// A bibliography citation, from `((bibcite label))`.
+    ///
+    /// The number is not saved, since it depends on the other citations.
+    /// It is the index of the entry in the `bibliography` list in the syntax tree.
+    BibliographyCite(Cow<'t, str>),
+
+    /// A bibliography block, containing all the bibliography entries from throughout the page.
+    ///
+    /// The entries themselves are in the syntax tree, not the element.
+    BibliographyBlock {
+        title: Option<Cow<'t, str>>,
+    },
+
     /// A user block, linking to their information and possibly showing their avatar.
     #[serde(rename_all = "kebab-case")]
     User {
@@ -323,13 +378,19 @@ impl Element<'_> {
             Element::Anchor { .. } => "Anchor",
             Element::AnchorName(_) => "AnchorName",
             Element::Link { .. } => "Link",
+            Element::File { .. } => "File",
             Element::Image { .. } => "Image",
+            Element::Embed { .. } => "Embed",
+            Element::Gallery { .. } => "Gallery",
             Element::List { .. } => "List",
             Element::DefinitionList(_) => "DefinitionList",
             Element::Collapsible { .. } => "Collapsible",
+            Element::Note { .. } => "Note",
             Element::TableOfContents { .. } => "TableOfContents",
             Element::Footnote => "Footnote",
             Element::FootnoteBlock { .. } => "FootnoteBlock",
+            Element::BibliographyCite(_) => "BibliographyCite",
+            Element::BibliographyBlock { .. } => "BibliographyBlock",
             Element::User { .. } => "User",
             Element::Date { .. } => "Date",
             Element::Color { .. } => "Color",
@@ -375,13 +436,19 @@ impl Element<'_> {
             Element::Anchor { .. } | Element::AnchorName(_) | Element::Link { .. } => {
                 true
             }
+            Element::File { .. } => true,
             Element::Image { .. } => true,
+            Element::Embed { .. } => false,
+            Element::Gallery { .. } => false,
             Element::List { .. } => false,
             Element::DefinitionList(_) => false,
             Element::Collapsible { .. } => false,
+            Element::Note { .. } => false,
             Element::TableOfContents { .. } => false,
             Element::Footnote => true,
             Element::FootnoteBlock { .. } => false,
+            Element::BibliographyCite(_) => true,
+            Element::BibliographyBlock { .. } => false,
             Element::User { .. } => true,
             Element::Date { .. } => true,
             Element::Color { .. } => true,
@@ -452,6 +519,10 @@ impl Element<'_> {
                 attributes: attributes.to_owned(),
                 items: list_items_to_owned(items),
             },
+            Element::File { file, label } => Element::File {
+                file: file.to_owned(),
+                label: option_string_to_owned(label),
+            },
             Element::Image {
                 source,
                 link,
@@ -465,6 +536,28 @@ impl Element<'_> {
                 alignment: *alignment,
                 attributes: attributes.to_owned(),
             },
+            Element::Embed {
+                provider,
+                media_id,
+                width,
+                height,
+            } => Element::Embed {
+                provider: string_to_owned(provider),
+                media_id: string_to_owned(media_id),
+                width: *width,
+                height: *height,
+            },
+            Element::Gallery {
+                items,
+                size,
+                viewer,
+                attributes,
+            } => Element::Gallery {
+                items: items.iter().map(|item| item.to_owned()).collect(),
+                size: *size,
+                viewer: *viewer,
+                attributes: attributes.to_owned(),
+            },
             Element::DefinitionList(items) => Element::DefinitionList(
                 items.iter().map(|item| item.to_owned()).collect(),
             ),
@@ -487,6 +580,17 @@ impl Element<'_> {
                 show_bottom: *show_bottom,
                 text_align: *text_align,
             },
+            Element::Note {
+                note_type,
+                title,
+                elements,
+                attributes,
+            } => Element::Note {
+                note_type: *note_type,
+                title: option_string_to_owned(title),
+                elements: elements_to_owned(elements),
+                attributes: attributes.to_owned(),
+            },
             Element::TableOfContents { align, attributes } => Element::TableOfContents {
                 align: *align,
                 attributes: attributes.to_owned(),
@@ -496,6 +600,12 @@ impl Element<'_> {
                 title: option_string_to_owned(title),
                 hide: *hide,
             },
+            Element::BibliographyCite(label) => {
+                Element::BibliographyCite(string_to_owned(label))
+            }
+            Element::BibliographyBlock { title } => Element::BibliographyBlock {
+                title: option_string_to_owned(title),
+            },
             Element::User { name, show_avatar } => Element::User {
                 name: string_to_owned(name),
                 show_avatar: *show_avatar,
@@ -1,5 +1,7 @@
+use super::clone::{option_string_to_owned, string_to_owned};
 use super::AttributeMap;
 use std::borrow::Cow;
+use std::convert::TryFrom;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
@@ -17,16 +19,153 @@ impl Form<'_> {
     }
 }
 
+/// A single control within a form.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-#[serde(rename_all = "kebab-case")]
-pub struct FormInput<'t> {
-    pub attributes: AttributeMap<'t>,
+#[serde(rename_all = "kebab-case", tag = "type", content = "data")]
+pub enum FormInput<'t> {
+    /// A generic input, from `[[input]]`.
+    ///
+    /// All safe attributes are passed through unchanged.
+    Input { attributes: AttributeMap<'t> },
+
+    /// A checkbox, which is checked if `checked` is true.
+    Checkbox {
+        label: Option<Cow<'t, str>>,
+        checked: bool,
+        attributes: AttributeMap<'t>,
+    },
+
+    /// One option of a radio group.
+    ///
+    /// All radio buttons sharing the same `group` are mutually exclusive.
+    Radio {
+        group: Cow<'t, str>,
+        label: Option<Cow<'t, str>>,
+        checked: bool,
+        attributes: AttributeMap<'t>,
+    },
+
+    /// A dropdown list of options.
+    Select {
+        label: Option<Cow<'t, str>>,
+        options: Vec<SelectOption<'t>>,
+        attributes: AttributeMap<'t>,
+    },
+
+    /// A multi-line text field, with its initial contents.
+    TextArea {
+        label: Option<Cow<'t, str>>,
+        contents: Cow<'t, str>,
+        attributes: AttributeMap<'t>,
+    },
+
+    /// A button which submits or resets the form.
+    Button {
+        button_type: ButtonType,
+        label: Option<Cow<'t, str>>,
+        attributes: AttributeMap<'t>,
+    },
 }
 
 impl FormInput<'_> {
     pub fn to_owned(&self) -> FormInput<'static> {
-        FormInput {
-            attributes: self.attributes.to_owned(),
+        match self {
+            FormInput::Input { attributes } => FormInput::Input {
+                attributes: attributes.to_owned(),
+            },
+            FormInput::Checkbox {
+                label,
+                checked,
+                attributes,
+            } => FormInput::Checkbox {
+                label: option_string_to_owned(label),
+                checked: *checked,
+                attributes: attributes.to_owned(),
+            },
+            FormInput::Radio {
+                group,
+                label,
+                checked,
+                attributes,
+            } => FormInput::Radio {
+                group: string_to_owned(group),
+                label: option_string_to_owned(label),
+                checked: *checked,
+                attributes: attributes.to_owned(),
+            },
+            FormInput::Select {
+                label,
+                options,
+                attributes,
+            } => FormInput::Select {
+                label: option_string_to_owned(label),
+                options: options.iter().map(|option| option.to_owned()).collect(),
+                attributes: attributes.to_owned(),
+            },
+            FormInput::TextArea {
+                label,
+                contents,
+                attributes,
+            } => FormInput::TextArea {
+                label: option_string_to_owned(label),
+                contents: string_to_owned(contents),
+                attributes: attributes.to_owned(),
+            },
+            FormInput::Button {
+                button_type,
+                label,
+                attributes,
+            } => FormInput::Button {
+                button_type: *button_type,
+                label: option_string_to_owned(label),
+                attributes: attributes.to_owned(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SelectOption<'t> {
+    pub value: Cow<'t, str>,
+    pub label: Cow<'t, str>,
+    pub selected: bool,
+}
+
+impl SelectOption<'_> {
+    pub fn to_owned(&self) -> SelectOption<'static> {
+        SelectOption {
+            value: string_to_owned(&self.value),
+            label: string_to_owned(&self.label),
+            selected: self.selected,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ButtonType {
+    Submit,
+    Reset,
+}
+
+impl ButtonType {
+    pub fn name(self) -> &'static str {
+        match self {
+            ButtonType::Submit => "submit",
+            ButtonType::Reset => "reset",
+        }
+    }
+}
+
+impl TryFrom<&'_ str> for ButtonType {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "submit" => Ok(ButtonType::Submit),
+            "reset" => Ok(ButtonType::Reset),
+            _ => Err(()),
         }
     }
 }
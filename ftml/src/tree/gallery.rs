@@ -0,0 +1,83 @@
+/*
+ * tree/gallery.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::clone::option_string_to_owned;
+use super::ImageSource;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GalleryItem<'t> {
+    pub source: ImageSource<'t>,
+    pub caption: Option<Cow<'t, str>>,
+}
+
+impl GalleryItem<'_> {
+    pub fn to_owned(&self) -> GalleryItem<'static> {
+        GalleryItem {
+            source: self.source.to_owned(),
+            caption: option_string_to_owned(&self.caption),
+        }
+    }
+}
+
+/// The size of the thumbnails shown in a gallery.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum GallerySize {
+    Square,
+    #[default]
+    Thumbnail,
+    Small,
+    Medium,
+    Large,
+
+    /// Show the full image, without requesting a thumbnail.
+    Original,
+}
+
+impl GallerySize {
+    pub fn name(self) -> &'static str {
+        match self {
+            GallerySize::Square => "square",
+            GallerySize::Thumbnail => "thumbnail",
+            GallerySize::Small => "small",
+            GallerySize::Medium => "medium",
+            GallerySize::Large => "large",
+            GallerySize::Original => "original",
+        }
+    }
+}
+
+impl TryFrom<&'_ str> for GallerySize {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "square" => Ok(GallerySize::Square),
+            "thumbnail" => Ok(GallerySize::Thumbnail),
+            "small" => Ok(GallerySize::Small),
+            "medium" => Ok(GallerySize::Medium),
+            "large" => Ok(GallerySize::Large),
+            "original" => Ok(GallerySize::Original),
+            _ => Err(()),
+        }
+    }
+}
@@ -19,6 +19,7 @@
  */
 
 use super::clone::string_to_owned;
+use crate::data::{FileRef, PageInfo};
 use crate::url::{is_known_scheme, validate_href};
 use std::borrow::Cow;
 use strum_macros::IntoStaticStr;
@@ -73,6 +74,27 @@ impl<'t> ImageSource<'t> {
         self.into()
     }
 
+    /// Gets the file name of the image, or the last part of its URL.
+    pub fn file_name(&self) -> &str {
+        match self {
+            ImageSource::Url(url) => url.rsplit('/').next().unwrap_or(url),
+            ImageSource::File1 { file } | ImageSource::File2 { file, .. } => file,
+        }
+    }
+
+    /// Gets the attached file this image refers to, if it isn't a URL.
+    pub fn file_ref(&self, info: &PageInfo) -> Option<FileRef<'t>> {
+        match self {
+            ImageSource::Url(_) => None,
+            ImageSource::File1 { file } => {
+                Some(FileRef::new(info.full_name(), file.clone()))
+            }
+            ImageSource::File2 { page, file } => {
+                Some(FileRef::new(page.clone(), file.clone()))
+            }
+        }
+    }
+
     pub fn to_owned(&self) -> ImageSource<'static> {
         match self {
             ImageSource::Url(url) => ImageSource::Url(string_to_owned(url)),
@@ -23,7 +23,7 @@ use crate::data::{PageCallbacks, PageRef};
 use crate::settings::WikitextSettings;
 use crate::url::is_url;
 use std::borrow::Cow;
-use std::rc::Rc;
+use std::sync::Arc;
 use strum_macros::EnumIter;
 
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
@@ -40,7 +40,7 @@ impl<'a> LinkLocation<'a> {
     pub fn parse_interwiki(
         link: Cow<'a, str>,
         settings: &WikitextSettings,
-        page_callbacks: Rc<dyn PageCallbacks>,
+        page_callbacks: Arc<dyn PageCallbacks>,
     ) -> Option<(Self, LinkType)> {
         // Handle interwiki (starts with "!", like "!wp:Apple")
         match link.as_ref().strip_prefix('!') {
@@ -59,7 +59,7 @@ impl<'a> LinkLocation<'a> {
         }
     }
 
-    pub fn parse(link: Cow<'a, str>, page_callbacks: Rc<dyn PageCallbacks>) -> Self {
+    pub fn parse(link: Cow<'a, str>, page_callbacks: Arc<dyn PageCallbacks>) -> Self {
         let mut link_str = link.to_string();
 
         // Check for direct URLs or anchor links
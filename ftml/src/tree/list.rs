@@ -21,6 +21,7 @@
 use super::attribute::AttributeMap;
 use super::clone::elements_to_owned;
 use super::Element;
+use std::convert::TryFrom;
 use strum_macros::IntoStaticStr;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -81,7 +82,7 @@ pub enum ListType {
     /// Numbered lists, or "ordered lists" in HTML.
     ///
     /// Corresponds to the tag `<ol>`.
-    Numbered,
+    Numbered(NumberedList),
 
     /// Generic list, which does not have a preferred list type.
     ///
@@ -101,7 +102,197 @@ impl ListType {
     pub fn html_tag(self) -> &'static str {
         match self {
             ListType::Bullet | ListType::Generic => "ul",
-            ListType::Numbered => "ol",
+            ListType::Numbered(_) => "ol",
+        }
+    }
+}
+
+/// How the items in a numbered list are numbered.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct NumberedList {
+    /// The number of the first item, if not the default.
+    ///
+    /// By default, lists start at 1, or count down to 1 if reversed.
+    pub start: Option<i32>,
+
+    /// Which kind of numbers the items are labelled with.
+    pub style: NumberStyle,
+
+    /// Whether the items count down instead of up.
+    pub reversed: bool,
+
+    /// Whether this list continues the numbering of the previous numbered list.
+    ///
+    /// The parser resolves this into a `start` value, this is kept as a record.
+    pub continued: bool,
+}
+
+impl NumberedList {
+    /// Gets the number of the first item in the list.
+    pub fn first_number(self, items: &[ListItem]) -> i64 {
+        match self.start {
+            Some(start) => i64::from(start),
+            None if self.reversed => count_numbered_items(items) as i64,
+            None => 1,
+        }
+    }
+
+    /// Gets the number which would come after the last item in the list.
+    pub fn next_number(self, items: &[ListItem]) -> i64 {
+        let count = count_numbered_items(items) as i64;
+        let first = self.first_number(items);
+
+        if self.reversed {
+            first - count
+        } else {
+            first + count
+        }
+    }
+}
+
+/// Counts the items in a list which are given a number.
+///
+/// This excludes sub-lists, and items which only contain a sub-list.
+fn count_numbered_items(items: &[ListItem]) -> usize {
+    items
+        .iter()
+        .filter(|item| matches!(item, ListItem::Elements { hidden: false, .. }))
+        .count()
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum NumberStyle {
+    /// Decimal numbers, like 1, 2, 3.
+    #[default]
+    Decimal,
+
+    /// Lowercase letters, like a, b, c.
+    LowerAlpha,
+
+    /// Uppercase letters, like A, B, C.
+    UpperAlpha,
+
+    /// Lowercase Roman numerals, like i, ii, iii.
+    LowerRoman,
+
+    /// Uppercase Roman numerals, like I, II, III.
+    UpperRoman,
+}
+
+impl NumberStyle {
+    pub fn name(self) -> &'static str {
+        match self {
+            NumberStyle::Decimal => "decimal",
+            NumberStyle::LowerAlpha => "lower-alpha",
+            NumberStyle::UpperAlpha => "upper-alpha",
+            NumberStyle::LowerRoman => "lower-roman",
+            NumberStyle::UpperRoman => "upper-roman",
+        }
+    }
+
+    /// The value of the `type` attribute on `<ol>` for this style.
+    pub fn html_type(self) -> &'static str {
+        match self {
+            NumberStyle::Decimal => "1",
+            NumberStyle::LowerAlpha => "a",
+            NumberStyle::UpperAlpha => "A",
+            NumberStyle::LowerRoman => "i",
+            NumberStyle::UpperRoman => "I",
+        }
+    }
+
+    /// Writes out a list item number in this style.
+    ///
+    /// Numbers which can't be written in this style, such as zero or
+    /// negative numbers, fall back to decimal.
+    pub fn format(self, number: i64) -> String {
+        let formatted = match self {
+            NumberStyle::Decimal => None,
+            NumberStyle::LowerAlpha => to_alpha(number),
+            NumberStyle::UpperAlpha => to_alpha(number).map(|s| s.to_ascii_uppercase()),
+            NumberStyle::LowerRoman => to_roman(number),
+            NumberStyle::UpperRoman => to_roman(number).map(|s| s.to_ascii_uppercase()),
+        };
+
+        formatted.unwrap_or_else(|| number.to_string())
+    }
+}
+
+/// Converts to letters, like a spreadsheet column: a, ..., z, aa, ab, ...
+fn to_alpha(mut number: i64) -> Option<String> {
+    if number < 1 {
+        return None;
+    }
+
+    let mut letters = Vec::new();
+    while number > 0 {
+        number -= 1;
+        letters.push(b'a' + (number % 26) as u8);
+        number /= 26;
+    }
+
+    letters.reverse();
+    String::from_utf8(letters).ok()
+}
+
+/// Converts to lowercase Roman numerals, which only go up to 3999.
+fn to_roman(mut number: i64) -> Option<String> {
+    const NUMERALS: [(i64, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+
+    if !(1..4000).contains(&number) {
+        return None;
+    }
+
+    let mut roman = String::new();
+    for (value, numeral) in NUMERALS {
+        while number >= value {
+            roman.push_str(numeral);
+            number -= value;
+        }
+    }
+
+    Some(roman)
+}
+
+impl TryFrom<&'_ str> for NumberStyle {
+    type Error = ();
+
+    /// Parses either the style name, or its HTML `type` value.
+    ///
+    /// The latter is case-sensitive, since `a` and `A` are different styles.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "1" => return Ok(NumberStyle::Decimal),
+            "a" => return Ok(NumberStyle::LowerAlpha),
+            "A" => return Ok(NumberStyle::UpperAlpha),
+            "i" => return Ok(NumberStyle::LowerRoman),
+            "I" => return Ok(NumberStyle::UpperRoman),
+            _ => (),
+        }
+
+        match value.to_ascii_lowercase().as_str() {
+            "decimal" => Ok(NumberStyle::Decimal),
+            "lower-alpha" | "alpha" => Ok(NumberStyle::LowerAlpha),
+            "upper-alpha" => Ok(NumberStyle::UpperAlpha),
+            "lower-roman" | "roman" => Ok(NumberStyle::LowerRoman),
+            "upper-roman" => Ok(NumberStyle::UpperRoman),
+            _ => Err(()),
         }
     }
 }
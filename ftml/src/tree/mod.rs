@@ -29,11 +29,13 @@ mod date;
 mod definition_list;
 mod element;
 mod form;
+mod gallery;
 mod heading;
 mod image;
 mod link;
 mod list;
 mod module;
+mod note;
 mod partial;
 mod ruby;
 mod tab;
@@ -45,17 +47,19 @@ pub use self::align::*;
 pub use self::anchor::*;
 pub use self::attribute::AttributeMap;
 pub use self::clear_float::*;
-use self::clone::page_refs_to_owned;
+use self::clone::{page_refs_to_owned, string_to_owned};
 pub use self::container::*;
 pub use self::date::Date;
 pub use self::definition_list::*;
 pub use self::element::*;
 pub use self::form::*;
+pub use self::gallery::*;
 pub use self::heading::*;
 pub use self::image::*;
 pub use self::link::*;
 pub use self::list::*;
 pub use self::module::*;
+pub use self::note::*;
 pub use self::partial::*;
 pub use self::ruby::*;
 pub use self::tab::*;
@@ -64,8 +68,9 @@ pub use self::tag::*;
 pub use self::variables::*;
 
 use self::clone::{elements_lists_to_owned, elements_to_owned};
-use crate::data::PageRef;
+use crate::data::{FileRef, PageRef};
 use crate::parsing::{ParseOutcome, ParseWarning};
+use std::borrow::Cow;
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
@@ -84,6 +89,12 @@ pub struct SyntaxTree<'t> {
     pub table_of_contents: Vec<Element<'t>>,
     pub has_toc_block: bool,
 
+    /// The IDs of each heading with a table of contents entry, in order.
+    ///
+    /// These are derived from the heading text, and only present
+    /// if enabled by `WikitextSettings::heading_anchors`.
+    pub heading_anchors: Vec<String>,
+
     /// The full footnote list for this page.
     pub footnotes: Vec<Vec<Element<'t>>>,
 
@@ -97,27 +108,81 @@ pub struct SyntaxTree<'t> {
     ///
     /// This is used for bulk querying the database for page titles and existence.
     pub internal_links: Vec<PageRef<'t>>,
+
+    /// The list of users referenced in the tree, by name.
+    ///
+    /// This is used for bulk querying the database for user information.
+    pub users: Vec<Cow<'t, str>>,
+
+    /// The list of attached files referenced in the tree.
+    ///
+    /// This is used for bulk querying file information, and for
+    /// finding which attachments are still in use by a page.
+    pub files: Vec<FileRef<'t>>,
+
+    /// The list of [[math]] blocks for this page, as the name and LaTeX source.
+    ///
+    /// Equations are numbered in this order, starting at one.
+    pub equations: Vec<(Option<String>, String)>,
+
+    /// The bibliography entries for this page, as the label and contents.
+    ///
+    /// Entries are numbered in this order, starting at one.
+    pub bibliography: Vec<(String, Vec<Element<'t>>)>,
+}
+
+/// Everything gathered while parsing a page, besides its elements.
+///
+/// Fields are named here so they can't be mixed up
+/// when the syntax tree is assembled.
+#[derive(Debug, Default)]
+pub(crate) struct SyntaxTreeParts<'t> {
+    pub table_of_contents: Vec<Element<'t>>,
+    pub has_toc_block: bool,
+    pub heading_anchors: Vec<String>,
+    pub footnotes: Vec<Vec<Element<'t>>>,
+    pub code: Vec<(String, String)>,
+    pub html: Vec<String>,
+    pub internal_links: Vec<PageRef<'t>>,
+    pub users: Vec<Cow<'t, str>>,
+    pub files: Vec<FileRef<'t>>,
+    pub equations: Vec<(Option<String>, String)>,
+    pub bibliography: Vec<(String, Vec<Element<'t>>)>,
 }
 
 impl<'t> SyntaxTree<'t> {
     pub(crate) fn from_element_result(
         elements: Vec<Element<'t>>,
         warnings: Vec<ParseWarning>,
-        table_of_contents: Vec<Element<'t>>,
-        has_toc_block: bool,
-        footnotes: Vec<Vec<Element<'t>>>,
-        code: Vec<(String, String)>,
-        html: Vec<String>,
-        internal_links: Vec<PageRef<'t>>,
+        parts: SyntaxTreeParts<'t>,
     ) -> ParseOutcome<Self> {
+        let SyntaxTreeParts {
+            table_of_contents,
+            has_toc_block,
+            heading_anchors,
+            footnotes,
+            code,
+            html,
+            internal_links,
+            users,
+            files,
+            equations,
+            bibliography,
+        } = parts;
+
         let tree = SyntaxTree {
             elements,
             table_of_contents,
             has_toc_block,
+            heading_anchors,
             footnotes,
             code,
             html,
             internal_links,
+            users,
+            files,
+            equations,
+            bibliography,
         };
         ParseOutcome::new(tree, warnings)
     }
@@ -127,10 +192,23 @@ impl<'t> SyntaxTree<'t> {
             elements: elements_to_owned(&self.elements),
             table_of_contents: elements_to_owned(&self.table_of_contents),
             has_toc_block: self.has_toc_block,
+            heading_anchors: self.heading_anchors.clone(),
             footnotes: elements_lists_to_owned(&self.footnotes),
             code: self.code.to_owned(),
             html: self.html.to_owned(),
             internal_links: page_refs_to_owned(&self.internal_links),
+            users: self
+                .users
+                .iter()
+                .map(|name| string_to_owned(name))
+                .collect(),
+            files: self.files.iter().map(|file| file.to_owned()).collect(),
+            equations: self.equations.clone(),
+            bibliography: self
+                .bibliography
+                .iter()
+                .map(|(label, contents)| (label.clone(), elements_to_owned(contents)))
+                .collect(),
         }
     }
 }
@@ -0,0 +1,57 @@
+/*
+ * tree/note.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::convert::TryFrom;
+
+/// The kind of note, which determines its styling and default title.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum NoteType {
+    #[default]
+    Info,
+    Warning,
+    Danger,
+    Tip,
+}
+
+impl NoteType {
+    pub fn name(self) -> &'static str {
+        match self {
+            NoteType::Info => "info",
+            NoteType::Warning => "warning",
+            NoteType::Danger => "danger",
+            NoteType::Tip => "tip",
+        }
+    }
+}
+
+impl TryFrom<&'_ str> for NoteType {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "info" => Ok(NoteType::Info),
+            "warning" => Ok(NoteType::Warning),
+            "danger" => Ok(NoteType::Danger),
+            "tip" => Ok(NoteType::Tip),
+            _ => Err(()),
+        }
+    }
+}
@@ -18,21 +18,86 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use super::clone::elements_to_owned;
+use super::clone::{elements_to_owned, option_string_to_owned};
 use super::{Alignment, AttributeMap, Element};
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::num::NonZeroU32;
 
+/// The largest column span a table cell may have, the same limit as in HTML.
+pub const MAX_COLUMN_SPAN: u32 = 1000;
+
+/// The largest row span a table cell may have, the same limit as in HTML.
+pub const MAX_ROW_SPAN: u32 = 65534;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub struct Table<'t> {
     pub attributes: AttributeMap<'t>,
+    pub caption: Option<Cow<'t, str>>,
+    pub sortable: bool,
+    pub column_types: Vec<ColumnType>,
     pub rows: Vec<TableRow<'t>>,
 }
 
 impl Table<'_> {
+    /// Moves leading rows made up entirely of header cells into the table head.
+    ///
+    /// This stops at the first row which isn't such a row, or which has
+    /// already been placed in another section.
+    pub fn detect_head_rows(&mut self) {
+        for row in &mut self.rows {
+            let all_headers =
+                !row.cells.is_empty() && row.cells.iter().all(|cell| cell.header);
+
+            if row.section != TableSection::Body || !all_headers {
+                break;
+            }
+
+            row.section = TableSection::Head;
+        }
+    }
+
+    /// Limits each cell's row span so it doesn't extend past the last row.
+    pub fn clamp_row_spans(&mut self) {
+        let row_count = self.rows.len();
+
+        for (index, row) in self.rows.iter_mut().enumerate() {
+            let remaining = (row_count - index) as u32;
+
+            for cell in &mut row.cells {
+                if cell.row_span.get() > remaining {
+                    cell.row_span = NonZeroU32::new(remaining).unwrap();
+                }
+            }
+        }
+    }
+
+    /// Finds the starting column of each cell in each row.
+    pub fn column_starts(&self) -> Vec<Vec<usize>> {
+        place_cells(self.rows.iter().map(|row| {
+            row.cells.iter().map(|cell| {
+                (
+                    cell.column_span.get() as usize,
+                    cell.row_span.get() as usize,
+                )
+            })
+        }))
+    }
+
+    /// Gets the declared type of the given column.
+    ///
+    /// Columns without a declared type are sorted as text.
+    pub fn column_type(&self, column: usize) -> ColumnType {
+        self.column_types.get(column).copied().unwrap_or_default()
+    }
+
     pub fn to_owned(&self) -> Table<'static> {
         Table {
             attributes: self.attributes.to_owned(),
+            caption: option_string_to_owned(&self.caption),
+            sortable: self.sortable,
+            column_types: self.column_types.clone(),
             rows: self.rows.iter().map(|row| row.to_owned()).collect(),
         }
     }
@@ -42,6 +107,7 @@ impl Table<'_> {
 #[serde(rename_all = "kebab-case")]
 pub struct TableRow<'t> {
     pub attributes: AttributeMap<'t>,
+    pub section: TableSection,
     pub cells: Vec<TableCell<'t>>,
 }
 
@@ -49,6 +115,7 @@ impl TableRow<'_> {
     pub fn to_owned(&self) -> TableRow<'static> {
         TableRow {
             attributes: self.attributes.to_owned(),
+            section: self.section,
             cells: self.cells.iter().map(|cell| cell.to_owned()).collect(),
         }
     }
@@ -59,7 +126,9 @@ impl TableRow<'_> {
 pub struct TableCell<'t> {
     pub header: bool,
     pub column_span: NonZeroU32,
+    pub row_span: NonZeroU32,
     pub align: Option<Alignment>,
+    pub sort_value: Option<Cow<'t, str>>,
     pub attributes: AttributeMap<'t>,
     pub elements: Vec<Element<'t>>,
 }
@@ -69,13 +138,135 @@ impl TableCell<'_> {
         TableCell {
             header: self.header,
             column_span: self.column_span,
+            row_span: self.row_span,
             align: self.align,
+            sort_value: option_string_to_owned(&self.sort_value),
             attributes: self.attributes.to_owned(),
             elements: elements_to_owned(&self.elements),
         }
     }
 }
 
+/// Which part of the table a row belongs to.
+///
+/// In HTML, these are the `<thead>`, `<tbody>`, and `<tfoot>` elements.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TableSection {
+    Head,
+    #[default]
+    Body,
+    Foot,
+}
+
+impl TableSection {
+    pub fn html_tag(self) -> &'static str {
+        match self {
+            TableSection::Head => "thead",
+            TableSection::Body => "tbody",
+            TableSection::Foot => "tfoot",
+        }
+    }
+}
+
+impl TryFrom<&'_ str> for TableSection {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "head" => Ok(TableSection::Head),
+            "body" => Ok(TableSection::Body),
+            "foot" => Ok(TableSection::Foot),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How the values in a sortable table column are compared.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColumnType {
+    #[default]
+    Text,
+    Number,
+    Date,
+    Rating,
+}
+
+impl ColumnType {
+    pub fn name(self) -> &'static str {
+        match self {
+            ColumnType::Text => "text",
+            ColumnType::Number => "number",
+            ColumnType::Date => "date",
+            ColumnType::Rating => "rating",
+        }
+    }
+}
+
+impl TryFrom<&'_ str> for ColumnType {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "text" => Ok(ColumnType::Text),
+            "number" => Ok(ColumnType::Number),
+            "date" => Ok(ColumnType::Date),
+            "rating" => Ok(ColumnType::Rating),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Finds the starting column of each cell, given each cell's column and row span.
+///
+/// Columns covered by cells spanning down from earlier rows are skipped.
+/// Spans are limited to [`MAX_COLUMN_SPAN`] and [`MAX_ROW_SPAN`].
+pub fn place_cells<R, C>(rows: R) -> Vec<Vec<usize>>
+where
+    R: IntoIterator<Item = C>,
+    C: IntoIterator<Item = (usize, usize)>,
+{
+    // How many more rows each column is covered for
+    let mut covered: Vec<usize> = Vec::new();
+    let mut starts = Vec::new();
+
+    for row in rows {
+        let mut row_starts = Vec::new();
+        let mut column = 0;
+
+        for (column_span, row_span) in row {
+            let column_span = column_span.min(MAX_COLUMN_SPAN as usize);
+            let row_span = row_span.min(MAX_ROW_SPAN as usize);
+
+            while matches!(covered.get(column), Some(&rows) if rows > 0) {
+                column += 1;
+            }
+
+            let end = column + column_span;
+            if covered.len() < end {
+                covered.resize(end, 0);
+            }
+
+            // Includes this row, which is subtracted below
+            for rows in &mut covered[column..end] {
+                *rows = row_span;
+            }
+
+            row_starts.push(column);
+            column = end;
+        }
+
+        for rows in &mut covered {
+            *rows = rows.saturating_sub(1);
+        }
+
+        starts.push(row_starts);
+    }
+
+    starts
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub enum TableItem<'t> {
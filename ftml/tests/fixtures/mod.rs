@@ -0,0 +1,311 @@
+/*
+ * tests/fixtures/mod.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Named input pages, shared between the tests and the benchmarks.
+//!
+//! Each fixture represents a kind of page which has been slow to
+//! process in the past, or is expected to be. Benchmarks run over
+//! all of them, and tests check that they render at all.
+//!
+//! Include this from a test or benchmark crate with:
+//! `#[path = "../tests/fixtures/mod.rs"] mod fixtures;`
+
+#![allow(dead_code)]
+
+use ftml::data::{PageCallbacks, PageInfo, PageRef};
+use ftml::includes::{include, FetchedPage, IncludeRef, Includer};
+use ftml::parsing::ParseWarning;
+use ftml::render::html::{HtmlOutput, HtmlRender};
+use ftml::render::text::TextRender;
+use ftml::render::Render;
+use ftml::settings::{WikitextMode, WikitextSettings};
+use ftml::tree::SyntaxTree;
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub name: &'static str,
+    pub text: String,
+}
+
+/// All fixtures, in a stable order.
+pub fn all() -> Vec<Fixture> {
+    vec![
+        Fixture {
+            name: "scp-article",
+            text: scp_article(),
+        },
+        Fixture {
+            name: "include-hub",
+            text: include_hub(),
+        },
+        Fixture {
+            name: "deep-nesting",
+            text: deep_nesting(),
+        },
+        Fixture {
+            name: "unclosed-bold",
+            text: unclosed_bold(),
+        },
+        Fixture {
+            name: "unclosed-blocks",
+            text: unclosed_blocks(),
+        },
+    ]
+}
+
+/// A long SCP article, with tables, footnotes, collapsibles and tabs.
+pub fn scp_article() -> String {
+    read("scp-article.ftml")
+}
+
+/// A hub page made almost entirely of `[[include]]`s with variables.
+///
+/// Use [`FixtureIncluder`] to substitute them.
+pub fn include_hub() -> String {
+    read("include-hub.ftml")
+}
+
+/// Blocks, formatting and lists nested close to the parser's recursion limit.
+pub fn deep_nesting() -> String {
+    const DEPTH: usize = 90;
+
+    let mut text = String::new();
+
+    for i in 0..DEPTH {
+        text.push_str(&format!("[[div class=\"level-{i}\"]]\n"));
+    }
+
+    text.push_str("**//__Innermost text__//**\n");
+
+    for _ in 0..DEPTH {
+        text.push_str("[[/div]]\n");
+    }
+
+    text.push('\n');
+
+    for i in 0..DEPTH {
+        text.push_str(&" ".repeat(i));
+        text.push_str(&format!("* List item at depth {i}\n"));
+    }
+
+    text.push('\n');
+    text.push_str(&"> ".repeat(DEPTH));
+    text.push_str("Deeply quoted text\n\n");
+
+    for _ in 0..DEPTH {
+        text.push_str("[[span]]**");
+    }
+
+    text.push_str("text");
+
+    for _ in 0..DEPTH {
+        text.push_str("**[[/span]]");
+    }
+
+    text
+}
+
+/// Thousands of `**` which are never closed, forcing the parser to backtrack.
+pub fn unclosed_bold() -> String {
+    const COUNT: usize = 2000;
+
+    let mut text = String::new();
+
+    for i in 0..COUNT {
+        text.push_str("**word ");
+
+        if i % 40 == 39 {
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+/// Many block openings which are never closed.
+///
+/// Parse time grows much faster than linearly with the count,
+/// which is kept small so the benchmarks stay practical.
+pub fn unclosed_blocks() -> String {
+    const COUNT: usize = 100;
+
+    let mut text = String::new();
+
+    for i in 0..COUNT {
+        text.push_str(&format!("[[div class=\"unclosed-{i}\"]]\n"));
+        text.push_str("Some //text [[span\n");
+    }
+
+    text
+}
+
+fn read(filename: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(filename);
+
+    fs::read_to_string(&path).unwrap_or_else(|error| {
+        panic!("Unable to read fixture {}: {error}", path.display())
+    })
+}
+
+/// Page information used when rendering fixtures.
+pub fn page_info() -> PageInfo<'static> {
+    PageInfo {
+        page: Cow::Borrowed("fixture"),
+        category: None,
+        site: Cow::Borrowed("test"),
+        domain: Cow::Borrowed("test.wikijump.com"),
+        media_domain: Cow::Borrowed("test.wjfiles.com"),
+        title: Cow::Borrowed("Fixture"),
+        alt_title: None,
+        rating: 0.0,
+        tags: vec![Cow::Borrowed("fixture")],
+        language: Cow::Borrowed("default"),
+    }
+}
+
+/// Settings used when rendering fixtures.
+pub fn settings() -> WikitextSettings {
+    let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+    settings.use_include_compatibility = true;
+    settings
+}
+
+/// Preprocesses and parses the text as the given page.
+pub fn parse_page(
+    text: &str,
+    page_info: &PageInfo,
+    settings: &WikitextSettings,
+    callbacks: Arc<dyn PageCallbacks>,
+) -> (SyntaxTree<'static>, Vec<ParseWarning>) {
+    let mut text = String::from(text);
+    ftml::preprocess(&mut text);
+
+    let tokens = ftml::tokenize(&text);
+    let (tree, warnings) = ftml::parse(&tokens, page_info, callbacks, settings).into();
+
+    (tree.to_owned(), warnings)
+}
+
+/// Preprocesses and parses the text as the fixture page.
+pub fn parse(
+    text: &str,
+    settings: &WikitextSettings,
+    callbacks: Arc<dyn PageCallbacks>,
+) -> (SyntaxTree<'static>, Vec<ParseWarning>) {
+    parse_page(text, &page_info(), settings, callbacks)
+}
+
+/// Parses and renders the text as the fixture page.
+pub fn render<R: Render>(
+    renderer: &R,
+    text: &str,
+    settings: &WikitextSettings,
+    callbacks: Arc<dyn PageCallbacks>,
+) -> (R::Output, Vec<ParseWarning>) {
+    let (tree, warnings) = parse(text, settings, Arc::clone(&callbacks));
+    let output = renderer.render(&tree, &page_info(), callbacks, settings);
+
+    (output, warnings)
+}
+
+/// Parses and renders the text as the fixture page, to HTML.
+pub fn render_html(
+    text: &str,
+    settings: &WikitextSettings,
+    callbacks: Arc<dyn PageCallbacks>,
+) -> (HtmlOutput, Vec<ParseWarning>) {
+    render(&HtmlRender, text, settings, callbacks)
+}
+
+/// Parses and renders the text as the fixture page, to plain text.
+pub fn render_text(
+    text: &str,
+    settings: &WikitextSettings,
+    callbacks: Arc<dyn PageCallbacks>,
+) -> (String, Vec<ParseWarning>) {
+    render(&TextRender, text, settings, callbacks)
+}
+
+/// Substitutes all includes in the (preprocessed) text using [`FixtureIncluder`].
+pub fn expand_includes(text: &str, settings: &WikitextSettings) -> String {
+    let (output, _pages) = include(text, settings, FixtureIncluder::new(), || {
+        panic!("Mismatched includer page count")
+    })
+    .unwrap_or_else(|error| match error {});
+
+    output
+}
+
+/// An includer serving a hub entry component for every page.
+///
+/// Pages in the `component` category named `hub-entry` get the
+/// full `component.ftml` fixture, all others a single line.
+#[derive(Debug)]
+pub struct FixtureIncluder {
+    component: String,
+}
+
+impl FixtureIncluder {
+    pub fn new() -> Self {
+        FixtureIncluder {
+            component: read("component.ftml"),
+        }
+    }
+}
+
+impl<'t> Includer<'t> for FixtureIncluder {
+    type Error = Infallible;
+
+    fn include_pages(
+        &mut self,
+        includes: &[IncludeRef<'t>],
+    ) -> Result<Vec<FetchedPage<'t>>, Infallible> {
+        Ok(includes
+            .iter()
+            .map(|include| {
+                let page_ref = include.page_ref();
+                let content = if page_ref.name() == "hub-entry" {
+                    self.component.clone()
+                } else {
+                    format!("[[span class=\"{}\"]]component[[/span]]", page_ref.name())
+                };
+
+                FetchedPage {
+                    page_ref: page_ref.clone(),
+                    content: Some(Cow::Owned(content)),
+                }
+            })
+            .collect())
+    }
+
+    fn no_such_include(
+        &mut self,
+        page_ref: &PageRef<'t>,
+    ) -> Result<Cow<'t, str>, Infallible> {
+        Ok(Cow::Owned(format!("Missing page: {page_ref}")))
+    }
+}
@@ -0,0 +1,123 @@
+/*
+ * tests/lexer.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Differential tests between the hand-written lexer and the pest reference.
+
+extern crate ftml;
+extern crate proptest;
+
+mod fixtures;
+
+use ftml::tokenizer::{tokenize, tokenize_reference};
+use proptest::prelude::*;
+use std::fs;
+use std::path::Path;
+
+fn check_lexers(text: &str) -> Result<(), String> {
+    let expected = tokenize_reference(text);
+    let actual = tokenize(text);
+
+    let expected = expected.tokens();
+    let actual = actual.tokens();
+
+    for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+        if expected != actual {
+            return Err(format!(
+                "Token {index} differs\nExpected: {expected:?}\nActual:   {actual:?}",
+            ));
+        }
+    }
+
+    if expected.len() != actual.len() {
+        return Err(format!(
+            "Token count differs, expected {}, actual {}",
+            expected.len(),
+            actual.len(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn corpus() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut failures = Vec::new();
+    let mut count = 0;
+
+    for entry in fs::read_dir(&directory).expect("Unable to read corpus directory") {
+        let path = entry.expect("Unable to read corpus entry").path();
+        let text = fs::read_to_string(&path).expect("Unable to read corpus file");
+
+        // Check both the raw file and each line on its own,
+        // so start-of-input cases are covered too.
+        let inputs = std::iter::once(text.as_str()).chain(text.lines());
+
+        for input in inputs {
+            if let Err(message) = check_lexers(input) {
+                failures.push(format!("{}: {message}", path.display()));
+            }
+        }
+
+        count += 1;
+    }
+
+    assert!(count > 0, "No corpus files found");
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
+
+#[test]
+fn fixtures() {
+    for fixture in fixtures::all() {
+        if let Err(message) = check_lexers(&fixture.text) {
+            panic!("{}: {message}", fixture.name);
+        }
+    }
+}
+
+/// Generates text made mostly of fragments which are meaningful to the lexer.
+fn wikitext() -> impl Strategy<Value = String> {
+    let fragment = prop_oneof![
+        4 => prop::sample::select(vec![
+            "@", "@@", "@<", ">@", "&", "&#", ";", "[", "]", "[[", "]]", "[!--", "--]",
+            "[[$", "$]]", "[[/", "$", "*", "**", "/", "//", "_", "__", "^", "^^", ",", ",,",
+            "#", "##", "{", "}", "{{", "}}", "{$", "{@", "|", "||", "||~", "||>", "||=",
+            "~", "~~~", "<", ">", ">>", "<<", "-", "--", "---", "+", "++++++", "=", ":",
+            "\"", "\\", "\n", "\r", "\r\n", " ", "\t", "\u{a0}", "http://", "https://",
+            "ftp://", "x@y.z", "ab", "Z9", "ё", "日", "🙂", "!", ".",
+        ])
+        .prop_map(String::from),
+        1 => any::<char>().prop_map(String::from),
+        1 => "[a-z0-9]{1,8}",
+    ];
+
+    prop::collection::vec(fragment, 0..64).prop_map(|fragments| fragments.concat())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2048))]
+
+    #[test]
+    fn generated(text in wikitext()) {
+        if let Err(message) = check_lexers(&text) {
+            return Err(TestCaseError::fail(format!("{message}\nInput: {text:?}")));
+        }
+    }
+}
@@ -0,0 +1,1678 @@
+/*
+ * tests/render.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2022 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Runs every fixture through the full pipeline.
+
+extern crate ftml;
+
+mod fixtures;
+
+use ftml::batch::{render_batch, BatchPage};
+use ftml::data::{
+    ExpressionResult, FileInfo, FileRef, NullPageCallbacks, PageCallbacks, PageInfo,
+    PageRef, PartialPageInfo, UserInfo,
+};
+use ftml::i18n;
+use ftml::includes::{CachedIncluder, FetchedPage, IncludeCache, IncludeRef, Includer};
+use ftml::parsing::ParseWarningKind;
+use ftml::render::excerpt::{ExcerptLength, ExcerptRender};
+use ftml::render::html::{fill_placeholders, HtmlRender};
+use ftml::render::text::{ReadableTextRender, TextRender};
+use ftml::render::{ModuleRenderMode, Render};
+use ftml::settings::{
+    EmbedMedia, EmbedProvider, HeadingAnchors, WikitextMode, WikitextSettings,
+};
+use ftml::tree::{ColumnType, Element, GallerySize, NoteType};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fs;
+use std::io;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn fixtures() {
+    let page_info = fixtures::page_info();
+    let settings = fixtures::settings();
+
+    for fixture in fixtures::all() {
+        let mut text = fixture.text;
+        ftml::preprocess(&mut text);
+
+        let text = fixtures::expand_includes(&text, &settings);
+        let tokens = ftml::tokenize(&text);
+        let (tree, _warnings) = ftml::parse(
+            &tokens,
+            &page_info,
+            Arc::new(NullPageCallbacks {}),
+            &settings,
+        )
+        .into();
+
+        let html = HtmlRender.render(
+            &tree,
+            &page_info,
+            Arc::new(NullPageCallbacks {}),
+            &settings,
+        );
+        let text = TextRender.render(
+            &tree,
+            &page_info,
+            Arc::new(NullPageCallbacks {}),
+            &settings,
+        );
+
+        assert!(!html.body.is_empty(), "No HTML output for {}", fixture.name);
+        assert!(!text.is_empty(), "No text output for {}", fixture.name);
+    }
+}
+
+#[test]
+fn include_hub_expands() {
+    let settings = fixtures::settings();
+    let mut text = fixtures::include_hub();
+    ftml::preprocess(&mut text);
+
+    let expanded = fixtures::expand_includes(&text, &settings);
+
+    assert!(!expanded.contains("[[include "));
+    assert!(expanded.contains("hub-entry-title"));
+}
+
+#[test]
+fn deferred_modules() {
+    let mut settings = fixtures::settings();
+    settings.defer_modules = true;
+
+    let text = "Before @@[<!--wj-module:guess-0-->]@@\n\n\
+                [[module ListPages category=\"fragment\"]]\n\n\
+                [[module Rate]]\n\n\
+                After";
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    let names: Vec<_> = output.modules.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["ListPages", "Rate"]);
+    assert_eq!(output.modules[0].params["category"], "fragment");
+    assert_ne!(output.modules[0].id, output.modules[1].id);
+
+    // Module output which looks like a placeholder must not be expanded again.
+    let rendered: HashMap<_, _> = output
+        .modules
+        .iter()
+        .map(|module| {
+            let html = format!("<ul>{}</ul><!--wj-module:{}-->", module.name, module.id);
+            (module.id.clone(), html)
+        })
+        .collect();
+
+    let body = fill_placeholders(&output.body, &rendered);
+    assert!(body.contains("&lt;!--wj-module:guess-0--&gt;"));
+    assert!(body.contains("<ul>ListPages</ul>"));
+    assert!(body.contains("<ul>Rate</ul>"));
+    assert_eq!(body.matches("<ul>").count(), 2);
+}
+
+#[test]
+fn users_collected() {
+    let settings = fixtures::settings();
+    let text = "[[user alice]] [[*user bob]] [[user alice]]";
+
+    let (tree, _warnings) =
+        fixtures::parse(text, &settings, Arc::new(NullPageCallbacks {}));
+    assert_eq!(tree.users, ["alice", "bob", "alice"]);
+
+    // NullPageCallbacks knows no users, so all are shown as deleted.
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert_eq!(output.body.matches("w-user-deleted").count(), 3);
+    assert!(output.body.contains("(account deleted)"));
+}
+
+#[test]
+fn text_modules() {
+    let settings = fixtures::settings();
+
+    let (output, _warnings) = fixtures::render_text(
+        "Listing:\n\n[[module ListPages category=\"fragment\"]]\n\nEnd",
+        &settings,
+        Arc::new(NullPageCallbacks {}),
+    );
+
+    assert!(output.contains("\n[module ListPages]\n"), "{output:?}");
+    assert!(!output.contains("NullModule"));
+}
+
+#[test]
+fn readable_text() {
+    let settings = fixtures::settings();
+
+    let text = "||~ Имя ||~ Class ||\n\
+                || SCP-173 || Евклид ||\n\
+                || 彫刻 || Safe ||\n\
+                \n\
+                See [[[scp-173|the sculpture]]] and [https://example.com/ Example].\n\
+                \n\
+                [[image picture.png alt=\"A picture\"]]\n\
+                \n\
+                [[iframe https://example.com/embed]]\n\
+                \n\
+                > A quoted line which is long enough that it has to be wrapped.";
+
+    let renderer = ReadableTextRender::new(NonZeroUsize::new(30));
+    let (output, _warnings) =
+        fixtures::render(&renderer, text, &settings, Arc::new(NullPageCallbacks {}));
+    let lines: Vec<_> = output.lines().collect();
+
+    assert_eq!(
+        &lines[..4],
+        [
+            "Имя     | Class",
+            "--------+-------",
+            "SCP-173 | Евклид",
+            "彫刻    | Safe",
+        ],
+    );
+    assert!(output.contains("the sculpture <1>"));
+    assert!(output.contains("Example <2>"));
+    assert!(output.contains("<1> https://test.wikijump.com/scp-173"));
+    assert!(output.contains("<2> https://example.com/"));
+    assert!(output.contains("[image: A picture]"));
+    assert!(
+        output.contains("[iframe:\nhttps://example.com/embed]"),
+        "{output}"
+    );
+
+    // Wrapped quote lines keep their marker
+    let quote: Vec<_> = lines
+        .iter()
+        .filter(|line| line.starts_with('>') && line.len() > 2)
+        .copied()
+        .collect();
+
+    assert_eq!(
+        quote,
+        [
+            "> A quoted line which is long",
+            "> enough that it has to be",
+            "> wrapped.",
+        ],
+        "{output}",
+    );
+    assert!(
+        lines
+            .iter()
+            .filter(|line| !line.contains("https://"))
+            .all(|line| line.chars().count() <= 30),
+        "{output}",
+    );
+}
+
+#[test]
+fn excerpts() {
+    let settings = fixtures::settings();
+
+    let text = "[[toc]]\n\n\
+                + Overview\n\n\
+                The **quick //brown//** fox[[footnote]]Not a wolf.[[/footnote]] jumps. \
+                It lands <safely> over the lazy dog! The end.\n\n\
+                [[module Rate]]\n\n\
+                || Table || cell ||\n\n\
+                [[code]]\nlet x = 1;\n[[/code]]\n\n\
+                [[hidden]]\nSecret notes.\n[[/hidden]]\n\n\
+                Final words.";
+
+    let render = |renderer: &ExcerptRender| {
+        fixtures::render(renderer, text, &settings, Arc::new(NullPageCallbacks {})).0
+    };
+
+    let full = render(&ExcerptRender::new(ExcerptLength::Characters(1000)));
+    assert_eq!(
+        full.text,
+        "Overview The quick brown fox jumps. \
+         It lands <safely> over the lazy dog! The end. Final words.",
+    );
+    assert!(!full.truncated);
+
+    // Cut on a word boundary within formatting, with balanced tags
+    let short = render(&ExcerptRender::new(ExcerptLength::Characters(23)));
+    assert_eq!(short.text, "Overview The quick…");
+    assert_eq!(short.html, "Overview The <strong>quick</strong>…");
+    assert!(short.truncated);
+
+    let short = render(&ExcerptRender::new(ExcerptLength::Characters(26)));
+    assert_eq!(
+        short.html,
+        "Overview The <strong>quick <em>brown</em></strong>…"
+    );
+
+    let sentences = render(&ExcerptRender::new(ExcerptLength::Sentences(2)));
+    assert_eq!(
+        sentences.html,
+        "Overview The <strong>quick <em>brown</em></strong> fox jumps. \
+         It lands &lt;safely&gt; over the lazy dog!",
+    );
+    assert!(sentences.truncated);
+
+    // Everything included
+    let everything = render(&ExcerptRender {
+        skip_modules: false,
+        skip_hidden: false,
+        skip_footnotes: false,
+        skip_table_of_contents: false,
+        skip_code: false,
+        skip_tables: false,
+        ..ExcerptRender::new(ExcerptLength::Characters(1000))
+    });
+    for part in [
+        "fox[1] jumps",
+        "[module Rate]",
+        "Table cell",
+        "let x = 1;",
+        "Secret notes.",
+    ] {
+        assert!(everything.text.contains(part), "{:?}", everything.text);
+    }
+}
+
+#[test]
+fn preview_metadata() {
+    let settings = fixtures::settings();
+
+    let text = "Some **introduction** text.\n\n\
+                [[div]]\n[[image picture.png]]\n[[/div]]\n\n\
+                [[image second.png]]";
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+    let meta = |name: &str| {
+        output
+            .meta
+            .iter()
+            .find(|meta| meta.name == name)
+            .map(|meta| meta.value.as_str())
+    };
+
+    let image = "https://test.wjfiles.com/local--files/fixture/picture.png";
+    assert_eq!(meta("og:title"), Some("Fixture"));
+    assert_eq!(meta("og:description"), Some("Some introduction text."));
+    assert_eq!(meta("og:url"), Some("https://test.wikijump.com/fixture"));
+    assert_eq!(meta("og:image"), Some(image));
+    assert_eq!(meta("twitter:card"), Some("summary_large_image"));
+    assert_eq!(meta("twitter:image"), Some(image));
+
+    let json_ld = output.json_ld.expect("No JSON-LD for page");
+    let json_ld: serde_json::Value = serde_json::from_str(&json_ld).unwrap();
+    assert_eq!(json_ld["@type"], "Article");
+    assert_eq!(json_ld["headline"], "Fixture");
+    assert_eq!(json_ld["keywords"], "fixture");
+    assert!(json_ld.get("inLanguage").is_none());
+
+    // Only generated for full pages by default
+    let settings = WikitextSettings::from_mode(WikitextMode::ForumPost);
+    assert!(!settings.preview_metadata);
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+    assert!(
+        output
+            .meta
+            .iter()
+            .all(|meta| !meta.name.starts_with("og:")
+                && !meta.name.starts_with("twitter:")),
+        "{:?}",
+        output.meta,
+    );
+    assert!(output.json_ld.is_none());
+}
+
+#[test]
+fn heading_anchors() {
+    let mut settings = fixtures::settings();
+    settings.heading_anchors = HeadingAnchors::SlugAndIndex;
+
+    let text = "[[toc]]\n\n\
+                + Объект SCP-173\n\n\
+                ++ Don't blink!\n\n\
+                ++ Don't blink!\n\n\
+                +* No entry";
+
+    let (tree, _warnings) =
+        fixtures::parse(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert_eq!(
+        tree.heading_anchors,
+        ["u-obekt-scp-173", "u-dont-blink", "u-dont-blink-1"],
+    );
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    for anchor in &tree.heading_anchors {
+        assert!(output.body.contains(&format!("href=\"#{anchor}\"")));
+    }
+
+    assert!(output
+        .body
+        .contains("<h1 id=\"u-obekt-scp-173\"><a id=\"toc0\"></a>"));
+    assert!(output
+        .body
+        .contains("<h2 id=\"u-dont-blink-1\"><a id=\"toc2\"></a>"));
+    assert!(output.body.contains("<h1><span>No entry</span></h1>"));
+
+    // Positional IDs remain the default
+    settings.heading_anchors = HeadingAnchors::Index;
+    let (tree, _warnings) =
+        fixtures::parse(text, &settings, Arc::new(NullPageCallbacks {}));
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert!(tree.heading_anchors.is_empty());
+    assert!(output.body.contains("<h2 id=\"toc1\">"));
+    assert!(output.body.contains("href=\"#toc1\""));
+}
+
+#[test]
+fn id_namespace() {
+    let mut settings = fixtures::settings();
+    settings.id_namespace = Some(String::from("post-7"));
+
+    let text = "[[toc]]\n\n\
+                + Heading\n\n\
+                Text[[footnote]]Note[[/footnote]]";
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    for fragment in [
+        "id=\"post-7-toc\"",
+        "id=\"post-7-toc-list\"",
+        "href=\"#post-7-toc0\"",
+        "<h1 id=\"post-7-toc0\">",
+        "id=\"post-7-footnoteref-1\"",
+        "href=\"#post-7-footnote-1\"",
+        "id=\"post-7-footnote-1\"",
+        "href=\"#post-7-footnoteref-1\"",
+    ] {
+        assert!(
+            output.body.contains(fragment),
+            "{fragment} in {}",
+            output.body
+        );
+    }
+
+    assert!(!output.body.contains("\"#toc0\""));
+    assert!(!output.body.contains("\"footnote-1\""));
+}
+
+#[test]
+fn equation_references() {
+    let settings = fixtures::settings();
+
+    let text = "See [[eref mass-energy]] and [[eref missing]].\n\n\
+                [[math]]\na^2 + b^2 = c^2\n[[/math]]\n\n\
+                [[math mass-energy]]\nE = mc^2\n[[/math]]";
+
+    let (tree, warnings) =
+        fixtures::parse(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert_eq!(
+        tree.equations,
+        vec![
+            (None, String::from("a^2 + b^2 = c^2")),
+            (Some(String::from("mass-energy")), String::from("E = mc^2")),
+        ],
+    );
+
+    let unknown = warnings
+        .iter()
+        .filter(|warning| warning.kind() == ParseWarningKind::NoSuchEquation)
+        .count();
+    assert_eq!(unknown, 1, "{warnings:#?}");
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert!(output.body.contains("id=\"equation-2\""), "{}", output.body);
+    assert!(
+        output
+            .body
+            .contains("href=\"#equation-2\" data-name=\"mass-energy\">(2)</a>"),
+        "{}",
+        output.body,
+    );
+    assert!(
+        output.body.contains("wj-equation-ref-tooltip"),
+        "{}",
+        output.body
+    );
+    assert!(output.body.contains("E = mc^2"), "{}", output.body);
+
+    let (text, _warnings) =
+        fixtures::render_text(text, &settings, Arc::new(NullPageCallbacks {}));
+    assert!(text.starts_with("See (2) and (?)."), "{text}");
+}
+
+#[test]
+fn latex_math() {
+    let settings = fixtures::settings();
+
+    let text = "[[math]]\n\\newcommand{\\sq}[1]{#1^2}\n[[/math]]\n\n\
+                [[math]]\n\\sq{x} + 1\n[[/math]]\n\n\
+                [[math]]\n|x| = \\begin{cases} x & x \\geq 0 \\\\ -x & x < 0 \\end{cases}\n[[/math]]\n\n\
+                [[math]]\n\\frac{1}{\\nope}\n[[/math]]";
+
+    let (tree, _warnings) =
+        fixtures::parse(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    // The preamble isn't numbered
+    assert_eq!(tree.equations.len(), 3);
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    for fragment in [
+        "id=\"equation-1\"",
+        "alttext=\"x squared plus 1\"",
+        "<msup><mi>x</mi><mn>2</mn></msup>",
+        "<mtable columnalign=\"left\">",
+        "<span class=\"wj-math-error-message\">Unknown command \\nope</span>",
+        "\\frac{1}{<mark class=\"wj-math-error-token\">\\nope</mark>}",
+    ] {
+        assert!(
+            output.body.contains(fragment),
+            "{fragment} in {}",
+            output.body
+        );
+    }
+
+    assert!(!output.body.contains("equation-4"));
+    assert!(!output.body.contains("PARSE ERROR"), "{}", output.body);
+
+    let (text, _warnings) =
+        fixtures::render_text(text, &settings, Arc::new(NullPageCallbacks {}));
+    assert_eq!(
+        text,
+        "x squared plus 1\n\n\
+         vertical bar x vertical bar equals cases: x, x is greater than or equal to 0; \
+         minus x, x is less than 0\n\n\
+         \\frac{1}{\\nope}",
+    );
+}
+
+#[test]
+fn latex_nesting_depth() {
+    let settings = fixtures::settings();
+
+    let groups = format!("{}x{}", "{".repeat(10000), "}".repeat(10000));
+    let roots = format!("{}x{}", "\\sqrt{".repeat(5000), "}".repeat(5000));
+    let text = format!(
+        "[[math]]\n{groups}\n[[/math]]\n\n\
+         [[math]]\n{roots}\n[[/math]]\n\n\
+         Inline [[$ {groups} $]] math.",
+    );
+
+    let (output, _warnings) =
+        fixtures::render_html(&text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert_eq!(
+        output
+            .body
+            .matches(
+                "<span class=\"wj-math-error-message\">Formula is nested too deeply</span>",
+            )
+            .count(),
+        3,
+    );
+
+    // Unreadable formulas are given as source
+    let (text, _warnings) =
+        fixtures::render_text(&text, &settings, Arc::new(NullPageCallbacks {}));
+    assert!(text.starts_with(&groups), "{text}");
+    assert!(text.contains(&roots), "{text}");
+}
+
+#[test]
+fn latex_macro_size() {
+    let settings = fixtures::settings();
+
+    let text = "[[math]]\n\\newcommand{\\ten}[1]{#1#1#1#1#1#1#1#1#1#1}\n[[/math]]\n\n\
+                [[math]]\n\\ten{\\ten{x}}\n[[/math]]\n\n\
+                [[math]]\n\\ten{\\ten{\\ten{xxxxxxxxxx}}}\n[[/math]]";
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    // Few expansions, but each repeats its argument
+    assert_eq!(output.body.matches("<mi>x</mi>").count(), 100);
+    assert_eq!(
+        output
+            .body
+            .matches(
+                "<span class=\"wj-math-error-message\">\
+                 Macros expand too much, is a macro recursive?</span>",
+            )
+            .count(),
+        1,
+    );
+}
+
+/// Finds the message IDs requested in the given source directory.
+fn find_message_ids(directory: &Path, ids: &mut Vec<String>) {
+    const CALL: &str = "get_message(\"";
+
+    for entry in fs::read_dir(directory).expect("Unable to read source directory") {
+        let path = entry.expect("Unable to read source entry").path();
+
+        if path.is_dir() {
+            find_message_ids(&path, ids);
+            continue;
+        }
+
+        let source = fs::read_to_string(&path).expect("Unable to read source file");
+        for (index, _) in source.match_indices(CALL) {
+            let rest = &source[index + CALL.len()..];
+            let end = rest.find('"').expect("Unterminated message ID");
+            ids.push(String::from(&rest[..end]));
+        }
+    }
+}
+
+#[test]
+fn message_catalogs() {
+    let mut ids = Vec::new();
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/render");
+    find_message_ids(&directory, &mut ids);
+    assert!(!ids.is_empty(), "No message IDs found");
+
+    let english = i18n::catalog("en").unwrap();
+
+    for language in i18n::LANGUAGES {
+        let catalog = i18n::catalog(language).unwrap();
+
+        for id in &ids {
+            assert!(
+                catalog.iter().any(|(message_id, _)| message_id == id),
+                "Message '{id}' is missing from catalog '{language}'",
+            );
+        }
+
+        for (id, _) in english {
+            assert!(
+                catalog.iter().any(|(message_id, _)| message_id == id),
+                "Message '{id}' is missing from catalog '{language}'",
+            );
+        }
+    }
+
+    // Fallbacks
+    assert_eq!(i18n::get_message("default", "toc-open"), Some("Unfold"));
+    assert_eq!(i18n::get_message("ru-RU", "toc-open"), Some("Раскрыть"));
+    assert_eq!(i18n::get_message("xx", "toc-open"), Some("Unfold"));
+    assert_eq!(i18n::get_message("en", "no-such-message"), None);
+
+    // Rendering uses the page's language
+    let mut page_info = fixtures::page_info();
+    page_info.language = Cow::Borrowed("ru");
+    let settings = fixtures::settings();
+
+    let (tree, _warnings) = fixtures::parse_page(
+        "[[toc]]\n\n+ Заголовок\n\nТекст[[footnote]]Сноска[[/footnote]]",
+        &page_info,
+        &settings,
+        Arc::new(NullPageCallbacks {}),
+    );
+
+    let output =
+        HtmlRender.render(&tree, &page_info, Arc::new(NullPageCallbacks {}), &settings);
+
+    assert!(output.body.contains("Содержание"), "{}", output.body);
+    assert!(output.body.contains("Сноски"), "{}", output.body);
+}
+
+#[test]
+fn bibliography() {
+    let settings = fixtures::settings();
+
+    let text = "Shown by Smith((bibcite smith)) and Jones((bibcite jones)).\n\
+                Again((bibcite smith)), but not((bibcite missing)).\n\n\
+                [[bibliography]]\n\
+                : jones : Jones, //A Book//.\n\
+                : smith : Smith, An Article.\n\
+                : unused : Never cited.\n\
+                [[/bibliography]]";
+
+    let (tree, warnings) =
+        fixtures::parse(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    // Numbered in citation order, then uncited entries
+    let labels = tree
+        .bibliography
+        .iter()
+        .map(|(label, _)| label.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(labels, ["smith", "jones", "unused"]);
+
+    let count = |kind| {
+        warnings
+            .iter()
+            .filter(|warning| warning.kind() == kind)
+            .count()
+    };
+    assert_eq!(
+        count(ParseWarningKind::NoSuchBibliographyEntry),
+        1,
+        "{warnings:#?}",
+    );
+    assert_eq!(
+        count(ParseWarningKind::BibliographyEntryUnused),
+        1,
+        "{warnings:#?}",
+    );
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert!(
+        output
+            .body
+            .contains("Jones[<a class=\"bibcite w-bibcite\" href=\"#bibitem-2\""),
+        "{}",
+        output.body,
+    );
+    assert!(output.body.contains("id=\"bibitem-1\""), "{}", output.body);
+    assert!(output.body.contains("Bibliography"), "{}", output.body);
+    assert!(output.body.contains("<em>A Book</em>"), "{}", output.body);
+    assert!(output.body.contains("[?]"), "{}", output.body);
+
+    let (text, _warnings) =
+        fixtures::render_text(text, &settings, Arc::new(NullPageCallbacks {}));
+    assert!(
+        text.starts_with("Shown by Smith[1] and Jones[2].\nAgain[1], but not[?]."),
+        "{text}",
+    );
+    assert!(
+        text.ends_with("1. Smith, An Article.\n2. Jones, A Book.\n3. Never cited."),
+        "{text}",
+    );
+
+    // Only the first entry with a label is used
+    let text = "Cited((bibcite smith)).\n\n\
+                [[bibliography]]\n\
+                : smith : The first.\n\
+                [[/bibliography]]\n\n\
+                [[bibliography]]\n\
+                : smith : The second.\n\
+                : smith : The third.\n\
+                [[/bibliography]]";
+
+    let (output, warnings) =
+        fixtures::render_text(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    let kinds: Vec<_> = warnings.iter().map(|warning| warning.kind()).collect();
+    assert_eq!(
+        kinds,
+        [
+            ParseWarningKind::BibliographyEntryDuplicate,
+            ParseWarningKind::BibliographyEntryDuplicate,
+        ],
+    );
+    assert!(output.ends_with("1. The first."), "{output}");
+    assert!(!output.contains("The second."), "{output}");
+
+    // Labels may be in any script, with text right after the closing "))"
+    let text = "См. ((bibcite Иванов)). И ((bibcite Петров)).\n\n\
+                [[bibliography]]\n\
+                : Иванов : Иванов, //Книга//.\n\
+                [[/bibliography]]";
+
+    let (output, warnings) =
+        fixtures::render_text(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    let kinds: Vec<_> = warnings.iter().map(|warning| warning.kind()).collect();
+    assert_eq!(kinds, [ParseWarningKind::NoSuchBibliographyEntry]);
+    assert!(output.starts_with("См. [1]. И [?]."), "{output}");
+}
+
+/// Callbacks with attached files and thumbnails, for attachment tests.
+#[derive(Debug)]
+struct AttachmentCallbacks;
+
+impl PageCallbacks for AttachmentCallbacks {
+    fn module_has_body(&self, module_name: Cow<str>) -> bool {
+        NullPageCallbacks {}.module_has_body(module_name)
+    }
+
+    fn render_module<'a>(
+        &self,
+        module_name: Cow<str>,
+        params: HashMap<Cow<str>, Cow<str>>,
+        body: Cow<str>,
+        mode: ModuleRenderMode,
+    ) -> Cow<'static, str> {
+        NullPageCallbacks {}.render_module(module_name, params, body, mode)
+    }
+
+    fn get_html_injected_code<'a>(&self, html_id: Cow<str>) -> Cow<'static, str> {
+        NullPageCallbacks {}.get_html_injected_code(html_id)
+    }
+
+    fn get_page_info<'a>(
+        &self,
+        page_refs: &Vec<PageRef<'a>>,
+    ) -> Vec<PartialPageInfo<'static>> {
+        NullPageCallbacks {}.get_page_info(page_refs)
+    }
+
+    fn get_user_info<'a>(&self, names: &[Cow<'a, str>]) -> Vec<UserInfo<'static>> {
+        NullPageCallbacks {}.get_user_info(names)
+    }
+
+    fn list_page_files<'a>(&self, page_ref: &PageRef<'a>) -> Vec<Cow<'static, str>> {
+        assert_eq!(page_ref.name(), "fixture");
+
+        vec![
+            Cow::Borrowed("zebra.png"),
+            Cow::Borrowed("notes.txt"),
+            Cow::Borrowed("apple.JPG"),
+        ]
+    }
+
+    fn get_file_info<'a>(&self, files: &[FileRef<'a>]) -> Vec<FileInfo<'static>> {
+        files
+            .iter()
+            .map(|file| match file.file.as_ref() {
+                "report.pdf" => FileInfo {
+                    file: file.to_owned(),
+                    size: Some(12345),
+                    mime_type: Some(Cow::Borrowed("application/pdf")),
+                    exists: true,
+                },
+                _ => FileInfo::missing(file),
+            })
+            .collect()
+    }
+
+    fn get_thumbnail_url<'a>(
+        &self,
+        image_url: Cow<str>,
+        size: GallerySize,
+    ) -> Option<Cow<'static, str>> {
+        Some(Cow::Owned(format!("{image_url}?size={}", size.name())))
+    }
+
+    fn evaluate_expression<'a>(&self, expression: Cow<str>) -> ExpressionResult<'static> {
+        NullPageCallbacks {}.evaluate_expression(expression)
+    }
+
+    fn normalize_page_name<'a>(&self, full_name: Cow<str>) -> Cow<'static, str> {
+        NullPageCallbacks {}.normalize_page_name(full_name)
+    }
+}
+
+#[test]
+fn gallery() {
+    let mut settings = fixtures::settings();
+
+    let render = |input: &str, settings: &WikitextSettings| {
+        let (output, warnings) =
+            fixtures::render_html(input, settings, Arc::new(AttachmentCallbacks));
+
+        assert!(warnings.is_empty(), "{warnings:#?}");
+        output.body
+    };
+
+    // Explicit items, with captions
+    let input = "[[gallery size=\"small\"]]\n\
+                 : cover.png | The cover\n\
+                 : https://example.com/photo.jpg\n\
+                 [[/gallery]]";
+
+    let html = render(input, &settings);
+    assert!(html.contains("wj-gallery-small"), "{html}");
+    assert!(
+        html.contains(
+            "src=\"//test.wjfiles.com/local--files/fixture/cover.png?size=small\""
+        ),
+        "{html}",
+    );
+    assert!(
+        html.contains("href=\"//test.wjfiles.com/local--files/fixture/cover.png\""),
+        "{html}",
+    );
+    assert!(html.contains("The cover</figcaption>"), "{html}");
+    assert!(html.contains("alt=\"photo.jpg\""), "{html}");
+
+    // Attached images, sorted by name
+    let html = render("[[gallery order=\"name\" viewer=\"false\"]]", &settings);
+    let apple = html.find("apple.JPG").expect("Missing attached image");
+    let zebra = html.find("zebra.png").expect("Missing attached image");
+    assert!(apple < zebra, "{html}");
+    assert!(!html.contains("notes.txt"), "{html}");
+    assert!(!html.contains("wj-gallery-link"), "{html}");
+
+    // A body-less gallery doesn't take a later gallery's end
+    let html = render(
+        &format!("[[gallery]]\n\nBetween the galleries.\n\n{input}"),
+        &settings,
+    );
+    assert_eq!(html.matches("<div class=\"wj-gallery").count(), 2, "{html}");
+    assert!(html.contains("Between the galleries."), "{html}");
+    let zebra = html.find("zebra.png").expect("Missing attached image");
+    let cover = html.find("cover.png").expect("Missing listed image");
+    assert!(zebra < cover, "{html}");
+
+    // Empty explicit list
+    let html = render("[[gallery]]\n[[/gallery]]", &settings);
+    assert!(!html.contains("zebra.png"), "{html}");
+
+    // Local files are dropped when not allowed
+    settings.allow_local_paths = false;
+
+    let html = render(input, &settings);
+    assert!(!html.contains("cover.png"), "{html}");
+    assert!(html.contains("https://example.com/photo.jpg"), "{html}");
+
+    let html = render("[[gallery]]", &settings);
+    assert!(!html.contains("wj-gallery"), "{html}");
+}
+
+#[test]
+fn file_links() {
+    let settings = fixtures::settings();
+
+    let text = "[[file report.pdf | The report]] and [[file other/gone.zip]].\n\
+                Also [/local--files/other/gone.zip an old link] and [[image photo.png]].";
+
+    let (tree, warnings) =
+        fixtures::parse(text, &settings, Arc::new(AttachmentCallbacks));
+
+    assert!(warnings.is_empty(), "{warnings:#?}");
+
+    // Every referenced attachment is recorded
+    let files = tree
+        .files
+        .iter()
+        .map(|file| file.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        files,
+        [
+            "fixture/report.pdf",
+            "other/gone.zip",
+            "other/gone.zip",
+            "fixture/photo.png",
+        ],
+    );
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(AttachmentCallbacks));
+
+    assert!(
+        output.body.contains(
+            "<a class=\"wj-file\" href=\"//test.wjfiles.com/local--files/fixture/report.pdf\"",
+        ),
+        "{}",
+        output.body,
+    );
+    assert!(
+        output.body.contains("The report</a> <span class=\"wj-file-info\">(12.1 kB, application/pdf)</span>"),
+        "{}",
+        output.body,
+    );
+    assert!(
+        output.body.contains("<a class=\"wj-file wj-file-missing\""),
+        "{}",
+        output.body,
+    );
+    assert!(output.body.contains(">gone.zip</a>"), "{}", output.body);
+    assert!(
+        output.body.contains(
+            "href=\"//test.wjfiles.com/local--files/other/gone.zip\" class=\"wj-file-missing\">an old link</a>",
+        ),
+        "{}",
+        output.body,
+    );
+
+    // Without local files, only the labels remain
+    let mut settings = settings.clone();
+    settings.allow_local_paths = false;
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(AttachmentCallbacks));
+
+    assert!(!output.body.contains("local--files"), "{}", output.body);
+    assert!(output.body.contains("The report"), "{}", output.body);
+    assert!(output.body.contains("an old link"), "{}", output.body);
+}
+
+#[test]
+fn embeds() {
+    let mut settings = fixtures::settings();
+
+    // A self-hosted provider, using <video> instead of an iframe
+    settings.embed.providers.push(EmbedProvider {
+        name: Cow::Borrowed("media"),
+        media: EmbedMedia::Video,
+        iframe: false,
+        patterns: vec![Cow::Borrowed("https://media.example.com/$$")],
+        embed_url: Cow::Borrowed("https://media.example.com/$$"),
+        link_url: Cow::Borrowed("https://media.example.com/$$"),
+    });
+
+    let text = "[[embed https://youtu.be/dQw4w9WgXcQ?t=10]]\n\n\
+                [[embedvideo https://media.example.com/clips/intro.mp4 width=\"640\"]]\n\n\
+                [[embedaudio https://www.youtube.com/watch?v=dQw4w9WgXcQ]]\n\n\
+                [[embed https://evil.example.com/\"onload=\"alert(1)]]";
+
+    let (output, warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    // YouTube is not audio, and the last URL has no provider
+    let not_allowed = warnings
+        .iter()
+        .filter(|warning| warning.kind() == ParseWarningKind::EmbedNotAllowed)
+        .count();
+    assert_eq!(not_allowed, 2, "{warnings:#?}");
+
+    assert!(
+        output.body.contains(
+            "<iframe src=\"https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ\" sandbox=",
+        ),
+        "{}",
+        output.body,
+    );
+    assert!(
+        output.body.contains(
+            "<video src=\"https://media.example.com/clips/intro.mp4\" width=\"640\" controls",
+        ),
+        "{}",
+        output.body,
+    );
+    assert_eq!(output.body.matches("<iframe").count(), 1, "{}", output.body);
+    assert!(!output.body.contains("<audio"), "{}", output.body);
+
+    let (text, _warnings) =
+        fixtures::render_text(text, &settings, Arc::new(NullPageCallbacks {}));
+    assert!(
+        text.starts_with(
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ\nhttps://media.example.com/clips/intro.mp4\n",
+        ),
+        "{text}",
+    );
+}
+
+#[test]
+fn form_controls() {
+    let settings = fixtures::settings();
+
+    let text = "[[*checkbox name=\"agree\" label=\"I agree\"]]\n\
+                [[checkbox name=\"news\" label=\"Newsletter\" rows=\"4\"]]\n\
+                [[radio color label=\"Red\" value=\"red\"]] [[*radio color label=\"Blue\" value=\"blue\"]]\n\
+                [[select name=\"size\" label=\"Size\" selected=\"m\"]]\n\
+                : s | Small\n\
+                : m | Medium\n\
+                [[/select]]\n\
+                [[textarea name=\"notes\" rows=\"4\" label=\"Notes\"]]\n\
+                <b>draft</b>\n\
+                [[/textarea]]\n\
+                [[button submit]] [[button reset label=\"Clear\"]]";
+
+    let (output, warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert!(warnings.is_empty(), "{warnings:#?}");
+
+    for expected in [
+        "<label class=\"wj-form-checkbox\"><input type=\"checkbox\" checked name=\"agree\"><span class=\"wj-form-label\">I agree</span></label>",
+        // Attributes the control doesn't use, like rows, are dropped
+        "<input type=\"checkbox\" name=\"news\">",
+        "<input type=\"radio\" name=\"color\" value=\"red\">",
+        "<input type=\"radio\" name=\"color\" checked value=\"blue\">",
+        "<select name=\"size\"><option value=\"s\">Small</option><option value=\"m\" selected>Medium</option></select>",
+        "<textarea name=\"notes\" rows=\"4\">&lt;b&gt;draft&lt;/b&gt;</textarea>",
+        "<button type=\"submit\" class=\"wj-form-button\">Submit</button>",
+        "<button type=\"reset\" class=\"wj-form-button\">Clear</button>",
+    ] {
+        assert!(output.body.contains(expected), "{}", output.body);
+    }
+
+    let (text, _warnings) =
+        fixtures::render_text(text, &settings, Arc::new(NullPageCallbacks {}));
+    assert!(
+        text.starts_with("[x] I agree\n[ ] Newsletter\n( ) Red (*) Blue"),
+        "{text}",
+    );
+}
+
+#[test]
+fn hidden_blocks() {
+    let settings = fixtures::settings();
+
+    let text = "Shown [[hidden]]secret[[/hidden]] and [[invisible]]ghost[[/invisible]] text.\n\n\
+                [[hidden class=\"notes\"]]\nHidden paragraph.\n[[/hidden]]\n\n\
+                [[invisible]]\nBlank\n[[/invisible]]\n\n\
+                End.";
+
+    let (output, warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert!(warnings.is_empty(), "{warnings:#?}");
+
+    for expected in [
+        "<p>Shown <span class=\"wj-hidden\">secret</span> and \
+         <span class=\"wj-invisible\">ghost</span> text.</p>",
+        "<div class=\"wj-hidden notes\"><p>Hidden paragraph.</p></div>",
+        "<div class=\"wj-invisible\"><p>Blank</p></div>",
+    ] {
+        assert!(output.body.contains(expected), "{}", output.body);
+    }
+
+    // Hidden text is dropped, invisible text keeps its space
+    let (text, _warnings) =
+        fixtures::render_text(text, &settings, Arc::new(NullPageCallbacks {}));
+    assert!(text.starts_with("Shown  and       text.\n"), "{text:?}");
+    assert!(
+        !text.contains("Hidden") && !text.contains("Blank"),
+        "{text:?}"
+    );
+    assert!(text.ends_with("End."), "{text:?}");
+}
+
+#[test]
+fn notes() {
+    let settings = fixtures::settings();
+
+    let text = "[[note type=\"warning\"]]\nBe **careful** here.\n[[/note]]\n\n\
+                [[note type=\"tip\" title=\"Did you know?\"]]\nNotes nest.\n[[/note]]\n\n\
+                [[note title=\"\"]]\nUntitled.\n[[/note]]";
+
+    let (tree, warnings) =
+        fixtures::parse(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert!(warnings.is_empty(), "{warnings:#?}");
+    assert!(matches!(
+        tree.elements[0],
+        Element::Note {
+            note_type: NoteType::Warning,
+            title: None,
+            ..
+        },
+    ));
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    for expected in [
+        "<aside class=\"wj-note wj-note-warning\" role=\"note\" data-note-type=\"warning\">\
+         <div class=\"wj-note-title\">Warning</div>\
+         <div class=\"wj-note-content\"><p>Be <strong>careful</strong> here.</p></div></aside>",
+        "<div class=\"wj-note-title\">Did you know?</div>",
+        "<aside class=\"wj-note wj-note-info\" role=\"note\" data-note-type=\"info\">\
+         <div class=\"wj-note-content\">",
+    ] {
+        assert!(output.body.contains(expected), "{}", output.body);
+    }
+
+    let (plain, _warnings) =
+        fixtures::render_text(text, &settings, Arc::new(NullPageCallbacks {}));
+    assert!(plain.contains("Warning:\nBe careful here."), "{plain:?}");
+    assert!(plain.contains("Did you know?\nNotes nest."), "{plain:?}");
+
+    // Default titles are localized
+    let mut page_info = fixtures::page_info();
+    page_info.language = Cow::Borrowed("ru");
+
+    let (tree, _warnings) =
+        fixtures::parse_page(text, &page_info, &settings, Arc::new(NullPageCallbacks {}));
+    let output =
+        HtmlRender.render(&tree, &page_info, Arc::new(NullPageCallbacks {}), &settings);
+    assert!(output.body.contains("Предупреждение"), "{}", output.body);
+}
+
+#[test]
+fn table_spans() {
+    let settings = fixtures::settings();
+
+    let text = "||~ Name ||~ Value ||\n\
+                || alpha || 1 ||\n\
+                || ^ || 22 ||\n\
+                || beta || 3 ||\n\
+                \n\
+                [[table caption=\"Totals\"]]\n\
+                [[row section=\"foot\"]]\n\
+                [[cell]]Sum[[/cell]]\n\
+                [[/row]]\n\
+                [[row]]\n\
+                [[cell rowspan=\"5\"]]X[[/cell]]\n\
+                [[/row]]\n\
+                [[/table]]";
+
+    let (output, warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert!(warnings.is_empty(), "{warnings:#?}");
+
+    for expected in [
+        "<thead><tr><th scope=\"col\">Name</th><th scope=\"col\">Value</th></tr></thead>",
+        "<tbody><tr><td rowspan=\"2\">alpha</td><td>1</td></tr><tr><td>22</td></tr>",
+        // Sections are ordered, and spans are clamped to the table
+        "<table><caption>Totals</caption><tbody><tr><td>X</td></tr></tbody>\
+         <tfoot><tr><td>Sum</td></tr></tfoot></table>",
+    ] {
+        assert!(output.body.contains(expected), "{}", output.body);
+    }
+
+    let renderer = ReadableTextRender::new(None);
+    let (output, _warnings) =
+        fixtures::render(&renderer, text, &settings, Arc::new(NullPageCallbacks {}));
+    let lines: Vec<_> = output.lines().collect();
+
+    assert_eq!(
+        &lines[..5],
+        [
+            "Name  | Value",
+            "------+------",
+            "alpha | 1",
+            "      | 22",
+            "beta  | 3",
+        ],
+    );
+
+    // Plain text keeps an empty column under row spans
+    let (output, _warnings) =
+        fixtures::render(&TextRender, text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert!(
+        output.starts_with("Name\tValue\nalpha\t1\n\t22\nbeta\t3\n"),
+        "{output:?}",
+    );
+
+    // Rows made only of continuations are dropped
+    let (output, warnings) = fixtures::render_html(
+        "|| alpha || 1 ||\n|| ^ || ^ ||\n|| beta || 2 ||",
+        &settings,
+        Arc::new(NullPageCallbacks {}),
+    );
+
+    assert!(warnings.is_empty(), "{warnings:#?}");
+    assert!(!output.body.contains("<tr></tr>"), "{}", output.body);
+    assert!(
+        output.body.contains(
+            "<tr><td>alpha</td><td>1</td></tr><tr><td>beta</td><td>2</td></tr>",
+        ),
+        "{}",
+        output.body,
+    );
+
+    let (output, _warnings) = fixtures::render(
+        &TextRender,
+        "|| w || A ||\n|| x || B ||\n|| ^ || C ||\n|| D || ^ ||",
+        &settings,
+        Arc::new(NullPageCallbacks {}),
+    );
+
+    assert_eq!(output.trim_end_matches('\n'), "w\tA\nx\tB\n\tC\nD\t");
+
+    // Row spans must be at least one
+    let (_tree, warnings) = fixtures::parse(
+        "[[table]]\n[[row]]\n[[cell rowspan=\"0\"]]X[[/cell]]\n[[/row]]\n[[/table]]",
+        &settings,
+        Arc::new(NullPageCallbacks {}),
+    );
+
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning.kind() == ParseWarningKind::BlockMalformedArguments),
+        "{warnings:#?}",
+    );
+}
+
+#[test]
+fn table_span_limits() {
+    let settings = fixtures::settings();
+
+    // Spans are capped when parsed, rather than allocating every column
+    let text = "[[table sortable=\"true\"]]\n\
+                [[row]]\n\
+                [[cell colspan=\"4000000000\" rowspan=\"4000000000\"]]Wide[[/cell]]\n\
+                [[/row]]\n\
+                [[row]]\n\
+                [[cell]]Narrow[[/cell]]\n\
+                [[/row]]\n\
+                [[/table]]";
+
+    let (output, warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert!(warnings.is_empty(), "{warnings:#?}");
+    assert!(
+        output
+            .body
+            .contains("<td colspan=\"1000\" rowspan=\"2\">Wide</td>"),
+        "{}",
+        output.body,
+    );
+
+    // Text renderers lay out every column, so they rely on the same cap
+    let text = "[[table]]\n\
+                [[row]]\n\
+                [[cell colspan=\"4000000000\" rowspan=\"2\"]]Wide[[/cell]]\n\
+                [[/row]]\n\
+                [[row]]\n\
+                [[cell]]Narrow[[/cell]]\n\
+                [[/row]]\n\
+                [[/table]]";
+
+    let (output, _warnings) =
+        fixtures::render(&TextRender, text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert_eq!(output, "Wide\n\tNarrow");
+
+    let renderer = ReadableTextRender::new(None);
+    let (output, _warnings) =
+        fixtures::render(&renderer, text, &settings, Arc::new(NullPageCallbacks {}));
+    let lines: Vec<_> = output.lines().collect();
+
+    assert_eq!(lines[0].trim_end(), "Wide", "{output}");
+    assert_eq!(lines[1].trim(), "| Narrow", "{output}");
+}
+
+#[test]
+fn sortable_tables() {
+    let settings = fixtures::settings();
+
+    let text = "||~ Name ||~[number] Score ||~[date] Added ||~[rating] Rating ||\n\
+                || alpha || 1,024.5 || 2020-05-01 || +25 ||\n\
+                || beta || lots || [[date 1398763929 format=\"%B %d\"]] || -4 ||\n\
+                \n\
+                [[table types=\"text, number\"]]\n\
+                [[row]]\n\
+                [[hcell]]A[[/hcell]]\n\
+                [[hcell]]B[[/hcell]]\n\
+                [[/row]]\n\
+                [[row]]\n\
+                [[cell]]x[[/cell]]\n\
+                [[cell]]**12**[[/cell]]\n\
+                [[/row]]\n\
+                [[/table]]";
+
+    let (tree, warnings) =
+        fixtures::parse(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    // Only "lots" isn't a number
+    let kinds: Vec<_> = warnings.iter().map(|warning| warning.kind()).collect();
+    assert_eq!(kinds, [ParseWarningKind::TableCellTypeMismatch]);
+
+    match &tree.elements[0] {
+        Element::Table(table) => {
+            assert!(table.sortable);
+            assert_eq!(
+                table.column_types,
+                [
+                    ColumnType::Text,
+                    ColumnType::Number,
+                    ColumnType::Date,
+                    ColumnType::Rating,
+                ],
+            );
+        }
+        element => panic!("Not a table: {element:?}"),
+    }
+
+    let (output, _warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    for expected in [
+        "<table data-sortable=\"true\" class=\"wiki-content-table\">",
+        "<th scope=\"col\" data-sort-type=\"number\">Score</th>",
+        "<td data-sort-value=\"1024.5\">1,024.5</td>",
+        "<td data-sort-value=\"1588291200\">2020-05-01</td>",
+        "<td data-sort-value=\"25\">+25</td>",
+        "<td>lots</td>",
+        // Formatted dates still sort by their value
+        "<td data-sort-value=\"1398763929\"><span class=\"odate",
+        "<th scope=\"col\" data-sort-type=\"text\">A</th>",
+        "<td data-sort-value=\"12\"><strong>12</strong></td>",
+    ] {
+        assert!(output.body.contains(expected), "{}", output.body);
+    }
+}
+
+#[test]
+fn sortable_table_numbers() {
+    let settings = fixtures::settings();
+
+    let sort_values = |language: &'static str| {
+        let mut page_info = fixtures::page_info();
+        page_info.language = Cow::Borrowed(language);
+
+        let (tree, warnings) = fixtures::parse_page(
+            "||~[number] Value ||\n\
+             || 1,5 ||\n\
+             || 1 234 ||\n\
+             || 1\u{a0}234,75 ||\n\
+             || 1,024.5 ||",
+            &page_info,
+            &settings,
+            Arc::new(NullPageCallbacks {}),
+        );
+
+        let table = match &tree.elements[0] {
+            Element::Table(table) => table,
+            element => panic!("Not a table: {element:?}"),
+        };
+
+        let values: Vec<_> = table.rows[1..]
+            .iter()
+            .map(|row| row.cells[0].sort_value.as_deref().map(String::from))
+            .collect();
+
+        (values, warnings.len())
+    };
+
+    // Decimal point, with commas or spaces between groups of digits
+    assert_eq!(
+        sort_values("en"),
+        (
+            vec![
+                None,
+                Some(String::from("1234")),
+                None,
+                Some(String::from("1024.5")),
+            ],
+            2,
+        ),
+    );
+
+    // Decimal comma, with spaces between groups of digits
+    assert_eq!(
+        sort_values("ru"),
+        (
+            vec![
+                Some(String::from("1.5")),
+                Some(String::from("1234")),
+                Some(String::from("1234.75")),
+                None,
+            ],
+            1,
+        ),
+    );
+}
+
+#[test]
+fn numbered_lists() {
+    let settings = fixtures::settings();
+
+    let text = "# First\n\
+                # Second\n\
+                \n\
+                [[code]]\nsample\n[[/code]]\n\
+                \n\
+                #+ Third\n\
+                # Fourth\n\
+                \n\
+                [[ol start=\"5\" type=\"i\" reversed=\"true\"]]\n\
+                [[li]]Five[[/li]]\n\
+                [[li]]Four[[/li]]\n\
+                [[/ol]]";
+
+    let (output, warnings) =
+        fixtures::render_html(text, &settings, Arc::new(NullPageCallbacks {}));
+
+    assert!(warnings.is_empty(), "{warnings:#?}");
+
+    for expected in [
+        "<ol><li>First</li><li>Second</li></ol>",
+        "<ol start=\"3\"><li>Third</li><li>Fourth</li></ol>",
+        "<ol start=\"5\" type=\"i\" reversed><li>Five</li><li>Four</li></ol>",
+    ] {
+        assert!(output.body.contains(expected), "{}", output.body);
+    }
+
+    let (text, _warnings) =
+        fixtures::render_text(text, &settings, Arc::new(NullPageCallbacks {}));
+    for expected in [
+        " 2. Second",
+        " 3. Third",
+        " 4. Fourth",
+        " v. Five",
+        " iv. Four",
+    ] {
+        assert!(text.contains(expected), "{text:?}");
+    }
+
+    // Numbering types are validated
+    let (_tree, warnings) = fixtures::parse(
+        "[[ol type=\"x\"]]\n[[li]]Item[[/li]]\n[[/ol]]",
+        &settings,
+        Arc::new(NullPageCallbacks {}),
+    );
+
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning.kind() == ParseWarningKind::BlockMalformedArguments),
+        "{warnings:#?}",
+    );
+}
+
+/// An includer which counts how many pages it was asked for.
+///
+/// The page `missing` doesn't exist, all others have a line of text.
+#[derive(Debug, Clone, Default)]
+struct CountingIncluder {
+    fetches: Arc<AtomicUsize>,
+}
+
+impl<'t> Includer<'t> for CountingIncluder {
+    type Error = Infallible;
+
+    fn include_pages(
+        &mut self,
+        includes: &[IncludeRef<'t>],
+    ) -> Result<Vec<FetchedPage<'t>>, Infallible> {
+        self.fetches.fetch_add(includes.len(), Ordering::SeqCst);
+
+        Ok(includes
+            .iter()
+            .map(|include| {
+                let page_ref = include.page_ref();
+                let content = match page_ref.name() {
+                    "missing" => None,
+                    name => Some(Cow::Owned(format!("Included {name}."))),
+                };
+
+                FetchedPage {
+                    page_ref: page_ref.clone(),
+                    content,
+                }
+            })
+            .collect())
+    }
+
+    fn no_such_include(
+        &mut self,
+        page_ref: &PageRef<'t>,
+    ) -> Result<Cow<'t, str>, Infallible> {
+        Ok(Cow::Owned(format!("Missing page: {page_ref}")))
+    }
+}
+
+#[test]
+fn include_cache() {
+    let cache = IncludeCache::new();
+    let includer = CountingIncluder::default();
+    let includes = [
+        IncludeRef::page_only(PageRef::new1("shared")),
+        IncludeRef::page_only(PageRef::new1("missing")),
+    ];
+
+    let first = CachedIncluder::new(&cache, includer.clone())
+        .include_pages(&includes)
+        .unwrap_or_else(|error| match error {});
+    assert_eq!(includer.fetches.load(Ordering::SeqCst), 2);
+    assert_eq!(cache.len(), 2);
+
+    // Another page's includer is served from the cache,
+    // including the page which doesn't exist
+    let second = CachedIncluder::new(&cache, includer.clone())
+        .include_pages(&includes)
+        .unwrap_or_else(|error| match error {});
+    assert_eq!(includer.fetches.load(Ordering::SeqCst), 2);
+    assert_eq!(first, second);
+    assert_eq!(second[0].content.as_deref(), Some("Included shared."));
+    assert_eq!(second[1].content, None);
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn batch_rendering() {
+    let settings = fixtures::settings();
+    let pages: Vec<_> = (0..12)
+        .map(|index| BatchPage {
+            page_info: PageInfo {
+                page: Cow::Owned(format!("page-{index}")),
+                ..fixtures::page_info()
+            },
+            text: format!("Page {index}.\n\n[[include shared]]\n\n[[include missing]]",),
+        })
+        .collect();
+
+    let render = |workers: usize| {
+        let cache = IncludeCache::new();
+        let includer = CountingIncluder::default();
+        let outputs = render_batch(
+            &TextRender,
+            &pages,
+            Arc::new(NullPageCallbacks {}),
+            &settings,
+            &cache,
+            || includer.clone(),
+            NonZeroUsize::new(workers).unwrap(),
+        );
+
+        (outputs, includer.fetches.load(Ordering::SeqCst))
+    };
+
+    // Each included page is fetched once, regardless of how many pages include it
+    let (single, fetches) = render(1);
+    assert_eq!(fetches, 2);
+
+    for (index, output) in single.iter().enumerate() {
+        assert_eq!(
+            output,
+            &format!("Page {index}.\n\nIncluded shared.\n\nMissing page: missing"),
+        );
+    }
+
+    // The outputs are in the same order as the pages
+    let (multiple, _) = render(4);
+    assert_eq!(single, multiple);
+}
+
+/// A byte sink which fails once it has been given a certain amount.
+#[derive(Debug)]
+struct FailingSink {
+    written: Vec<u8>,
+    capacity: usize,
+}
+
+impl io::Write for FailingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written.len() + buf.len() > self.capacity {
+            return Err(io::Error::other("sink is full"));
+        }
+
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn streaming_html() {
+    let page_info = fixtures::page_info();
+    let settings = fixtures::settings();
+
+    let text = vec![fixtures::scp_article(); 4].join("\n\n");
+    let (tree, _warnings) =
+        fixtures::parse(&text, &settings, Arc::new(NullPageCallbacks {}));
+
+    let output =
+        HtmlRender.render(&tree, &page_info, Arc::new(NullPageCallbacks {}), &settings);
+
+    // Large enough to be flushed several times
+    assert!(output.body.len() > 4 * 8 * 1024, "{}", output.body.len());
+
+    let mut body = String::new();
+    let streamed = HtmlRender
+        .render_to_writer(
+            &tree,
+            &page_info,
+            Arc::new(NullPageCallbacks {}),
+            &settings,
+            &mut body,
+        )
+        .expect("Writing to a string failed");
+    assert!(body == output.body, "Streamed body differs");
+    assert_eq!(streamed.meta.len(), output.meta.len());
+    assert_eq!(streamed.json_ld, output.json_ld);
+
+    let mut bytes = Vec::new();
+    HtmlRender
+        .render_to_io_writer(
+            &tree,
+            &page_info,
+            Arc::new(NullPageCallbacks {}),
+            &settings,
+            &mut bytes,
+        )
+        .expect("Writing to a vector failed");
+    assert!(bytes == output.body.as_bytes(), "Streamed bytes differ");
+
+    // Errors from the sink are returned, not ignored
+    let mut sink = FailingSink {
+        written: Vec::new(),
+        capacity: 12 * 1024,
+    };
+    let error = HtmlRender
+        .render_to_io_writer(
+            &tree,
+            &page_info,
+            Arc::new(NullPageCallbacks {}),
+            &settings,
+            &mut sink,
+        )
+        .expect_err("Writing to a full sink succeeded");
+    assert_eq!(error.to_string(), "sink is full");
+    assert!(output.body.as_bytes().starts_with(&sink.written));
+}
@@ -322,7 +322,7 @@ def get_post_info(
         has_revisions = post_content[2] > 1
 
         content = highlight_mentions(
-            renderer.single_pass_render(post_content[0], RenderContext(None, None, {}, context.user), 'message'),
+            renderer.single_pass_render(post_content[0], RenderContext(None, None, {}, context.user), 'message', f'post-{post.id}'),
             usernames
         )
         render_post = {
@@ -74,7 +74,7 @@ def get_post_info(context, posts, category_for_comments, usernames: set[str]=set
             author_mark = 'Автор статьи' if is_article_author else ''
         
         content = highlight_mentions(
-            renderer.single_pass_render(post_contents.get(post.id, ('', None))[0], RenderContext(None, None, {}, context.user), 'message'),
+            renderer.single_pass_render(post_contents.get(post.id, ('', None))[0], RenderContext(None, None, {}, context.user), 'message', f'post-{post.id}'),
             usernames
         )
         reaction_state = None if hide_reactions else forum_reactions.serialize_post_reaction_state(post, context.user, reaction_context)
@@ -2,7 +2,9 @@ import re
 from typing import Optional
 import logging
 
+from django.utils.html import strip_tags
 from django.utils.safestring import SafeString
+from html import unescape
 
 import modules
 from web.models.users import User
@@ -22,6 +24,13 @@ from renderer.templates import apply_template
 MAX_INCLUDE_LEVEL = 25
 
 
+def _html_to_text(source: str) -> str:
+    # Block-level tags start new lines, everything else is flattened into text.
+    source = re.sub(r'<br\s*/?>|</(p|div|li|tr|h[1-6])>', '\n', source, flags=re.IGNORECASE)
+    lines = (' '.join(line.split()) for line in unescape(strip_tags(source)).splitlines())
+    return '\n'.join(line for line in lines if line)
+
+
 def callbacks_with_context(context):
     from ftml import ftml
 
@@ -33,42 +42,62 @@ def callbacks_with_context(context):
         def module_has_body(self, module_name: str) -> bool:
             return modules.module_has_content(module_name.lower())
 
-        def render_module(self, module_name: str, params: dict[str, str], body: str) -> str:
+        def render_module(self, module_name: str, params: dict[str, str], body: str, mode: str = 'html') -> str:
             params_for_module = {key.lower(): value for (key, value) in params.items()}
             try:
-                return modules.render_module(module_name, self.context, params_for_module, content=body)
+                rendered = modules.render_module(module_name, self.context, params_for_module, content=body)
             except modules.ModuleError as e:
+                if mode == 'text':
+                    return e.message
                 return render_template_from_string('<div class="error-block"><p>{{error}}</p></div>', error=e.message)
+            if mode == 'text':
+                return _html_to_text(rendered)
+            return rendered
 
-        def render_user(self, username: str, avatar: bool) -> str:
-            try:
+        def fetch_users(self, usernames: list[str]) -> list[ftml.UserInfo]: # type: ignore
+            from django.conf import settings
+            from web.controllers import articles
+
+            local_names = [x for x in usernames if not x.lower().startswith(('external:', 'wd:'))]
+            wikidot_names = [x[3:] for x in usernames if x.lower().startswith('wd:')]
+            local_users = {u.username: u for u in User.objects.filter(username__in=local_names)}
+            wikidot_users = {
+                u.wikidot_username: u
+                for u in User.objects.filter(type=User.UserType.Wikidot, wikidot_username__in=wikidot_names)
+            }
+            result = []
+            for username in usernames:
                 if username.lower().startswith('external:'):
-                    user = username[len('external:'):]
-                    return render_external_user_to_html(user, avatar=avatar)
+                    name = username[len('external:'):]
+                    result.append(ftml.UserInfo(
+                        name=username,
+                        display_name=name,
+                        profile_url='https://www.wikidot.com/user:info/%s' % articles.normalize_article_name(name),
+                        avatar_url=settings.WIKIDOT_AVATAR,
+                        exists=True
+                    ))
+                    continue
                 if username.lower().startswith('wd:'):
-                    user = User.objects.get(type=User.UserType.Wikidot, wikidot_username=username[3:])
+                    user = wikidot_users.get(username[3:])
                 else:
-                    user = User.objects.get(username=username)
-                return render_user_to_html(user, avatar=avatar)
-            except User.DoesNotExist:
-                return render_template_from_string(
-                    '<span class="error-inline">Пользователь \'{{username}}\' не существует</span>',
-                    username=username
-                )
-
-        def get_i18n_message(self, message_id: str) -> str:
-            messages = {
-                "button-copy-clipboard": "Скопировать",
-                "collapsible-open": "+ открыть блок",
-                "collapsible-hide": "- закрыть блок",
-                "table-of-contents": "Содержание",
-                "toc-open": "Раскрыть",
-                "toc-close": "Свернуть",
-                "footnote": "Сноска",
-                "footnote-block-title": "Сноски",
-                "image-context-bad": "Некорректный адрес изображения",
-            }
-            return messages.get(message_id, '?')
+                    user = local_users.get(username)
+                if user is None:
+                    continue
+                if user.type == User.UserType.Wikidot:
+                    display_name = 'wd:' + user.wikidot_username
+                    avatar = settings.WIKIDOT_AVATAR
+                else:
+                    display_name = user.username
+                    avatar = user.get_avatar(default=settings.DEFAULT_AVATAR)
+                result.append(ftml.UserInfo(
+                    name=username,
+                    id=user.pk,
+                    display_name=display_name,
+                    profile_url='/-/users/%d-%s' % (user.pk, user.username),
+                    avatar_url=avatar,
+                    exists=True
+                ))
+            return result
 
         @staticmethod
         def get_html_injected_code(html_id: str) -> str:
@@ -138,6 +167,22 @@ def callbacks_with_context(context):
                     result.append(ftml.PartialPageInfo(full_name=ref, exists=True, title=page_map[ref_dumb].title))
             return result
 
+        def fetch_files(self, files: list[tuple[str, str]]) -> list[ftml.FileInfo]: # type: ignore
+            from web.controllers.articles import get_files_in_article
+
+            result = []
+            for page, name in files:
+                file = get_files_in_article(page).filter(name=name).first()
+                if file:
+                    result.append(ftml.FileInfo(page=page, file=name, size=file.size, mime_type=file.mime_type, exists=True))
+                else:
+                    result.append(ftml.FileInfo(page=page, file=name, exists=False))
+            return result
+
+        def list_page_files(self, full_name: str) -> list[str]:
+            from web.controllers.articles import get_files_in_article
+            return [file.name for file in get_files_in_article(full_name).order_by('created_at')]
+
         def evaluate_expression(self, expr: str):
             result = expression.evaluate_expression(expr)
             return result
@@ -158,6 +203,7 @@ def callbacks_with_context(context):
 
 def page_info_from_context(context: RenderContext):
     from ftml import ftml
+    from django.conf import settings
 
     site = get_current_site()
 
@@ -179,7 +225,8 @@ def page_info_from_context(context: RenderContext):
         site=site.slug,
         domain=site.domain,
         media_domain=site.media_domain,
-        tags=tags
+        tags=tags,
+        language=settings.LANGUAGE_CODE
     )
 
 
@@ -207,14 +254,14 @@ def _format_internal_error(context: RenderContext, mode) -> str:
     return f"Failed to {action} page '{context.article.full_name}'"
 
 
-def single_pass_render(source, context: RenderContext, mode='article') -> str:
+def single_pass_render(source, context: RenderContext, mode='article', id_namespace: Optional[str] = None) -> str:
     try:
         from ftml import ftml
 
         with threadvars.context():
             page_vars = get_page_vars(context.article) if context else {}
             source = apply_template(source, lambda param: get_this_page_params(page_vars, param))
-            html = ftml.render_html(source, callbacks_with_context(context), page_info_from_context(context), mode)
+            html = ftml.render_html(source, callbacks_with_context(context), page_info_from_context(context), mode, None, id_namespace)
             return SafeString(html.body)
     except (GeneratorExit, KeyboardInterrupt, SystemExit):
         raise
@@ -236,14 +283,11 @@ def single_pass_render_with_excerpt(source, context: RenderContext, mode='articl
         with threadvars.context():
             html = ftml.render_html(source, callbacks_with_context(context), page_info_from_context(context), mode)
         with threadvars.context():
-            text = ftml.render_text(source, callbacks_with_context(context), page_info_from_context(context), mode).body
+            text = ftml.render_excerpt(source, callbacks_with_context(context), page_info_from_context(context), mode, 384, None).text
 
-        text = '\n'.join([x.strip() for x in text.split('\n')])
-        text = re.sub(r'\n+', '\n', text)
-        if len(text) > 384:
-            text = text[:384] + '...'
+        image = next((value for _, name, value in html.meta if name == 'og:image'), None)
 
-        return SafeString(html.body), text, None
+        return SafeString(html.body), text, image
     except (GeneratorExit, KeyboardInterrupt, SystemExit):
         raise
     except BaseException as e: